@@ -0,0 +1,20 @@
+mod antichain;
+mod components;
+mod depth;
+mod descendants;
+mod dominators;
+mod matching;
+mod orders;
+mod path_cover;
+mod paths;
+#[cfg(feature = "rand")]
+mod random;
+mod reachability;
+mod series_parallel;
+mod shape;
+mod topological;
+
+pub use dominators::{Dominators, DominatorsIter};
+pub use orders::TopologicalOrders;
+pub use reachability::ReachabilityIndex;
+pub use series_parallel::SeriesParallel;
@@ -0,0 +1,97 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use super::matching::maximum_matching;
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the width of `Dag`, which is the size of the largest set of nodes
+    /// where no node reaches another
+    /// # Remarks
+    /// * It is the maximum count of nodes which can run in parallel
+    /// * By Dilworth's theorem, it is the count of nodes minus a maximum matching
+    ///   in the transitive closure
+    pub fn width(&self) -> usize {
+        let (ids, closure) = self.closure();
+        let matched = maximum_matching(&closure, ids.len());
+        ids.len() - matched.iter().flatten().count()
+    }
+
+    /// Get a largest set of nodes where no node reaches another
+    /// # Returns
+    /// Return the ids in the order of `NodeId`, its length is [`Dag::width`]
+    /// # Remarks
+    /// * It is built from a minimum vertex cover of the matching by König's theorem
+    pub fn maximum_antichain(&self) -> Vec<NodeId> {
+        let (ids, closure) = self.closure();
+        let len = ids.len();
+        let matched = maximum_matching(&closure, len);
+        let mut matched_left = vec![false; len];
+        for left in matched.iter().flatten() {
+            matched_left[*left] = true;
+        }
+        // vertices reached by alternating paths from unmatched left vertices
+        let mut reached_left = vec![false; len];
+        let mut reached_right = vec![false; len];
+        let mut queue = (0..len)
+            .filter(|left| !matched_left[*left])
+            .collect::<VecDeque<_>>();
+        for left in &queue {
+            reached_left[*left] = true;
+        }
+        while let Some(left) = queue.pop_front() {
+            for right in &closure[left] {
+                if reached_right[*right] {
+                    continue;
+                }
+                reached_right[*right] = true;
+                if let Some(next) = matched[*right] {
+                    if !reached_left[next] {
+                        reached_left[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        // nodes whose both sides are out of the vertex cover
+        ids.into_iter()
+            .enumerate()
+            .filter(|(index, _)| reached_left[*index] && !reached_right[*index])
+            .map(|(_, id)| id)
+            .collect()
+    }
+
+    /// Get all the ids with their descendants by dense indices
+    fn closure(&self) -> (Vec<NodeId>, Vec<Vec<usize>>) {
+        let (ids, children) = self.dense_children();
+        let len = ids.len();
+        let mut reach = vec![vec![false; len]; len];
+        // children first
+        for node_id in self.topological_order().into_iter().rev() {
+            let node = ids
+                .binary_search(&node_id)
+                .unwrap_or_else(|_| unreachable!("ids are all the nodes"));
+            for child in &children[node] {
+                let child_reach = reach[*child].clone();
+                reach[node][*child] = true;
+                for (node_bit, child_bit) in reach[node].iter_mut().zip(child_reach) {
+                    *node_bit |= child_bit;
+                }
+            }
+        }
+        let closure = reach
+            .into_iter()
+            .map(|reach| {
+                reach
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, bit)| *bit)
+                    .map(|(index, _)| index)
+                    .collect()
+            })
+            .collect();
+        (ids, closure)
+    }
+}
@@ -0,0 +1,36 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the groups of nodes connected ignoring the direction of edges
+    /// # Returns
+    /// Every group is sorted, groups are in the order of their smallest ids
+    /// # Remarks
+    /// * Nodes in different groups never reach each other, so groups can be processed independently
+    pub fn weakly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut visited = BTreeSet::new();
+        let mut components = Vec::new();
+        for node_id in self.nodes.keys() {
+            if visited.contains(node_id) {
+                continue;
+            }
+            let mut component = Vec::new();
+            let mut stack = Vec::from([node_id.clone()]);
+            while let Some(top) = stack.pop() {
+                if !visited.contains(&top) {
+                    stack.extend(self.children(&top).map(|(id, _)| id));
+                    stack.extend(self.parents(&top));
+                    visited.insert(top.clone());
+                    component.push(top);
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+}
@@ -0,0 +1,117 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::borrow::Borrow;
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the longest distance from any root to `node_id`
+    /// # Returns
+    /// * Return `Some(0)` when `node_id` is a root
+    /// * Return `None` when `node_id` is NOT found in `Dag`
+    pub fn depth<Q>(&self, node_id: &Q) -> Option<usize>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (node_id, _) = self.nodes.get_key_value(node_id)?;
+        // roots are the farthest, which come first in the topological order
+        Some(self.longest(node_id, false, |dag, id| {
+            dag.parents::<NodeId>(id).collect()
+        }))
+    }
+
+    /// Get the longest distance from `node_id` to any leaf
+    /// # Returns
+    /// * Return `Some(0)` when `node_id` is a leaf
+    /// * Return `None` when `node_id` is NOT found in `Dag`
+    pub fn height<Q>(&self, node_id: &Q) -> Option<usize>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (node_id, _) = self.nodes.get_key_value(node_id)?;
+        // leaves are the farthest, which come last in the topological order
+        Some(self.longest(node_id, true, |dag, id| {
+            dag.children::<NodeId>(id).map(|(id, _)| id).collect()
+        }))
+    }
+
+    /// Get the longest-path layering of all the nodes
+    #[deprecated(note = "use `Dag::depth_map`, which is the same map")]
+    pub fn layers(&self) -> BTreeMap<NodeId, usize> {
+        self.depth_map()
+    }
+
+    /// Get [`Dag::depth`] of every node, which is the longest-path layering
+    /// # Remarks
+    /// * Roots are in layer 0, every edge goes from a lower layer to a higher one
+    /// * All the depths are computed in one pass over the nodes and edges
+    #[doc(alias = "layers")]
+    pub fn depth_map(&self) -> BTreeMap<NodeId, usize> {
+        let mut depths = BTreeMap::new();
+        for node_id in self.topological_order() {
+            let depth = self
+                .parents(&node_id)
+                .map(|parent_id| depths[&parent_id] + 1)
+                .max()
+                .unwrap_or(0);
+            depths.insert(node_id, depth);
+        }
+        depths
+    }
+
+    /// Get [`Dag::height`] of every node
+    /// # Remarks
+    /// * All the heights are computed in one pass over the nodes and edges
+    pub fn height_map(&self) -> BTreeMap<NodeId, usize> {
+        let mut heights = BTreeMap::new();
+        for node_id in self.topological_order().into_iter().rev() {
+            let height = self
+                .children(&node_id)
+                .map(|(child_id, _)| heights[&child_id] + 1)
+                .max()
+                .unwrap_or(0);
+            heights.insert(node_id, height);
+        }
+        heights
+    }
+
+    /// Longest distance from `node_id` along `next`, by DP over the nodes reachable by `next`
+    /// in the topological order, which is reversed by `reverse`
+    fn longest<F>(&self, node_id: &NodeId, reverse: bool, next: F) -> usize
+    where
+        F: Fn(&Self, &NodeId) -> Vec<NodeId>,
+    {
+        // all the nodes reachable from node_id
+        let mut reached = BTreeSet::new();
+        let mut stack = Vec::from([node_id.clone()]);
+        while let Some(top) = stack.pop() {
+            if !reached.contains(&top) {
+                stack.extend(next(self, &top));
+                reached.insert(top);
+            }
+        }
+        // farthest nodes first, so `next` of a node is always computed before it
+        let mut reached = reached.into_iter().collect::<Vec<_>>();
+        reached.sort_by_key(|id| self.order_of(id));
+        if reverse {
+            reached.reverse();
+        }
+        let mut distances = BTreeMap::new();
+        for id in reached {
+            let distance = next(self, &id)
+                .iter()
+                .map(|next_id| distances[next_id] + 1)
+                .max()
+                .unwrap_or(0);
+            distances.insert(id, distance);
+        }
+        distances[node_id]
+    }
+}
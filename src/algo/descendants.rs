@@ -0,0 +1,125 @@
+use alloc::{
+    borrow::ToOwned,
+    collections::{BTreeSet, VecDeque},
+    vec::Vec,
+};
+use core::borrow::Borrow;
+
+use crate::{Dag, NodeNotFound};
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get an iterator of all the descendants of `node_id` at most `max_depth` edges away,
+    /// as `(depth, id)`
+    /// # Remarks
+    /// * It is a breadth-first search, `depth` is the shortest distance from `node_id`
+    /// * Every descendant is yielded once, `node_id` itself is excluded
+    /// * Nodes beyond `max_depth` are never visited
+    pub fn descendants_within<Q>(
+        &self,
+        node_id: &Q,
+        max_depth: usize,
+    ) -> impl Iterator<Item = (usize, NodeId)> + '_
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        if let Some((node_id, _)) = self.nodes.get_key_value(node_id) {
+            visited.insert(node_id.clone());
+            queue.push_back((0, node_id.clone()));
+        }
+        core::iter::from_fn(move || loop {
+            let (depth, node_id) = queue.pop_front()?;
+            if depth < max_depth {
+                for (child_id, _) in self.children::<NodeId>(&node_id) {
+                    if !visited.contains::<NodeId>(&child_id) {
+                        visited.insert(child_id.clone());
+                        queue.push_back((depth + 1, child_id));
+                    }
+                }
+            }
+            if depth > 0 {
+                return Some((depth, node_id));
+            }
+        })
+    }
+
+    /// Fold all the descendants of `node_id` by `f(acc,id,data)`, starting from `init`
+    /// # Remarks
+    /// * Every descendant is visited exactly once, `node_id` itself is excluded
+    /// * The order of visiting is NOT specified
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `node_id` is NOT found in `Dag`
+    pub fn fold_descendants<Q, B, F>(
+        &self,
+        node_id: &Q,
+        init: B,
+        f: F,
+    ) -> Result<B, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+        F: FnMut(B, NodeId, &NodeData) -> B,
+    {
+        let (node_id, _) = self
+            .nodes
+            .get_key_value(node_id)
+            .ok_or_else(|| NodeNotFound(node_id.to_owned()))?;
+        Ok(self.fold_reachable(node_id, init, f, |dag, id| {
+            dag.children::<NodeId>(id).map(|(id, _)| id).collect()
+        }))
+    }
+
+    /// Fold all the ancestors of `node_id` by `f(acc,id,data)`, starting from `init`
+    /// # Remarks
+    /// * Every ancestor is visited exactly once, `node_id` itself is excluded
+    /// * The order of visiting is NOT specified
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `node_id` is NOT found in `Dag`
+    pub fn fold_ancestors<Q, B, F>(
+        &self,
+        node_id: &Q,
+        init: B,
+        f: F,
+    ) -> Result<B, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+        F: FnMut(B, NodeId, &NodeData) -> B,
+    {
+        let (node_id, _) = self
+            .nodes
+            .get_key_value(node_id)
+            .ok_or_else(|| NodeNotFound(node_id.to_owned()))?;
+        Ok(self.fold_reachable(node_id, init, f, |dag, id| {
+            dag.parents::<NodeId>(id).collect()
+        }))
+    }
+
+    /// Fold the nodes reachable from `node_id` along `next`
+    fn fold_reachable<B, F, N>(&self, node_id: &NodeId, init: B, mut f: F, next: N) -> B
+    where
+        F: FnMut(B, NodeId, &NodeData) -> B,
+        N: Fn(&Self, &NodeId) -> Vec<NodeId>,
+    {
+        let mut visited = BTreeSet::from([node_id.clone()]);
+        let mut stack = next(self, node_id);
+        let mut acc = init;
+        while let Some(top) = stack.pop() {
+            if !visited.contains(&top) {
+                stack.extend(next(self, &top));
+                let data = self
+                    .nodes
+                    .get(&top)
+                    .unwrap_or_else(|| unreachable!("ids are from Dag"));
+                acc = f(acc, top.clone(), data);
+                visited.insert(top);
+            }
+        }
+        acc
+    }
+}
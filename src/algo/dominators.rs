@@ -0,0 +1,218 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+use core::borrow::Borrow;
+
+use crate::{Dag, NodeNotFound};
+
+/// Immediate dominators of all the nodes reachable from a root
+/// # Remarks
+/// * A node `a` dominates `b` if every path from root to `b` goes through `a`
+/// * Nodes which are NOT reachable from root have no dominator
+#[derive(Debug, Clone)]
+pub struct Dominators<NodeId> {
+    root: NodeId,
+    idoms: BTreeMap<NodeId, NodeId>,
+}
+
+impl<NodeId> Dominators<NodeId>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the root which dominators were computed from
+    pub fn root(&self) -> NodeId {
+        self.root.clone()
+    }
+
+    /// Get the immediate dominator of `node_id`
+    /// # Returns
+    /// * Return `None` when `node_id` is root or NOT reachable from root
+    pub fn immediate_dominator<Q>(&self, node_id: &Q) -> Option<NodeId>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if self.root.borrow() == node_id {
+            return None;
+        }
+        self.idoms.get(node_id).cloned()
+    }
+
+    /// Get an iterator of all the dominators of `node_id`,
+    /// from `node_id` itself up to root
+    /// # Returns
+    /// * Return `None` when `node_id` is NOT reachable from root
+    pub fn dominators<Q>(&self, node_id: &Q) -> Option<DominatorsIter<'_, NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (node_id, _) = self.idoms.get_key_value(node_id)?;
+        Some(DominatorsIter {
+            dominators: self,
+            node: Some(node_id.clone()),
+        })
+    }
+
+    /// Check if `a` dominates `b`
+    /// # Remarks
+    /// * Every reachable node dominates itself
+    pub fn dominates<Q>(&self, a: &Q, b: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dominators(b)
+            .map(|mut iter| iter.any(|id| id.borrow() == a))
+            .unwrap_or(false)
+    }
+
+    /// Get all the `(node_id, immediate_dominator)` pairs, root is excluded
+    pub fn immediate_dominators(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.idoms
+            .iter()
+            .filter(move |(id, _)| **id != self.root)
+            .map(|(id, idom)| (id.clone(), idom.clone()))
+    }
+}
+
+/// iterator of the dominators of a node in `Dominators`
+pub struct DominatorsIter<'a, NodeId> {
+    dominators: &'a Dominators<NodeId>,
+    node: Option<NodeId>,
+}
+
+impl<'a, NodeId> Iterator for DominatorsIter<'a, NodeId>
+where
+    NodeId: Clone + Ord,
+{
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node.take()?;
+        self.node = self.dominators.immediate_dominator(&node);
+        Some(node)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Compute the immediate dominators of all the nodes reachable from `root`
+    /// # Remarks
+    /// * It uses the iterative algorithm from Cooper, Harvey and Kennedy.
+    ///   Because reverse postorder is a topological order in `Dag`, one pass is enough
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `root` is NOT found in `Dag`
+    pub fn dominators(&self, root: NodeId) -> Result<Dominators<NodeId>, NodeNotFound<NodeId>> {
+        if !self.nodes.contains_key(&root) {
+            return Err(NodeNotFound(root));
+        }
+        // postorder by DFS
+        let mut postorder = Vec::new();
+        let mut indices = BTreeMap::new();
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![(root.clone(), self.children(&root))];
+        visited.insert(root.clone());
+        while let Some((node_id, children)) = stack.last_mut() {
+            let node_id = node_id.clone();
+            if let Some((child_id, _)) = children.next() {
+                if !visited.contains(&child_id) {
+                    visited.insert(child_id.clone());
+                    stack.push((child_id.clone(), self.children(&child_id)));
+                }
+            } else {
+                indices.insert(node_id.clone(), postorder.len());
+                postorder.push(node_id);
+                stack.pop();
+            }
+        }
+
+        let mut idoms = BTreeMap::new();
+        idoms.insert(root.clone(), root.clone());
+        for node_id in postorder.into_iter().rev().skip(1) {
+            let mut new_idom = None;
+            for parent_id in self.parents(&node_id) {
+                // unreachable parents have no index
+                if !idoms.contains_key(&parent_id) {
+                    continue;
+                }
+                new_idom = match new_idom {
+                    None => Some(parent_id),
+                    Some(idom) => Some(intersect(&idoms, &indices, parent_id, idom)),
+                };
+            }
+            let new_idom =
+                new_idom.unwrap_or_else(|| unreachable!("reachable node has a reachable parent"));
+            idoms.insert(node_id, new_idom);
+        }
+
+        Ok(Dominators { root, idoms })
+    }
+
+    /// Compute the dominance frontier of all the nodes reachable from `root`
+    /// # Returns
+    /// Return the frontier of every reachable node, which may be empty
+    /// # Remarks
+    /// * The frontier of `a` is the nodes `b` where `a` dominates a parent of `b`
+    ///   but does NOT strictly dominate `b`
+    /// * Unreachable parents are ignored
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `root` is NOT found in `Dag`
+    #[allow(clippy::type_complexity)]
+    pub fn dominance_frontiers(
+        &self,
+        root: NodeId,
+    ) -> Result<BTreeMap<NodeId, BTreeSet<NodeId>>, NodeNotFound<NodeId>> {
+        let dominators = self.dominators(root)?;
+        let mut frontiers = dominators
+            .idoms
+            .keys()
+            .map(|id| (id.clone(), BTreeSet::new()))
+            .collect::<BTreeMap<_, _>>();
+        for (node_id, idom) in dominators.immediate_dominators() {
+            let parents = self
+                .parents(&node_id)
+                .filter(|id| dominators.idoms.contains_key(id))
+                .collect::<Vec<_>>();
+            if parents.len() < 2 {
+                continue;
+            }
+            for mut runner in parents {
+                while runner != idom {
+                    frontiers
+                        .get_mut(&runner)
+                        .unwrap_or_else(|| unreachable!("runner is reachable"))
+                        .insert(node_id.clone());
+                    runner = dominators.idoms[&runner].clone();
+                }
+            }
+        }
+        Ok(frontiers)
+    }
+}
+
+/// Find the nearest common dominator of `a` and `b`
+fn intersect<NodeId>(
+    idoms: &BTreeMap<NodeId, NodeId>,
+    indices: &BTreeMap<NodeId, usize>,
+    mut a: NodeId,
+    mut b: NodeId,
+) -> NodeId
+where
+    NodeId: Clone + Ord,
+{
+    while a != b {
+        while indices[&a] < indices[&b] {
+            a = idoms[&a].clone();
+        }
+        while indices[&b] < indices[&a] {
+            b = idoms[&b].clone();
+        }
+    }
+    a
+}
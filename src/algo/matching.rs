@@ -0,0 +1,62 @@
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get all the ids with children by dense indices, in the order of `NodeId`
+    pub(crate) fn dense_children(&self) -> (Vec<NodeId>, Vec<Vec<usize>>) {
+        let ids = self.nodes.keys().cloned().collect::<Vec<_>>();
+        let index = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (id, index))
+            .collect::<BTreeMap<_, _>>();
+        let children = self
+            .edges
+            .values()
+            .map(|children| children.keys().map(|id| index[id]).collect())
+            .collect();
+        (ids, children)
+    }
+}
+
+/// Maximum matching of a bipartite graph by augmenting paths (Kuhn's algorithm)
+/// # Returns
+/// Return the left vertex matched with every right vertex
+pub(crate) fn maximum_matching(adjacency: &[Vec<usize>], right_len: usize) -> Vec<Option<usize>> {
+    let mut matched = vec![None; right_len];
+    for start in 0..adjacency.len() {
+        let mut visited = vec![false; right_len];
+        // left vertices on the path with the next edge to try,
+        // `via[i]` is the right vertex between `path[i]` and `path[i + 1]`
+        let mut path = vec![(start, 0)];
+        let mut via = Vec::new();
+        while let Some((left, edge)) = path.last_mut() {
+            let Some(&right) = adjacency[*left].get(*edge) else {
+                path.pop();
+                via.pop();
+                continue;
+            };
+            *edge += 1;
+            if visited[right] {
+                continue;
+            }
+            visited[right] = true;
+            via.push(right);
+            match matched[right] {
+                Some(next) => path.push((next, 0)),
+                None => {
+                    // flip the path
+                    for ((left, _), right) in path.iter().zip(&via) {
+                        matched[*right] = Some(*left);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    matched
+}
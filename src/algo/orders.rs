@@ -0,0 +1,148 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+use crate::Dag;
+
+/// An iterator of all the topological orders of `Dag`, created by [`Dag::all_topological_orders`]
+/// # Remarks
+/// * Orders are generated lazily by backtracking, in the lexicographic order of `NodeId`
+/// * The count of orders grows factorially, use it on small `Dag`s only
+#[derive(Debug, Clone)]
+pub struct TopologicalOrders<NodeId> {
+    ids: Vec<NodeId>,
+    children: Vec<Vec<usize>>,
+    in_degrees: Vec<usize>,
+    // the prefix of the current order
+    current: Vec<usize>,
+    // ready nodes of every position in the prefix, with the next choice to try
+    stack: Vec<(Vec<usize>, usize)>,
+    started: bool,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get an iterator of every valid topological order of `Dag`
+    /// # Remarks
+    /// * An empty `Dag` has exactly one order, which is empty
+    pub fn all_topological_orders(&self) -> TopologicalOrders<NodeId> {
+        let (ids, children) = self.dense_children();
+        let in_degrees = self.back_edges.values().map(BTreeSet::len).collect();
+        TopologicalOrders {
+            ids,
+            children,
+            in_degrees,
+            current: Vec::new(),
+            stack: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Count the valid topological orders of `Dag` without generating them
+    /// # Remarks
+    /// * It is a DP over the sets of nodes which can be emitted first,
+    ///   usually much faster than counting [`Dag::all_topological_orders`]
+    /// * The count saturates at `u128::MAX`
+    pub fn count_topological_orders(&self) -> u128 {
+        let orders = self.all_topological_orders();
+        let len = orders.ids.len();
+        let parents = {
+            let mut parents = vec![Vec::new(); len];
+            for (from, children) in orders.children.iter().enumerate() {
+                for to in children {
+                    parents[*to].push(from);
+                }
+            }
+            parents
+        };
+        // emitted sets with the count of orders emitting them, one level per emitted node
+        let mut level = BTreeMap::from([(vec![false; len], 1u128)]);
+        for _ in 0..len {
+            let mut next = BTreeMap::new();
+            for (emitted, count) in level {
+                for node in 0..len {
+                    if !emitted[node] && parents[node].iter().all(|parent| emitted[*parent]) {
+                        let mut emitted = emitted.clone();
+                        emitted[node] = true;
+                        let total = next.entry(emitted).or_insert(0u128);
+                        *total = total.saturating_add(count);
+                    }
+                }
+            }
+            level = next;
+        }
+        level.into_values().next().unwrap_or(0)
+    }
+}
+
+impl<NodeId> TopologicalOrders<NodeId> {
+    /// Emit `node` after the current prefix
+    fn push(&mut self, node: usize) {
+        self.current.push(node);
+        for child in &self.children[node] {
+            self.in_degrees[*child] -= 1;
+        }
+    }
+
+    /// Undo the last emitted node
+    fn pop(&mut self) {
+        if let Some(node) = self.current.pop() {
+            for child in &self.children[node] {
+                self.in_degrees[*child] += 1;
+            }
+        }
+    }
+}
+
+impl<NodeId> Iterator for TopologicalOrders<NodeId>
+where
+    NodeId: Clone,
+{
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            let ready = (0..self.ids.len())
+                .filter(|node| self.in_degrees[*node] == 0)
+                .collect::<Vec<_>>();
+            if self.ids.is_empty() {
+                return Some(Vec::new());
+            }
+            self.stack.push((ready, 0));
+        }
+        while let Some((ready, choice)) = self.stack.last_mut() {
+            if *choice == ready.len() {
+                // all the choices at this position are tried
+                self.stack.pop();
+                self.pop();
+                continue;
+            }
+            let node = ready[*choice];
+            *choice += 1;
+            let mut next_ready = ready.clone();
+            next_ready.retain(|other| *other != node);
+            self.push(node);
+            next_ready.extend(
+                self.children[node]
+                    .iter()
+                    .filter(|child| self.in_degrees[**child] == 0),
+            );
+            next_ready.sort_unstable();
+            self.stack.push((next_ready, 0));
+            if self.current.len() == self.ids.len() {
+                return Some(
+                    self.current
+                        .iter()
+                        .map(|node| self.ids[*node].clone())
+                        .collect(),
+                );
+            }
+        }
+        None
+    }
+}
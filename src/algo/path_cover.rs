@@ -0,0 +1,39 @@
+use alloc::{vec, vec::Vec};
+
+use super::matching::maximum_matching;
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get a minimum set of vertex-disjoint paths covering all the nodes
+    /// # Returns
+    /// Every path follows the edges in `Dag`, paths are in the order of their first ids
+    /// # Remarks
+    /// * Every node is in exactly one path, a path may be a single node
+    /// * The count of paths is the count of nodes minus a maximum matching of the edges
+    pub fn minimum_path_cover(&self) -> Vec<Vec<NodeId>> {
+        let (ids, children) = self.dense_children();
+        let matched = maximum_matching(&children, ids.len());
+        let mut next = vec![None; ids.len()];
+        for (to, from) in matched.iter().enumerate() {
+            if let Some(from) = from {
+                next[*from] = Some(to);
+            }
+        }
+        // a path starts from a node without a matched parent
+        (0..ids.len())
+            .filter(|node| matched[*node].is_none())
+            .map(|start| {
+                let mut path = Vec::from([ids[start].clone()]);
+                let mut node = start;
+                while let Some(to) = next[node] {
+                    path.push(ids[to].clone());
+                    node = to;
+                }
+                path
+            })
+            .collect()
+    }
+}
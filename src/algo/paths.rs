@@ -0,0 +1,151 @@
+use alloc::{
+    borrow::ToOwned,
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{borrow::Borrow, cmp::Ordering, ops::Add};
+
+use crate::{Dag, NodeNotFound};
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get at most `k` cheapest paths `from -> ... -> to`, the cost of an edge is `cost(from,to,data)`
+    /// # Returns
+    /// Return `Ok(paths)` with `(total_cost, path)` sorted by cost, `path` includes `from` and `to`
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
+    /// # Remarks
+    /// * The cost of the path `[from]` when `from == to` is `C::default()`
+    /// * It runs a DP in the topological order keeping `k` best costs of every node,
+    ///   in O(k·(V+E)) additions
+    /// * Costs should NOT be negative, incomparable costs (like `NaN`) are treated as equal
+    #[allow(clippy::type_complexity)]
+    pub fn k_shortest_paths<Q, C, F>(
+        &self,
+        from: &Q,
+        to: &Q,
+        k: usize,
+        cost: F,
+    ) -> Result<Vec<(C, Vec<NodeId>)>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+        C: Clone + PartialOrd + Add<Output = C> + Default,
+        F: FnMut(&NodeId, &NodeId, &EdgeData) -> C,
+    {
+        let (from, _) = self
+            .nodes
+            .get_key_value(from)
+            .ok_or_else(|| NodeNotFound(from.to_owned()))?;
+        let (to, _) = self
+            .nodes
+            .get_key_value(to)
+            .ok_or_else(|| NodeNotFound(to.to_owned()))?;
+        Ok(self.k_shortest_paths_between(from, to, k, cost))
+    }
+
+    /// Get an iterator of every path from any root down to `node_id`
+    /// # Remarks
+    /// * A path is `[root, ..., node_id]`, it is `[node_id]` when `node_id` is a root
+    /// * Paths are generated lazily by backtracking over the parents,
+    ///   the count of paths may grow exponentially
+    /// * It is empty when `node_id` is NOT found in `Dag`
+    pub fn paths_to<Q>(&self, node_id: &Q) -> impl Iterator<Item = Vec<NodeId>> + '_
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // the current path reversed, with parents of every node and the next one to try
+        let mut stack = Vec::new();
+        if let Some((node_id, _)) = self.nodes.get_key_value(node_id) {
+            stack.push((
+                node_id.clone(),
+                self.parents::<NodeId>(node_id).collect::<Vec<_>>(),
+                0,
+            ));
+        }
+        core::iter::from_fn(move || loop {
+            let (_, parents, next) = stack.last_mut()?;
+            if parents.is_empty() {
+                let path = stack.iter().rev().map(|(id, _, _)| id.clone()).collect();
+                stack.pop();
+                return Some(path);
+            }
+            match parents.get(*next).cloned() {
+                Some(parent_id) => {
+                    *next += 1;
+                    let grandparents = self.parents::<NodeId>(&parent_id).collect();
+                    stack.push((parent_id, grandparents, 0));
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        })
+    }
+
+    fn k_shortest_paths_between<C, F>(
+        &self,
+        from: &NodeId,
+        to: &NodeId,
+        k: usize,
+        mut cost: F,
+    ) -> Vec<(C, Vec<NodeId>)>
+    where
+        C: Clone + PartialOrd + Add<Output = C> + Default,
+        F: FnMut(&NodeId, &NodeId, &EdgeData) -> C,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+        // only the nodes between `from` and `to` are on the paths
+        let mut reached = BTreeSet::new();
+        let mut stack = Vec::from([from.clone()]);
+        while let Some(top) = stack.pop() {
+            if !reached.contains(&top) && self.order_of(&top) <= self.order_of(to) {
+                stack.extend(self.children(&top).map(|(id, _)| id));
+                reached.insert(top);
+            }
+        }
+        let mut reached = reached.into_iter().collect::<Vec<_>>();
+        reached.sort_by_key(|id| self.order_of(id));
+
+        // the best costs of every node with the previous (node, rank), sorted by cost
+        let mut best = BTreeMap::new();
+        best.insert(from.clone(), Vec::from([(C::default(), None)]));
+        for node_id in reached {
+            let costs = match best.get(&node_id) {
+                Some(costs) => costs.clone(),
+                None => continue,
+            };
+            for (child_id, data) in self.children(&node_id) {
+                let edge_cost = cost(&node_id, &child_id, data);
+                let child_costs = best.entry(child_id).or_default();
+                for (rank, (node_cost, _)) in costs.iter().enumerate() {
+                    let total = node_cost.clone() + edge_cost.clone();
+                    let position = child_costs.partition_point(|(other, _)| {
+                        other.partial_cmp(&total).unwrap_or(Ordering::Equal) != Ordering::Greater
+                    });
+                    if position < k {
+                        child_costs.insert(position, (total, Some((node_id.clone(), rank))));
+                        child_costs.truncate(k);
+                    }
+                }
+            }
+        }
+
+        let mut paths = Vec::new();
+        for (total, mut previous) in best.get(to).cloned().unwrap_or_default() {
+            let mut path = Vec::from([to.clone()]);
+            while let Some((node_id, rank)) = previous {
+                previous = best[&node_id][rank].1.clone();
+                path.push(node_id);
+            }
+            path.reverse();
+            paths.push((total, path));
+        }
+        paths
+    }
+}
@@ -0,0 +1,59 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use rand_core::Rng;
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get all the nodes in a random topological order, enabled by feature `rand`
+    /// # Remarks
+    /// * Every step emits one of the ready nodes, chosen uniformly by `rng`
+    /// * Every valid order can be sampled, but NOT with the same probability,
+    ///   nodes with fewer descendants tend to be emitted later
+    /// * The same seed gives the same order on the same `Dag`
+    pub fn random_topological_order<R>(&self, rng: &mut R) -> Vec<NodeId>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut in_degrees = self
+            .back_edges
+            .iter()
+            .map(|(id, parents)| (id.clone(), parents.len()))
+            .collect::<BTreeMap<_, _>>();
+        let mut ready = self.roots().map(|(id, _)| id).collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(self.nodes_len());
+        while !ready.is_empty() {
+            let node_id = ready.swap_remove(below(rng, ready.len()));
+            for (child_id, _) in self.children(&node_id) {
+                let in_degree = in_degrees
+                    .get_mut(&child_id)
+                    .unwrap_or_else(|| unreachable!("all nodes are in in_degrees"));
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(child_id);
+                }
+            }
+            order.push(node_id);
+        }
+        order
+    }
+}
+
+/// A uniform random number in `0..n` without modulo bias, `n > 0`
+fn below<R>(rng: &mut R, n: usize) -> usize
+where
+    R: Rng + ?Sized,
+{
+    let n = n as u64;
+    // reject the values in the incomplete range at the top
+    let zone = u64::MAX - u64::MAX % n;
+    loop {
+        let value = rng.next_u64();
+        if value < zone {
+            return (value % n) as usize;
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::borrow::Borrow;
+
+use crate::Dag;
+
+/// A precomputed index answering if a node reaches another, created by [`Dag::reachability_index`]
+/// # Remarks
+/// * Every node has a bitset of its descendants, so it takes `V²/8` bytes
+/// * A query is a lookup of both ids plus a bit test
+/// * It is a snapshot, later changes of `Dag` are NOT reflected
+#[derive(Debug, Clone)]
+pub struct ReachabilityIndex<NodeId> {
+    index: BTreeMap<NodeId, usize>,
+    // words of a bitset per node
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Build a [`ReachabilityIndex`] of `Dag`
+    pub fn reachability_index(&self) -> ReachabilityIndex<NodeId> {
+        let (ids, children) = self.dense_children();
+        let len = ids.len();
+        let words = len.div_ceil(64);
+        let mut bits = vec![0u64; len * words];
+        let index = ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, id)| (id, index))
+            .collect::<BTreeMap<_, _>>();
+        // children first
+        for node_id in self.topological_order().into_iter().rev() {
+            let node = index[&node_id];
+            bits[node * words + node / 64] |= 1 << (node % 64);
+            for child in &children[node] {
+                for word in 0..words {
+                    bits[node * words + word] |= bits[child * words + word];
+                }
+            }
+        }
+        ReachabilityIndex { index, words, bits }
+    }
+}
+
+impl<NodeId> ReachabilityIndex<NodeId>
+where
+    NodeId: Ord,
+{
+    /// Check if there is a path `from -> ... -> to`
+    /// # Returns
+    /// * Return `true` when `from == to`
+    /// * Return `false` when `from` or `to` is NOT indexed
+    pub fn reaches<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(from), Some(to)) => {
+                self.bits[from * self.words + to / 64] & (1 << (to % 64)) != 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the count of indexed nodes
+    pub fn nodes_len(&self) -> usize {
+        self.index.len()
+    }
+}
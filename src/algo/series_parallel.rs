@@ -0,0 +1,119 @@
+use alloc::{vec, vec::Vec};
+
+use crate::Dag;
+
+/// The composition tree of a series-parallel `Dag`, see [`Dag::series_parallel_decomposition`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeriesParallel<NodeId> {
+    /// A single node
+    Node(NodeId),
+    /// Parts which run one after another, every node of a part reaches every node of the later parts
+    Series(Vec<SeriesParallel<NodeId>>),
+    /// Parts which are independent, no node of a part reaches a node of another part
+    Parallel(Vec<SeriesParallel<NodeId>>),
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Decompose `Dag` into series and parallel compositions of its nodes
+    /// # Returns
+    /// * Return `None` when `Dag` is NOT series-parallel, that is some nodes `a,b,c,d`
+    ///   make an "N": `a` reaches `c` and `d`, `b` reaches `d` only
+    /// * Return `Some(Parallel([]))` when `Dag` is empty
+    /// # Remarks
+    /// * Nodes are related by reachability, so redundant edges like `a -> c`
+    ///   besides `a -> b -> c` do NOT matter
+    /// * Parts of a `Series` are in the topological order,
+    ///   parts of a `Parallel` are in the order of their smallest `NodeId`s
+    /// * Every `Series` and `Parallel` has at least 2 parts, and no part of a `Series` is
+    ///   a `Series`, no part of a `Parallel` is a `Parallel`
+    /// * It takes O(V³) time in the worst case and O(V²) space
+    pub fn series_parallel_decomposition(&self) -> Option<SeriesParallel<NodeId>> {
+        let (ids, children) = self.dense_children();
+        let mut nodes = (0..ids.len()).collect::<Vec<_>>();
+        nodes.sort_by_key(|node| self.order_of(&ids[*node]));
+        let mut reaches = vec![vec![false; ids.len()]; ids.len()];
+        for node in nodes.iter().rev() {
+            // children come later in the topological order, so they are done
+            for child in &children[*node] {
+                let child_reaches = reaches[*child].clone();
+                reaches[*node][*child] = true;
+                for (reached, child_reached) in reaches[*node].iter_mut().zip(child_reaches) {
+                    *reached |= child_reached;
+                }
+            }
+        }
+        if nodes.is_empty() {
+            return Some(SeriesParallel::Parallel(Vec::new()));
+        }
+        decompose(&ids, &reaches, &nodes)
+    }
+}
+
+/// Decompose `nodes` sorted in a topological order
+fn decompose<NodeId>(
+    ids: &[NodeId],
+    reaches: &[Vec<bool>],
+    nodes: &[usize],
+) -> Option<SeriesParallel<NodeId>>
+where
+    NodeId: Clone,
+{
+    if let [node] = nodes {
+        return Some(SeriesParallel::Node(ids[*node].clone()));
+    }
+    let comparable = |a: usize, b: usize| reaches[a][b] || reaches[b][a];
+    let mut parts = components(nodes, comparable);
+    if parts.len() > 1 {
+        // dense indices are in the order of `NodeId`
+        parts.sort_unstable_by_key(|part| part.iter().min().copied());
+        return parts
+            .iter()
+            .map(|part| decompose(ids, reaches, part))
+            .collect::<Option<_>>()
+            .map(SeriesParallel::Parallel);
+    }
+    // every node of a part is comparable with every node of another part,
+    // so the parts are totally ordered
+    let parts = components(nodes, |a, b| !comparable(a, b));
+    if parts.len() > 1 {
+        return parts
+            .iter()
+            .map(|part| decompose(ids, reaches, part))
+            .collect::<Option<_>>()
+            .map(SeriesParallel::Series);
+    }
+    None
+}
+
+/// Connected components of `nodes` by `adjacent`, every component keeps the order of `nodes`
+fn components<F>(nodes: &[usize], adjacent: F) -> Vec<Vec<usize>>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let mut labels = vec![usize::MAX; nodes.len()];
+    let mut len = 0;
+    for start in 0..nodes.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+        labels[start] = len;
+        let mut stack = vec![start];
+        while let Some(top) = stack.pop() {
+            for other in 0..nodes.len() {
+                if labels[other] == usize::MAX && adjacent(nodes[top], nodes[other]) {
+                    labels[other] = len;
+                    stack.push(other);
+                }
+            }
+        }
+        len += 1;
+    }
+    let mut parts = vec![Vec::new(); len];
+    for (node, label) in nodes.iter().zip(labels) {
+        parts[label].push(*node);
+    }
+    parts
+}
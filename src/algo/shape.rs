@@ -0,0 +1,35 @@
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Check if every node has at most one parent
+    /// # Remarks
+    /// * An empty `Dag` is a forest
+    pub fn is_forest(&self) -> bool {
+        self.back_edges.values().all(|parents| parents.len() <= 1)
+    }
+
+    /// Check if `Dag` is a forest with exactly one root
+    /// # Remarks
+    /// * An empty `Dag` is NOT a tree
+    pub fn is_tree(&self) -> bool {
+        self.is_forest() && self.roots_len() == 1
+    }
+
+    /// Check if `Dag` is a tree where every node has at most one child,
+    /// which means it is a single path
+    /// # Remarks
+    /// * An empty `Dag` is NOT a chain
+    pub fn is_chain(&self) -> bool {
+        self.edges.values().all(|children| children.len() <= 1) && self.is_tree()
+    }
+
+    fn roots_len(&self) -> usize {
+        self.back_edges
+            .values()
+            .filter(|parents| parents.is_empty())
+            .count()
+    }
+}
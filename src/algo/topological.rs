@@ -0,0 +1,137 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get all the nodes in a topological order by Kahn's algorithm
+    pub(crate) fn topological_order(&self) -> Vec<NodeId> {
+        let mut in_degrees = self
+            .nodes()
+            .map(|(id, _)| (id.clone(), self.parents(&id).len()))
+            .collect::<BTreeMap<_, _>>();
+        let mut ready = self.roots().map(|(id, _)| id).collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(self.nodes_len());
+        while let Some(node_id) = ready.pop() {
+            for (child_id, _) in self.children(&node_id) {
+                let in_degree = in_degrees
+                    .get_mut(&child_id)
+                    .unwrap_or_else(|| unreachable!("all nodes are in in_degrees"));
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(child_id);
+                }
+            }
+            order.push(node_id);
+        }
+        order
+    }
+
+    /// Get all the nodes in a topological order,
+    /// the smallest ready node by `compare` is emitted first
+    /// # Remarks
+    /// * A node is ready when all of its parents are emitted
+    /// * Ready nodes equal by `compare` are emitted in the order they became ready
+    /// * Ready nodes are kept in a binary heap, so it is O((V+E) log V)
+    pub fn topological_sort_by<F>(&self, compare: F) -> Vec<NodeId>
+    where
+        F: FnMut(&NodeId, &NodeId) -> Ordering,
+    {
+        let mut in_degrees = self
+            .back_edges
+            .iter()
+            .map(|(id, parents)| (id.clone(), parents.len()))
+            .collect::<BTreeMap<_, _>>();
+        let mut ready = ReadyHeap {
+            items: Vec::new(),
+            pushed: 0,
+            compare,
+        };
+        for (node_id, _) in self.roots() {
+            ready.push(node_id);
+        }
+        let mut order = Vec::with_capacity(self.nodes_len());
+        while let Some(node_id) = ready.pop() {
+            for (child_id, _) in self.children(&node_id) {
+                let in_degree = in_degrees
+                    .get_mut(&child_id)
+                    .unwrap_or_else(|| unreachable!("all nodes are in in_degrees"));
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(child_id);
+                }
+            }
+            order.push(node_id);
+        }
+        order
+    }
+
+    /// Get all the nodes in a topological order,
+    /// the ready node with the smallest `key(id)` is emitted first
+    /// # Remarks
+    /// * See [`Dag::topological_sort_by`]
+    pub fn topological_sort_by_key<K, F>(&self, mut key: F) -> Vec<NodeId>
+    where
+        K: Ord,
+        F: FnMut(&NodeId) -> K,
+    {
+        self.topological_sort_by(|a, b| key(a).cmp(&key(b)))
+    }
+}
+
+/// Min-heap of ready nodes by `compare`, ties are broken by the order they were pushed
+struct ReadyHeap<NodeId, F> {
+    // (push count, node id), a parent is never greater than its children
+    items: Vec<(usize, NodeId)>,
+    pushed: usize,
+    compare: F,
+}
+
+impl<NodeId, F> ReadyHeap<NodeId, F>
+where
+    F: FnMut(&NodeId, &NodeId) -> Ordering,
+{
+    fn less(&mut self, a: usize, b: usize) -> bool {
+        let (pushed_a, a) = &self.items[a];
+        let (pushed_b, b) = &self.items[b];
+        (self.compare)(a, b).then(pushed_a.cmp(pushed_b)) == Ordering::Less
+    }
+
+    fn push(&mut self, node_id: NodeId) {
+        self.items.push((self.pushed, node_id));
+        self.pushed += 1;
+        let mut child = self.items.len() - 1;
+        while child > 0 {
+            let parent = (child - 1) / 2;
+            if !self.less(child, parent) {
+                break;
+            }
+            self.items.swap(child, parent);
+            child = parent;
+        }
+    }
+
+    fn pop(&mut self) -> Option<NodeId> {
+        let last = self.items.len().checked_sub(1)?;
+        self.items.swap(0, last);
+        let (_, top) = self.items.pop()?;
+        let mut parent = 0;
+        loop {
+            let mut smallest = parent;
+            for child in [2 * parent + 1, 2 * parent + 2] {
+                if child < self.items.len() && self.less(child, smallest) {
+                    smallest = child;
+                }
+            }
+            if smallest == parent {
+                break;
+            }
+            self.items.swap(parent, smallest);
+            parent = smallest;
+        }
+        Some(top)
+    }
+}
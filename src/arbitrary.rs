@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::{vec, SizeRange},
+    sample::Index,
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Arbitrary for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Arbitrary + Clone + Ord + 'static,
+    NodeData: Arbitrary + 'static,
+    EdgeData: Arbitrary + 'static,
+    Self: Debug,
+{
+    /// The ranges of the count of nodes and edges tried
+    /// # Remarks
+    /// * Duplicate node ids and duplicate edges are dropped,
+    ///   so the result may be smaller than the ranges
+    type Parameters = (SizeRange, SizeRange);
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generate random valid `Dag`s, shrinking toward fewer nodes and edges
+    /// # Remarks
+    /// * The default ranges are `SizeRange::default()`, which is 0 to 99
+    /// * Edges go from nodes generated earlier to nodes generated later, so there is no cycle
+    fn arbitrary_with((nodes, edges): Self::Parameters) -> Self::Strategy {
+        (
+            vec(any::<(NodeId, NodeData)>(), nodes),
+            vec(any::<(Index, Index, EdgeData)>(), edges),
+        )
+            .prop_map(|(nodes, edges)| {
+                let mut dag = Dag::new();
+                let mut ids = Vec::new();
+                for (node_id, node_data) in nodes {
+                    if !dag.nodes.contains_key(&node_id) {
+                        ids.push(node_id.clone());
+                        dag.insert_node(node_id, node_data);
+                    }
+                }
+                if ids.is_empty() {
+                    return dag;
+                }
+                for (from, to, edge_data) in edges {
+                    let (from, to) = (from.index(ids.len()), to.index(ids.len()));
+                    if from == to {
+                        continue;
+                    }
+                    let (from, to) = (from.min(to), from.max(to));
+                    let children = dag
+                        .edges
+                        .get_mut(&ids[from])
+                        .unwrap_or_else(|| unreachable!("ids are inserted into Dag"));
+                    if children.insert(ids[to].clone(), edge_data).is_none() {
+                        dag.back_edges
+                            .get_mut(&ids[to])
+                            .unwrap_or_else(|| unreachable!("ids are inserted into Dag"))
+                            .insert(ids[from].clone());
+                        dag.edges_len += 1;
+                    }
+                }
+                if !dag.rebuild_order() {
+                    unreachable!("edges follow the order of generation, so there is no cycle");
+                }
+                dag
+            })
+            .boxed()
+    }
+}
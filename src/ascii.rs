@@ -0,0 +1,76 @@
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Render `Dag` as indented trees from every root like `cargo tree`,
+    /// nodes are labeled by their ids
+    pub fn render_ascii(&self) -> String
+    where
+        NodeId: Display,
+    {
+        self.render_ascii_with(|id, _| id.to_string())
+    }
+
+    /// Render `Dag` as indented trees from every root like `cargo tree`,
+    /// nodes are labeled by `label(id,data)`
+    /// # Remarks
+    /// * A shared node is expanded only the first time,
+    ///   later it is marked by `(*)` if it has children
+    /// * Roots and children are in the order of `NodeId`
+    pub fn render_ascii_with<F>(&self, mut label: F) -> String
+    where
+        F: FnMut(&NodeId, &NodeData) -> String,
+    {
+        let mut output = String::new();
+        let mut expanded = BTreeSet::new();
+        // (id, prefix, is the last child), `None` for roots
+        let mut stack = self
+            .roots()
+            .map(|(id, _)| (id, String::new(), None))
+            .collect::<Vec<_>>();
+        stack.reverse();
+        while let Some((node_id, prefix, last)) = stack.pop() {
+            let data = self
+                .nodes
+                .get(&node_id)
+                .unwrap_or_else(|| unreachable!("ids are from Dag"));
+            output.push_str(&prefix);
+            output.push_str(match last {
+                None => "",
+                Some(false) => "├── ",
+                Some(true) => "└── ",
+            });
+            output.push_str(&label(&node_id, data));
+            let children = self
+                .children(&node_id)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            if !children.is_empty() && expanded.contains(&node_id) {
+                output.push_str(" (*)\n");
+                continue;
+            }
+            output.push('\n');
+            let prefix = prefix
+                + match last {
+                    None => "",
+                    Some(false) => "│   ",
+                    Some(true) => "    ",
+                };
+            let len = children.len();
+            for (i, child_id) in children.into_iter().enumerate().rev() {
+                stack.push((child_id, prefix.clone(), Some(i + 1 == len)));
+            }
+            expanded.insert(node_id);
+        }
+        output
+    }
+}
@@ -0,0 +1,37 @@
+use alloc::vec::Vec;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize,
+    NodeData: Serialize,
+    EdgeData: Serialize,
+{
+    /// Serialize `Dag` into a compact binary form by postcard
+    /// # Remarks
+    /// * Only forward edges are stored
+    /// # Errors
+    /// * `Err(_)` when node ids or data can't be serialized
+    pub fn to_bytes(&self) -> Result<Vec<u8>, postcard::Error> {
+        postcard::to_allocvec(self)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + DeserializeOwned,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    /// Deserialize `Dag` from bytes created by [`Dag::to_bytes`]
+    /// # Remarks
+    /// * Back edges are rebuilt from forward edges
+    /// # Errors
+    /// * `Err(_)` when `bytes` is invalid, or it has a cycle or an edge referring to a missing node
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
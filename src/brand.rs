@@ -0,0 +1,147 @@
+use core::{borrow::Borrow, marker::PhantomData};
+
+use crate::Dag;
+
+// invariant in `'brand`, so brands of different `Dag`s never unify
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/// A node id branded by the `Dag` it is obtained from, see [`Dag::with_brand`]
+/// # Remarks
+/// * A `BrandedId` is NOT accepted by [`BrandedDag`]s of other `Dag`s, checked at compile time
+/// * Get the plain id back by [`BrandedId::id`] or [`BrandedId::into_inner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BrandedId<'brand, NodeId> {
+    id: NodeId,
+    _brand: Brand<'brand>,
+}
+
+impl<NodeId> BrandedId<'_, NodeId> {
+    /// Get the plain id
+    pub fn id(&self) -> &NodeId {
+        &self.id
+    }
+
+    /// Get the plain id, dropping the brand
+    pub fn into_inner(self) -> NodeId {
+        self.id
+    }
+}
+
+/// A borrowed `Dag` which only accepts [`BrandedId`]s obtained from itself
+/// # Remarks
+/// * Created by [`Dag::with_brand`], every call gets a brand of its own
+/// * The `Dag` can NOT change while borrowed, so a `BrandedId` always refers to a node
+///   and node lookups never fail
+///
+/// ```compile_fail
+/// use xdag::Dag;
+///
+/// let mut a = Dag::<u32, (), ()>::new();
+/// a.insert_node(1, ());
+/// let b = a.clone();
+/// a.with_brand(|a| {
+///     b.with_brand(|b| {
+///         let id = a.id(&1).unwrap();
+///         b.get_node(&id);
+///     })
+/// });
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BrandedDag<'brand, 'a, NodeId, NodeData, EdgeData> {
+    dag: &'a Dag<NodeId, NodeData, EdgeData>,
+    _brand: Brand<'brand>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Call `f` with a [`BrandedDag`] of `Dag`, whose ids can NOT be mixed with other `Dag`s
+    /// # Returns
+    /// Return the result of `f`
+    pub fn with_brand<R, F>(&self, f: F) -> R
+    where
+        F: for<'brand> FnOnce(BrandedDag<'brand, '_, NodeId, NodeData, EdgeData>) -> R,
+    {
+        f(BrandedDag {
+            dag: self,
+            _brand: PhantomData,
+        })
+    }
+}
+
+impl<'brand, 'a, NodeId, NodeData, EdgeData> BrandedDag<'brand, 'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    fn brand(id: NodeId) -> BrandedId<'brand, NodeId> {
+        BrandedId {
+            id,
+            _brand: PhantomData,
+        }
+    }
+
+    /// Get the underlying `Dag`
+    pub fn dag(&self) -> &'a Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Get the branded id of a node
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `Dag`
+    pub fn id<Q>(&self, node_id: &Q) -> Option<BrandedId<'brand, NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag
+            .nodes
+            .get_key_value(node_id)
+            .map(|(id, _)| Self::brand(id.clone()))
+    }
+
+    /// Get data from node
+    pub fn get_node(&self, node_id: &BrandedId<'brand, NodeId>) -> &'a NodeData {
+        self.dag
+            .nodes
+            .get(&node_id.id)
+            .unwrap_or_else(|| unreachable!("proved by the brand"))
+    }
+
+    /// Get data from edge
+    /// # Returns
+    /// Return `None` if there is no edge `from -> to`
+    pub fn get_edge(
+        &self,
+        from: &BrandedId<'brand, NodeId>,
+        to: &BrandedId<'brand, NodeId>,
+    ) -> Option<&'a EdgeData> {
+        self.dag
+            .edges
+            .get(&from.id)
+            .and_then(|children| children.get(&to.id))
+    }
+
+    /// Get an iterator of all the children of given node
+    pub fn children(
+        &self,
+        node_id: &BrandedId<'brand, NodeId>,
+    ) -> impl Iterator<Item = (BrandedId<'brand, NodeId>, &'a EdgeData)> {
+        self.dag
+            .children(&node_id.id)
+            .map(|(id, data)| (Self::brand(id), data))
+    }
+
+    /// Get an iterator of all the parents of given node
+    pub fn parents(
+        &self,
+        node_id: &BrandedId<'brand, NodeId>,
+    ) -> impl Iterator<Item = BrandedId<'brand, NodeId>> + 'a {
+        self.dag.parents(&node_id.id).map(Self::brand)
+    }
+
+    /// Get all the nodes in `Dag`
+    pub fn nodes(&self) -> impl Iterator<Item = (BrandedId<'brand, NodeId>, &'a NodeData)> {
+        self.dag.nodes().map(|(id, data)| (Self::brand(id), data))
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::vec::Vec;
+
+use crate::{Dag, DagError};
+
+/// A fluent builder of `Dag`, validating everything at once in [`DagBuilder::build`]
+/// # Remarks
+/// * Created by [`Dag::builder`]
+/// * Edges may refer to nodes declared later, the order of calls doesn't matter
+/// * A later edge between the same nodes replaces the data of the former one
+#[derive(Debug, Clone)]
+pub struct DagBuilder<NodeId, NodeData, EdgeData> {
+    nodes: Vec<(NodeId, NodeData)>,
+    edges: Vec<(NodeId, NodeId, EdgeData)>,
+}
+
+impl<NodeId, NodeData, EdgeData> Default for DagBuilder<NodeId, NodeData, EdgeData> {
+    fn default() -> Self {
+        DagBuilder {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Create a [`DagBuilder`]
+    pub fn builder() -> DagBuilder<NodeId, NodeData, EdgeData> {
+        DagBuilder::default()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> DagBuilder<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Add a node with data
+    pub fn node(mut self, node_id: NodeId, node_data: NodeData) -> Self {
+        self.nodes.push((node_id, node_data));
+        self
+    }
+
+    /// Add an edge `from -> to` with data
+    pub fn edge(mut self, from: NodeId, to: NodeId, edge_data: EdgeData) -> Self {
+        self.edges.push((from, to, edge_data));
+        self
+    }
+
+    /// Add edges between each pair of adjacent nodes in `node_ids`, with default data
+    pub fn chain<I>(mut self, node_ids: I) -> Self
+    where
+        I: IntoIterator<Item = NodeId>,
+        EdgeData: Default,
+    {
+        let mut node_ids = node_ids.into_iter();
+        if let Some(mut from) = node_ids.next() {
+            for to in node_ids {
+                self.edges.push((from, to.clone(), EdgeData::default()));
+                from = to;
+            }
+        }
+        self
+    }
+
+    /// Build the `Dag`
+    /// # Errors
+    /// Return `Err(errors)` with every problem found, in the order of calls:
+    /// * `DuplicateNode(id)` when a node is added twice
+    /// * `NodeNotFound(id)` when an edge refers to a node never added
+    /// * `HasCycle(from,to,data,path)` for each edge which makes a cycle with the edges before it
+    pub fn build(self) -> Result<Dag<NodeId, NodeData, EdgeData>, Vec<DagError<NodeId, EdgeData>>> {
+        let mut errors = Vec::new();
+        let mut dag = Dag::new();
+        for (node_id, node_data) in self.nodes {
+            if dag.nodes.contains_key(&node_id) {
+                errors.push(DagError::DuplicateNode(node_id));
+            } else {
+                dag.insert_node(node_id, node_data);
+            }
+        }
+        for (from, to, edge_data) in self.edges {
+            let mut found = true;
+            for node_id in [&from, &to] {
+                if !dag.nodes.contains_key(node_id) {
+                    errors.push(DagError::NodeNotFound(node_id.clone()));
+                    found = false;
+                }
+            }
+            if found {
+                if let Err(error) = dag.insert_edge(from, to, edge_data) {
+                    errors.push(error);
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(dag)
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::{borrow::Borrow, cell::OnceCell, ops::Deref};
+
+use crate::{algo::ReachabilityIndex, Dag, DagError, NodeNotFound};
+
+/// A `Dag` which memoizes derived data until the next structural mutation
+/// # Remarks
+/// * Created by [`Dag::into_cached`], reads go to `Dag` by `Deref`
+/// * Every structural mutation bumps [`CachedDag::mutations`] and drops the cached data,
+///   which is computed again by the next query
+/// * Mutating data of nodes or edges keeps the cache, since it only depends on the structure
+#[derive(Debug, Clone)]
+pub struct CachedDag<NodeId, NodeData, EdgeData> {
+    dag: Dag<NodeId, NodeData, EdgeData>,
+    mutations: u64,
+    cache: Cache<NodeId>,
+}
+
+#[derive(Debug, Clone)]
+struct Cache<NodeId> {
+    topological_order: OnceCell<Vec<NodeId>>,
+    reachability_index: OnceCell<ReachabilityIndex<NodeId>>,
+    roots: OnceCell<Vec<NodeId>>,
+    leaves: OnceCell<Vec<NodeId>>,
+}
+
+impl<NodeId> Default for Cache<NodeId> {
+    fn default() -> Self {
+        Cache {
+            topological_order: OnceCell::new(),
+            reachability_index: OnceCell::new(),
+            roots: OnceCell::new(),
+            leaves: OnceCell::new(),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Turn `Dag` into a [`CachedDag`] to memoize expensive queries
+    pub fn into_cached(self) -> CachedDag<NodeId, NodeData, EdgeData> {
+        CachedDag {
+            dag: self,
+            mutations: 0,
+            cache: Cache::default(),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> CachedDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the `Dag` back
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Get the count of structural mutations, it can key caches kept outside
+    pub fn mutations(&self) -> u64 {
+        self.mutations
+    }
+
+    /// Get all the nodes in a topological order, computed once per mutation
+    pub fn topological_order(&self) -> &[NodeId] {
+        self.cache
+            .topological_order
+            .get_or_init(|| self.dag.topological_order())
+    }
+
+    /// Get the reachability index, computed once per mutation, see [`Dag::reachability_index`]
+    pub fn reachability_index(&self) -> &ReachabilityIndex<NodeId> {
+        self.cache
+            .reachability_index
+            .get_or_init(|| self.dag.reachability_index())
+    }
+
+    /// Get ids of all the roots in order, computed once per mutation
+    pub fn root_ids(&self) -> &[NodeId] {
+        self.cache
+            .roots
+            .get_or_init(|| self.dag.roots().map(|(id, _)| id).collect())
+    }
+
+    /// Get ids of all the leaves in order, computed once per mutation
+    pub fn leaf_ids(&self) -> &[NodeId] {
+        self.cache
+            .leaves
+            .get_or_init(|| self.dag.leaves().map(|(id, _)| id).collect())
+    }
+
+    /// Insert a node with data, see [`Dag::insert_node`]
+    pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) -> Option<NodeData> {
+        let result = self.dag.insert_node(node_id, node_data);
+        if result.is_none() {
+            self.invalidate();
+        }
+        result
+    }
+
+    /// Insert an edge with data, see [`Dag::insert_edge`]
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        let result = self.dag.insert_edge(from, to, edge_data)?;
+        if result.is_none() {
+            self.invalidate();
+        }
+        Ok(result)
+    }
+
+    /// Remove an edge, see [`Dag::remove_edge`]
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        let result = self.dag.remove_edge(from, to)?;
+        if result.is_some() {
+            self.invalidate();
+        }
+        Ok(result)
+    }
+
+    /// Remove a node and all edges related, see [`Dag::remove_node`]
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &mut self,
+        node_id: NodeId,
+    ) -> (Option<NodeData>, Vec<(NodeId, NodeId, EdgeData)>) {
+        let result = self.dag.remove_node(node_id);
+        if result.0.is_some() {
+            self.invalidate();
+        }
+        result
+    }
+
+    /// Get mutable data from node, the cache is kept
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `CachedDag`
+    pub fn get_node_mut<Q>(&mut self, node_id: &Q) -> Option<&mut NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.get_node_mut(node_id)
+    }
+
+    /// Get mutable data from edge, the cache is kept, see [`Dag::get_edge_mut`]
+    pub fn get_edge_mut<Q>(
+        &mut self,
+        from: &Q,
+        to: &Q,
+    ) -> Result<Option<&mut EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        self.dag.get_edge_mut(from, to)
+    }
+
+    /// Remove all nodes and edges
+    pub fn clear(&mut self) {
+        self.dag.clear();
+        self.invalidate();
+    }
+
+    /// Get the mutable `Dag` for other mutations, the cache is dropped anyway
+    pub fn dag_mut(&mut self) -> &mut Dag<NodeId, NodeData, EdgeData> {
+        self.invalidate();
+        &mut self.dag
+    }
+
+    fn invalidate(&mut self) {
+        self.mutations += 1;
+        self.cache = Cache::default();
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for CachedDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
@@ -0,0 +1,128 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+use crate::{Dag, DagError};
+
+/// Nodes and edges removed from `Dag`, as `(id, data)` and `(from, to, data)`
+pub type Removed<NodeId, NodeData, EdgeData> =
+    (Vec<(NodeId, NodeData)>, Vec<(NodeId, NodeId, EdgeData)>);
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the descendants of `node_id` which are reachable only through it, in a topological order
+    fn exclusive_descendants(&self, node_id: NodeId) -> Vec<NodeId> {
+        // count of parents NOT removed yet
+        let mut remaining = BTreeMap::new();
+        let mut stack = vec![node_id];
+        let mut descendants = Vec::new();
+        while let Some(top) = stack.pop() {
+            for (child_id, _) in self.children(&top) {
+                let count = remaining
+                    .entry(child_id.clone())
+                    .or_insert_with(|| self.parents(&child_id).len());
+                *count -= 1;
+                if *count == 0 {
+                    descendants.push(child_id.clone());
+                    stack.push(child_id);
+                }
+            }
+        }
+        descendants
+    }
+
+    /// Remove the descendants of `node_id` which are reachable only through it
+    /// # Remarks
+    /// * `node_id` is kept
+    /// * A descendant is removed only when all of its parents are removed,
+    ///   so nodes still reachable from other nodes are kept
+    /// # Returns
+    /// * Return `Ok((nodes, edges))` of the removed nodes and all the removed edges
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `node_id` is NOT found in `Dag`
+    pub fn remove_descendants(
+        &mut self,
+        node_id: NodeId,
+    ) -> Result<Removed<NodeId, NodeData, EdgeData>, DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&node_id) {
+            return Err(DagError::NodeNotFound(node_id));
+        }
+        let descendants = self.exclusive_descendants(node_id);
+        Ok(self.remove_nodes(descendants))
+    }
+
+    /// Remove `node_id` and its descendants which are reachable only through it
+    /// # Remarks
+    /// * A descendant is removed only when all of its parents are removed,
+    ///   so nodes still reachable from other nodes are kept
+    /// # Returns
+    /// * Return `Ok((nodes, edges))` of the removed nodes and all the removed edges
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `node_id` is NOT found in `Dag`
+    pub fn remove_with_descendants(
+        &mut self,
+        node_id: NodeId,
+    ) -> Result<Removed<NodeId, NodeData, EdgeData>, DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&node_id) {
+            return Err(DagError::NodeNotFound(node_id));
+        }
+        let mut node_ids = vec![node_id.clone()];
+        node_ids.extend(self.exclusive_descendants(node_id));
+        Ok(self.remove_nodes(node_ids))
+    }
+
+    /// Remove all the nodes NOT reachable from `node_ids`, like a garbage collection from a root set
+    /// # Remarks
+    /// * `node_ids` and their descendants are kept
+    /// # Returns
+    /// * Return `Ok((nodes, edges))` of the removed nodes in the order of `NodeId`
+    ///   and all the removed edges
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when an id of `node_ids` is NOT found in `Dag`,
+    ///   nothing is removed
+    pub fn retain_reachable_from<I>(
+        &mut self,
+        node_ids: I,
+    ) -> Result<Removed<NodeId, NodeData, EdgeData>, DagError<NodeId, EdgeData>>
+    where
+        I: IntoIterator<Item = NodeId>,
+    {
+        let mut stack = Vec::new();
+        for node_id in node_ids {
+            if !self.nodes.contains_key(&node_id) {
+                return Err(DagError::NodeNotFound(node_id));
+            }
+            stack.push(node_id);
+        }
+        let mut reached = BTreeSet::new();
+        while let Some(top) = stack.pop() {
+            if !reached.contains(&top) {
+                stack.extend(self.children(&top).map(|(id, _)| id));
+                reached.insert(top);
+            }
+        }
+        let unreached = self
+            .nodes
+            .keys()
+            .filter(|id| !reached.contains(*id))
+            .cloned()
+            .collect();
+        Ok(self.remove_nodes(unreached))
+    }
+
+    fn remove_nodes(&mut self, node_ids: Vec<NodeId>) -> Removed<NodeId, NodeData, EdgeData> {
+        let mut nodes = Vec::with_capacity(node_ids.len());
+        let mut edges = Vec::new();
+        for node_id in node_ids {
+            let (data, removed_edges) = self.remove_node(node_id.clone());
+            let data = data.unwrap_or_else(|| unreachable!("removed nodes are in Dag"));
+            nodes.push((node_id, data));
+            edges.extend(removed_edges);
+        }
+        (nodes, edges)
+    }
+}
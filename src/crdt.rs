@@ -0,0 +1,341 @@
+//! A state-based CRDT replicating `Dag`, enabled by feature `crdt`
+//! # Remarks
+//! * Every node and edge is a last-writer-wins register ordered by [`Timestamp`],
+//!   removals leave tombstones
+//! * The visible `Dag` is derived from the state deterministically: live edges are inserted
+//!   from the oldest, an edge making a cycle is hidden.
+//!   So replicas with the same updates show the same `Dag` whatever the order of merging
+//! * A hidden edge shows again when an edge of its cycle is removed
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::ops::Deref;
+
+use crate::{Dag, DagError};
+
+/// A Lamport timestamp, ordered by `counter` then `replica`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    /// The logical clock
+    pub counter: u64,
+    /// Id of the replica which made the update
+    pub replica: u64,
+}
+
+/// A replica of a `Dag`, which converges with others by [`CrdtDag::merge`]
+/// # Remarks
+/// * Reads go to the visible `Dag` by `Deref`
+/// * Every replica must have a unique id
+#[derive(Debug, Clone)]
+pub struct CrdtDag<NodeId, NodeData, EdgeData> {
+    replica: u64,
+    counter: u64,
+    // `None` is a tombstone
+    nodes: BTreeMap<NodeId, (Timestamp, Option<NodeData>)>,
+    edges: BTreeMap<(NodeId, NodeId), (Timestamp, Option<EdgeData>)>,
+    dag: Dag<NodeId, NodeData, EdgeData>,
+}
+
+impl<NodeId, NodeData, EdgeData> CrdtDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    /// Create an empty replica with a unique id
+    pub fn new(replica: u64) -> Self {
+        CrdtDag {
+            replica,
+            counter: 0,
+            nodes: BTreeMap::new(),
+            edges: BTreeMap::new(),
+            dag: Dag::new(),
+        }
+    }
+
+    /// Get the id of the replica
+    pub fn replica(&self) -> u64 {
+        self.replica
+    }
+
+    /// Get the `Dag` visible now
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Get the edges hidden since they make cycles, as `(from, to)`
+    pub fn hidden_edges(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.edges
+            .iter()
+            .filter(|((from, to), (_, data))| {
+                data.is_some()
+                    && self.dag.contains_node(from)
+                    && self.dag.contains_node(to)
+                    && !self.dag.contains_edge(from, to)
+            })
+            .map(|(key, _)| key.clone())
+    }
+
+    /// Check if some live edges are hidden, only then a removal may show one again
+    fn has_hidden_edges(&self) -> bool {
+        self.hidden_edges().next().is_some()
+    }
+
+    fn tick(&mut self) -> Timestamp {
+        self.counter += 1;
+        Timestamp {
+            counter: self.counter,
+            replica: self.replica,
+        }
+    }
+
+    /// Insert a node with data, or replace its data
+    pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) {
+        let timestamp = self.tick();
+        self.nodes
+            .insert(node_id.clone(), (timestamp, Some(node_data.clone())));
+        if self.dag.insert_node(node_id.clone(), node_data).is_none() {
+            // edges to the node may show again
+            let edges = self
+                .edges
+                .iter()
+                .filter(|((from, to), _)| *from == node_id || *to == node_id);
+            if !show(&mut self.dag, edges) {
+                self.rebuild();
+            }
+        }
+    }
+
+    /// Insert an edge with data, or replace its data
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT visible
+    /// * `Err(HasCycle(from,to,data,path))` when a cycle is detected in the visible `Dag`,
+    ///   nothing is recorded
+    /// # Remarks
+    /// * Replacing data makes the edge the newest, so it may lose a cycle to a hidden edge.
+    ///   Then it is rejected by `HasCycle` as well
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<(), DagError<NodeId, EdgeData>> {
+        self.dag
+            .insert_edge(from.clone(), to.clone(), edge_data.clone())?;
+        let timestamp = self.tick();
+        let key = (from.clone(), to.clone());
+        let previous = self.edges.insert(key.clone(), (timestamp, Some(edge_data)));
+        // a new edge is the newest, so it is inserted last by a rebuild as well,
+        // but a replaced one moves to the end, which matters only to hidden edges
+        if let Some((previous_timestamp, Some(previous_data))) = previous {
+            if !self.has_hidden_edges() {
+                return Ok(());
+            }
+            self.rebuild();
+            if !self.dag.contains_edge(&from, &to) {
+                let path = self
+                    .dag
+                    .find_path(to.clone(), from.clone())
+                    .unwrap_or_else(|| unreachable!("the edge is hidden by a cycle"));
+                let (_, data) = self
+                    .edges
+                    .insert(key, (previous_timestamp, Some(previous_data)))
+                    .unwrap_or_else(|| unreachable!("inserted above"));
+                self.rebuild();
+                let data = data.unwrap_or_else(|| unreachable!("inserted above"));
+                return Err(DagError::HasCycle(from, to, data, path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove an edge
+    /// # Returns
+    /// * Return `false` when there is no such edge, visible or hidden
+    pub fn remove_edge(&mut self, from: NodeId, to: NodeId) -> bool {
+        let live = matches!(
+            self.edges.get(&(from.clone(), to.clone())),
+            Some((_, Some(_)))
+        );
+        if live {
+            let hidden = self.has_hidden_edges();
+            let timestamp = self.tick();
+            self.edges
+                .insert((from.clone(), to.clone()), (timestamp, None));
+            if hidden {
+                self.rebuild();
+            } else {
+                // the edge may be to a node NOT visible
+                let _ = self.dag.remove_edge(from, to);
+            }
+        }
+        live
+    }
+
+    /// Remove a node and all the edges related
+    /// # Returns
+    /// * Return `false` when `node_id` is NOT visible
+    pub fn remove_node(&mut self, node_id: NodeId) -> bool {
+        if !self.dag.contains_node(&node_id) {
+            return false;
+        }
+        let hidden = self.has_hidden_edges();
+        let timestamp = self.tick();
+        self.nodes.insert(node_id.clone(), (timestamp, None));
+        for ((from, to), (edge_timestamp, data)) in self.edges.iter_mut() {
+            if data.is_some() && (*from == node_id || *to == node_id) {
+                *edge_timestamp = timestamp;
+                *data = None;
+            }
+        }
+        if hidden {
+            self.rebuild();
+        } else {
+            self.dag.remove_node(node_id);
+        }
+        true
+    }
+
+    /// Merge the state of another replica
+    /// # Remarks
+    /// * It is commutative, associative and idempotent
+    /// * Updates are applied to the visible `Dag` directly, it is rebuilt only when
+    ///   a new edge makes a cycle, or a removal or a replaced edge may show a hidden one
+    pub fn merge(&mut self, other: &Self) {
+        self.counter = self.counter.max(other.counter);
+        let hidden = self.has_hidden_edges();
+        let mut rebuild = false;
+        // nodes visible again and edges inserted, whose edges may show
+        let mut revived = BTreeSet::new();
+        let mut inserted = BTreeSet::new();
+        for (node_id, entry) in &other.nodes {
+            if !merge_entry(&mut self.nodes, node_id, entry) {
+                continue;
+            }
+            match &entry.1 {
+                Some(data) => {
+                    if self
+                        .dag
+                        .insert_node(node_id.clone(), data.clone())
+                        .is_none()
+                    {
+                        revived.insert(node_id);
+                    }
+                }
+                None => {
+                    rebuild |= hidden;
+                    self.dag.remove_node(node_id.clone());
+                }
+            }
+        }
+        for (key, entry) in &other.edges {
+            let live = matches!(self.edges.get(key), Some((_, Some(_))));
+            if !merge_entry(&mut self.edges, key, entry) {
+                continue;
+            }
+            let (from, to) = key;
+            match &entry.1 {
+                // the edge moves to its new timestamp
+                Some(data) if live => {
+                    rebuild |= hidden;
+                    if self.dag.contains_edge(from, to) {
+                        let _ = self.dag.insert_edge(from.clone(), to.clone(), data.clone());
+                    }
+                }
+                Some(_) => {
+                    inserted.insert(key);
+                }
+                None => {
+                    rebuild |= hidden;
+                    // the edge may be to a node NOT visible
+                    let _ = self.dag.remove_edge(from.clone(), to.clone());
+                }
+            }
+        }
+        if !rebuild && (!revived.is_empty() || !inserted.is_empty()) {
+            let edges = self.edges.iter().filter(|(key, _)| {
+                let (from, to) = key;
+                inserted.contains(key) || revived.contains(from) || revived.contains(to)
+            });
+            rebuild = !show(&mut self.dag, edges);
+        }
+        if rebuild {
+            self.rebuild();
+        }
+    }
+
+    /// Derive the visible `Dag` from the state
+    fn rebuild(&mut self) {
+        let mut dag = self
+            .nodes
+            .iter()
+            .filter_map(|(id, (_, data))| Some((id.clone(), data.clone()?)))
+            .collect::<Dag<_, _, _>>();
+        let mut edges = self
+            .edges
+            .iter()
+            .filter_map(|((from, to), (timestamp, data))| {
+                Some((timestamp, from, to, data.as_ref()?))
+            })
+            .collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|(timestamp, _, _, _)| **timestamp);
+        for (_, from, to, data) in edges {
+            // an edge to a removed node or making a cycle is hidden
+            let _ = dag.insert_edge(from.clone(), to.clone(), data.clone());
+        }
+        self.dag = dag;
+    }
+}
+
+/// Keep the newer entry of `key`
+/// # Returns
+/// * Return `true` when `entry` is newer and kept
+fn merge_entry<K, T>(
+    entries: &mut BTreeMap<K, (Timestamp, T)>,
+    key: &K,
+    entry: &(Timestamp, T),
+) -> bool
+where
+    K: Clone + Ord,
+    T: Clone,
+{
+    match entries.get(key) {
+        Some((timestamp, _)) if *timestamp >= entry.0 => false,
+        _ => {
+            entries.insert(key.clone(), entry.clone());
+            true
+        }
+    }
+}
+
+/// Insert live `edges` into the visible `Dag`, skipping the ones to nodes NOT visible
+/// # Returns
+/// * Return `false` when an edge makes a cycle, then which edge is hidden depends on
+///   timestamps, so the visible `Dag` must be rebuilt
+/// # Remarks
+/// * When no edge makes a cycle, hidden edges are still hidden by the same paths
+///   and all the other live edges are visible, the same as a rebuild
+fn show<'a, NodeId, NodeData, EdgeData>(
+    dag: &mut Dag<NodeId, NodeData, EdgeData>,
+    edges: impl IntoIterator<Item = (&'a (NodeId, NodeId), &'a (Timestamp, Option<EdgeData>))>,
+) -> bool
+where
+    NodeId: Clone + Ord + 'a,
+    EdgeData: Clone + 'a,
+{
+    edges.into_iter().all(|((from, to), (_, data))| match data {
+        Some(data) if dag.contains_node(from) && dag.contains_node(to) => dag
+            .insert_edge(from.clone(), to.clone(), data.clone())
+            .is_ok(),
+        _ => true,
+    })
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for CrdtDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
@@ -0,0 +1,219 @@
+//! CSV node and edge lists with typed data, enabled by feature `csv`
+//! # Schema
+//! * Nodes: `id,<NodeData fields>...`
+//! * Edges: `from,to,<EdgeData fields>...`
+//!
+//! The first line is a header and is skipped, columns are read by position.
+//! `NodeData` and `EdgeData` are read by serde from the rest columns,
+//! so a struct takes one column per field and `()` takes none.
+//! # Remarks
+//! * Errors refer to records by their line numbers, the header is line 1
+use std::{collections::BTreeMap, error::Error, fmt::Display, io::Read};
+
+use serde::de::DeserializeOwned;
+
+use crate::{Dag, DagError};
+
+/// Errors when reading CSV
+#[derive(Debug)]
+pub enum CsvError<NodeId, EdgeData> {
+    /// The nodes CSV is NOT valid or a record doesn't match the types,
+    /// the error has the position of the record
+    Nodes(csv::Error),
+    /// The edges CSV is NOT valid or a record doesn't match the types,
+    /// the error has the position of the record
+    Edges(csv::Error),
+    /// The record at `line` breaks `Dag`, which is `DuplicateNode` in the nodes CSV
+    /// or `NodeNotFound` in the edges CSV
+    Dag {
+        line: u64,
+        error: DagError<NodeId, EdgeData>,
+    },
+    /// The edge at `line` is already at `first_line`
+    DuplicateEdge {
+        line: u64,
+        first_line: u64,
+        from: NodeId,
+        to: NodeId,
+    },
+    /// The edge at `line` makes a cycle with the edges at `cycle_lines`
+    /// # Remarks
+    /// * `error` is `HasCycle(from,to,data,path)`, `cycle_lines` are lines of the edges
+    ///   along `path`
+    Cycle {
+        line: u64,
+        cycle_lines: Vec<u64>,
+        error: DagError<NodeId, EdgeData>,
+    },
+}
+
+impl<NodeId, EdgeData> Display for CsvError<NodeId, EdgeData>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CsvError::Nodes(error) => writeln!(f, "Invalid nodes CSV: {}", error),
+            CsvError::Edges(error) => writeln!(f, "Invalid edges CSV: {}", error),
+            CsvError::Dag { line, error } => write!(f, "At line {}: {}", line, error),
+            CsvError::DuplicateEdge {
+                line,
+                first_line,
+                from,
+                to,
+            } => writeln!(
+                f,
+                "At line {}: edge '{}' -> '{}' is already at line {}.",
+                line, from, to, first_line
+            ),
+            CsvError::Cycle {
+                line,
+                cycle_lines,
+                error,
+            } => {
+                write!(f, "At line {}: {}", line, error)?;
+                write!(f, "Edges of the cycle are at lines")?;
+                for cycle_line in cycle_lines {
+                    write!(f, " {}", cycle_line)?;
+                }
+                writeln!(f)
+            }
+        }
+    }
+}
+
+impl<NodeId, EdgeData> Error for CsvError<NodeId, EdgeData>
+where
+    NodeId: core::fmt::Debug + Display,
+    EdgeData: core::fmt::Debug,
+{
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + DeserializeOwned,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    /// Read `Dag` from a nodes CSV and an edges CSV
+    /// # Errors
+    /// * `Err(Nodes(_))` or `Err(Edges(_))` when a record can't be read
+    /// * `Err(Dag{error: DuplicateNode(id),..})` when a node is repeated
+    /// * `Err(Dag{error: NodeNotFound(id),..})` when an edge refers to a node NOT found
+    /// * `Err(DuplicateEdge{..})` when an edge is repeated
+    /// * `Err(Cycle{..})` when edges make a cycle
+    pub fn from_csv<RN, RE>(nodes: RN, edges: RE) -> Result<Self, CsvError<NodeId, EdgeData>>
+    where
+        RN: Read,
+        RE: Read,
+    {
+        let mut dag = Dag::new();
+        let mut reader = csv::Reader::from_reader(nodes);
+        let mut record = csv::StringRecord::new();
+        while reader.read_record(&mut record).map_err(CsvError::Nodes)? {
+            let line = line_of(&record);
+            let (node_id, data): (NodeId, NodeData) = split(&record, 1).map_err(CsvError::Nodes)?;
+            if dag.contains_node(&node_id) {
+                let error = DagError::DuplicateNode(node_id);
+                return Err(CsvError::Dag { line, error });
+            }
+            dag.insert_node(node_id, data);
+        }
+        dag.read_csv_edges(edges, |_, _| false)?;
+        Ok(dag)
+    }
+
+    /// Read `Dag` from an edges CSV only, nodes are created with `NodeData::default()`
+    /// # Errors
+    /// * The same as [`Dag::from_csv`], except that nodes are never missing or repeated
+    pub fn from_csv_edges<R>(edges: R) -> Result<Self, CsvError<NodeId, EdgeData>>
+    where
+        R: Read,
+        NodeData: Default,
+    {
+        let mut dag = Dag::new();
+        dag.read_csv_edges(edges, |dag: &mut Self, node_id: &NodeId| {
+            dag.insert_node(node_id.clone(), NodeData::default());
+            true
+        })?;
+        Ok(dag)
+    }
+
+    /// Insert all the edges in CSV, `create_node` is called for a missing node
+    /// and returns `false` to report it
+    fn read_csv_edges<R, F>(
+        &mut self,
+        edges: R,
+        mut create_node: F,
+    ) -> Result<(), CsvError<NodeId, EdgeData>>
+    where
+        R: Read,
+        F: FnMut(&mut Self, &NodeId) -> bool,
+    {
+        let mut lines = BTreeMap::new();
+        let mut reader = csv::Reader::from_reader(edges);
+        let mut record = csv::StringRecord::new();
+        while reader.read_record(&mut record).map_err(CsvError::Edges)? {
+            let line = line_of(&record);
+            let ((from, to), data): ((NodeId, NodeId), EdgeData) =
+                split(&record, 2).map_err(CsvError::Edges)?;
+            for node_id in [&from, &to] {
+                if !self.contains_node(node_id) && !create_node(self, node_id) {
+                    let error = DagError::NodeNotFound(node_id.clone());
+                    return Err(CsvError::Dag { line, error });
+                }
+            }
+            if let Some(first_line) = lines.get(&(from.clone(), to.clone())) {
+                return Err(CsvError::DuplicateEdge {
+                    line,
+                    first_line: *first_line,
+                    from,
+                    to,
+                });
+            }
+            match self.insert_edge(from.clone(), to.clone(), data) {
+                Ok(_) => {
+                    lines.insert((from, to), line);
+                }
+                Err(error) => {
+                    let cycle_lines = match &error {
+                        DagError::HasCycle(_, _, _, path) => path
+                            .windows(2)
+                            .map(|pair| lines[&(pair[0].clone(), pair[1].clone())])
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    return Err(CsvError::Cycle {
+                        line,
+                        cycle_lines,
+                        error,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The line number where `record` starts
+fn line_of(record: &csv::StringRecord) -> u64 {
+    record
+        .position()
+        .map(csv::Position::line)
+        .unwrap_or_else(|| unreachable!("records read by Reader have positions"))
+}
+
+/// Read the first `len` fields of `record` as `K` and the rest as `D`,
+/// so `D` like `()` may take no field
+fn split<K, D>(record: &csv::StringRecord, len: usize) -> csv::Result<(K, D)>
+where
+    K: DeserializeOwned,
+    D: DeserializeOwned,
+{
+    let mut keys = record.iter().take(len).collect::<csv::StringRecord>();
+    let mut data = record.iter().skip(len).collect::<csv::StringRecord>();
+    // keep the position for errors
+    keys.set_position(record.position().cloned());
+    data.set_position(record.position().cloned());
+    Ok((keys.deserialize(None)?, data.deserialize(None)?))
+}
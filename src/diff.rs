@@ -0,0 +1,198 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use crate::{Dag, DagError};
+
+/// Differences between two `Dag`s, created by [`Dag::diff`]
+/// # Remarks
+/// * Edges of removed nodes are NOT listed in `removed_edges`,
+///   they are removed together with the nodes
+/// * `changed_*` store the new data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DagPatch<NodeId, NodeData, EdgeData> {
+    /// Nodes only in the new `Dag`
+    pub added_nodes: Vec<(NodeId, NodeData)>,
+    /// Nodes only in the old `Dag`
+    pub removed_nodes: Vec<NodeId>,
+    /// Nodes whose data was changed
+    pub changed_nodes: Vec<(NodeId, NodeData)>,
+    /// Edges only in the new `Dag`
+    pub added_edges: Vec<(NodeId, NodeId, EdgeData)>,
+    /// Edges only in the old `Dag`
+    pub removed_edges: Vec<(NodeId, NodeId)>,
+    /// Edges whose data was changed
+    pub changed_edges: Vec<(NodeId, NodeId, EdgeData)>,
+}
+
+impl<NodeId, NodeData, EdgeData> DagPatch<NodeId, NodeData, EdgeData> {
+    /// Check if the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone + PartialEq,
+    EdgeData: Clone + PartialEq,
+{
+    /// Compute the patch which turns `Dag` into `other`
+    pub fn diff(&self, other: &Self) -> DagPatch<NodeId, NodeData, EdgeData> {
+        let mut patch = DagPatch {
+            added_nodes: Vec::new(),
+            removed_nodes: Vec::new(),
+            changed_nodes: Vec::new(),
+            added_edges: Vec::new(),
+            removed_edges: Vec::new(),
+            changed_edges: Vec::new(),
+        };
+        for (node_id, data) in self.nodes() {
+            match other.nodes.get(&node_id) {
+                None => patch.removed_nodes.push(node_id),
+                Some(new_data) if new_data != data => {
+                    patch.changed_nodes.push((node_id, new_data.clone()))
+                }
+                _ => {}
+            }
+        }
+        for (node_id, data) in other.nodes() {
+            if !self.nodes.contains_key(&node_id) {
+                patch.added_nodes.push((node_id, data.clone()));
+            }
+        }
+        for (from, to, data) in self.edges() {
+            if !other.nodes.contains_key(&from) || !other.nodes.contains_key(&to) {
+                continue;
+            }
+            match other
+                .edges
+                .get(&from)
+                .and_then(|children| children.get(&to))
+            {
+                None => patch.removed_edges.push((from, to)),
+                Some(new_data) if new_data != data => {
+                    patch.changed_edges.push((from, to, new_data.clone()))
+                }
+                _ => {}
+            }
+        }
+        for (from, to, data) in other.edges() {
+            if !self.contains_edge(&from, &to) {
+                patch.added_edges.push((from, to, data.clone()));
+            }
+        }
+        patch
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Apply a patch created by [`Dag::diff`]
+    /// # Remarks
+    /// * Removals are applied before insertions
+    /// * It is all-or-nothing: the patch is checked before `Dag` is modified,
+    ///   and rolled back when an added edge makes a cycle
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when the patch removes or changes a node NOT found in `Dag`,
+    ///   or an added or changed edge refers to a node NOT found after patching
+    /// * `Err(DuplicateNode(id))` when an added node is already in `Dag`
+    /// * `Err(EdgeNotFound(from,to))` when the patch removes or changes an edge NOT found in `Dag`
+    /// * `Err(DuplicateEdge(from,to))` when an added edge is already in `Dag`
+    /// * `Err(HasCycle(from,to,data,path))` when an added edge makes a cycle
+    pub fn apply_patch(
+        &mut self,
+        patch: DagPatch<NodeId, NodeData, EdgeData>,
+    ) -> Result<(), DagError<NodeId, EdgeData>> {
+        self.check_patch(&patch)?;
+        self.transaction(|txn| {
+            for (from, to) in patch.removed_edges {
+                txn.remove_edge(from, to)?;
+            }
+            for node_id in patch.removed_nodes {
+                txn.remove_node(node_id);
+            }
+            for (node_id, data) in patch.added_nodes {
+                txn.insert_node(node_id, data);
+            }
+            for (node_id, data) in patch.changed_nodes {
+                txn.insert_node(node_id, data);
+            }
+            for (from, to, data) in patch.added_edges {
+                txn.insert_edge(from, to, data)?;
+            }
+            // changed edges are already in `Dag`, replacing their data can't make a cycle
+            for (from, to, data) in patch.changed_edges {
+                txn.insert_edge(from, to, data)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Check everything [`Dag::apply_patch`] can reject except cycles, without modifying `Dag`
+    fn check_patch(
+        &self,
+        patch: &DagPatch<NodeId, NodeData, EdgeData>,
+    ) -> Result<(), DagError<NodeId, EdgeData>> {
+        let mut removed_edges = BTreeSet::new();
+        for (from, to) in &patch.removed_edges {
+            if self.get_edge::<NodeId>(from, to)?.is_none() || !removed_edges.insert((from, to)) {
+                return Err(DagError::EdgeNotFound(from.clone(), to.clone()));
+            }
+        }
+        let mut removed_nodes = BTreeSet::new();
+        for node_id in &patch.removed_nodes {
+            if !self.nodes.contains_key(node_id) || !removed_nodes.insert(node_id) {
+                return Err(DagError::NodeNotFound(node_id.clone()));
+            }
+        }
+        let mut added_nodes = BTreeSet::new();
+        for (node_id, _) in &patch.added_nodes {
+            if self.nodes.contains_key(node_id) || !added_nodes.insert(node_id) {
+                return Err(DagError::DuplicateNode(node_id.clone()));
+            }
+        }
+
+        // whether a node or an edge is in `Dag` after patching
+        let has_node = |node_id: &NodeId| {
+            (self.nodes.contains_key(node_id) && !removed_nodes.contains(node_id))
+                || added_nodes.contains(node_id)
+        };
+        let check_nodes = |from: &NodeId, to: &NodeId| match [from, to]
+            .into_iter()
+            .find(|node_id| !has_node(node_id))
+        {
+            Some(node_id) => Err(DagError::NodeNotFound(node_id.clone())),
+            None => Ok(()),
+        };
+        let had_edge = |from: &NodeId, to: &NodeId| {
+            self.contains_edge::<NodeId>(from, to) && !removed_edges.contains(&(from, to))
+        };
+
+        for (node_id, _) in &patch.changed_nodes {
+            if !has_node(node_id) {
+                return Err(DagError::NodeNotFound(node_id.clone()));
+            }
+        }
+        let mut added_edges = BTreeSet::new();
+        for (from, to, _) in &patch.added_edges {
+            check_nodes(from, to)?;
+            if had_edge(from, to) || !added_edges.insert((from, to)) {
+                return Err(DagError::DuplicateEdge(from.clone(), to.clone()));
+            }
+        }
+        for (from, to, _) in &patch.changed_edges {
+            check_nodes(from, to)?;
+            if !had_edge(from, to) && !added_edges.contains(&(from, to)) {
+                return Err(DagError::EdgeNotFound(from.clone(), to.clone()));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,532 @@
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Dag, DagError, NodeNotFound};
+
+/// Position of data in the file of [`DiskDag`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataSlot {
+    offset: u64,
+    len: u64,
+}
+
+/// Errors of [`DiskDag`]
+#[derive(Debug)]
+pub enum DiskError<NodeId, EdgeData> {
+    /// Reading or writing the file failed
+    Io(io::Error),
+    /// Data can't be encoded or decoded by postcard
+    Encoding(postcard::Error),
+    /// The file is NOT of `DiskDag` or the record at the offset can't be applied,
+    /// the file is corrupted
+    InvalidRecord(u64),
+    /// The mutation is invalid, see [`DagError`]
+    Dag(DagError<NodeId, EdgeData>),
+}
+
+impl<NodeId, EdgeData> From<io::Error> for DiskError<NodeId, EdgeData> {
+    fn from(error: io::Error) -> Self {
+        DiskError::Io(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<postcard::Error> for DiskError<NodeId, EdgeData> {
+    fn from(error: postcard::Error) -> Self {
+        DiskError::Encoding(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<DagError<NodeId, EdgeData>> for DiskError<NodeId, EdgeData> {
+    fn from(error: DagError<NodeId, EdgeData>) -> Self {
+        DiskError::Dag(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<NodeNotFound<NodeId>> for DiskError<NodeId, EdgeData> {
+    fn from(error: NodeNotFound<NodeId>) -> Self {
+        DiskError::Dag(error.into())
+    }
+}
+
+impl<NodeId, EdgeData> Display for DiskError<NodeId, EdgeData>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiskError::Io(error) => writeln!(f, "Cannot access the data file: {}", error),
+            DiskError::Encoding(error) => writeln!(f, "Cannot encode or decode data: {}", error),
+            DiskError::InvalidRecord(offset) => {
+                writeln!(
+                    f,
+                    "Cannot load the record of the data file at offset {}.",
+                    offset
+                )
+            }
+            DiskError::Dag(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<NodeId, EdgeData> std::error::Error for DiskError<NodeId, EdgeData>
+where
+    NodeId: std::fmt::Debug + Display,
+    EdgeData: std::fmt::Debug,
+{
+}
+
+/// A mutation of the structure, the order of variants is a part of the format
+/// # Remarks
+/// * The data of an insertion follows its record, whose `u64` is the length of the data
+#[derive(Serialize, Deserialize)]
+enum Record<NodeId> {
+    InsertNode(NodeId, u64),
+    InsertEdge(NodeId, NodeId, u64),
+    RemoveEdge(NodeId, NodeId),
+    RemoveNode(NodeId),
+}
+
+/// The first bytes of a file of [`DiskDag`]
+const MAGIC: &[u8; 8] = b"xdagdisk";
+
+/// A `Dag` keeping only the structure in memory, data of nodes and edges is in a file
+/// # Remarks
+/// * Enabled by feature `disk`, data is encoded by postcard
+/// * Reads of the structure go to `Dag` by `Deref`, whose data are [`DataSlot`]s
+/// * Data is read from the file by [`DiskDag::get_node`] and [`DiskDag::get_edge`]
+/// * The file is appended only, replaced and removed data is garbage until
+///   [`DiskDag::compact`]
+/// * The file is a header and a sequence of entries, a `u32` little-endian length,
+///   a postcard encoded mutation and the data it inserts each,
+///   so the structure is loaded again by [`DiskDag::open`]
+#[derive(Debug)]
+pub struct DiskDag<NodeId, NodeData, EdgeData> {
+    dag: Dag<NodeId, DataSlot, DataSlot>,
+    path: PathBuf,
+    // locked by reads, which seek the file
+    file: Mutex<File>,
+    file_len: u64,
+    garbage_len: u64,
+    _data: PhantomData<fn() -> (NodeData, EdgeData)>,
+}
+
+impl<NodeId, NodeData, EdgeData> DiskDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize + DeserializeOwned,
+    NodeData: Serialize + DeserializeOwned,
+    EdgeData: Serialize + DeserializeOwned,
+{
+    /// Create an empty DAG whose data is in a file at `path`, the file is truncated
+    /// # Errors
+    /// * `Err(_)` when the file can't be created
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = create_file(path.as_ref())?;
+        file.write_all(MAGIC)?;
+        Ok(DiskDag {
+            dag: Dag::new(),
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(file),
+            file_len: MAGIC.len() as u64,
+            garbage_len: 0,
+            _data: PhantomData,
+        })
+    }
+
+    /// Open the file at `path` written by `DiskDag`, loading the structure from it
+    /// # Remarks
+    /// * Data is NOT read, only the records of mutations are
+    /// * An entry torn by a crash at the end of the file is cut
+    /// # Errors
+    /// * `Err(Io(_))` when the file can't be opened or read
+    /// * `Err(Encoding(_))` when a complete record is invalid
+    /// * `Err(InvalidRecord(offset))` when the header is NOT of `DiskDag`,
+    ///   or the record at the offset can't be applied
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DiskError<NodeId, EdgeData>> {
+        let file = File::options().read(true).write(true).open(path.as_ref())?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(File::open(path.as_ref())?);
+        let mut magic = [0; 8];
+        if len < MAGIC.len() as u64 {
+            return Err(DiskError::InvalidRecord(0));
+        }
+        reader.read_exact(&mut magic)?;
+        if magic != *MAGIC {
+            return Err(DiskError::InvalidRecord(0));
+        }
+        let mut dag = DiskDag {
+            dag: Dag::new(),
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(file),
+            file_len: MAGIC.len() as u64,
+            garbage_len: 0,
+            _data: PhantomData,
+        };
+        let mut offset = dag.file_len;
+        while offset + 4 <= len {
+            let mut record_len = [0; 4];
+            reader.read_exact(&mut record_len)?;
+            let record_len = u64::from(u32::from_le_bytes(record_len));
+            if offset + 4 + record_len > len {
+                break;
+            }
+            let mut bytes = vec![0; record_len as usize];
+            reader.read_exact(&mut bytes)?;
+            let record = postcard::from_bytes(&bytes)?;
+            let data_len = match record {
+                Record::InsertNode(_, data_len) | Record::InsertEdge(_, _, data_len) => data_len,
+                Record::RemoveEdge(..) | Record::RemoveNode(_) => 0,
+            };
+            let slot = DataSlot {
+                offset: offset + 4 + record_len,
+                len: data_len,
+            };
+            if slot.offset + slot.len > len {
+                break;
+            }
+            let applied = match record {
+                Record::InsertNode(node_id, _) => {
+                    dag.put_node(node_id, slot);
+                    true
+                }
+                Record::InsertEdge(from, to, _) => dag.put_edge(from, to, slot).is_ok(),
+                Record::RemoveEdge(from, to) => dag.take_edge(from, to) == Ok(true),
+                Record::RemoveNode(node_id) => dag.take_node(node_id),
+            };
+            if !applied {
+                return Err(DiskError::InvalidRecord(offset));
+            }
+            reader.seek_relative(data_len as i64)?;
+            offset = slot.offset + slot.len;
+        }
+        // cut the torn entry
+        dag.file
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_len(offset)?;
+        dag.file_len = offset;
+        Ok(dag)
+    }
+
+    /// Get the count of bytes in the file
+    pub fn file_len(&self) -> u64 {
+        self.file_len
+    }
+
+    /// Get the count of bytes of replaced and removed data in the file
+    pub fn garbage_len(&self) -> u64 {
+        self.garbage_len
+    }
+
+    /// Insert a node with data, the data is written to the file
+    /// # Returns
+    /// * Return `Ok(true)` when `node_id` is already in `DiskDag` and its data is replaced
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the data can't be written
+    pub fn insert_node(
+        &mut self,
+        node_id: NodeId,
+        node_data: &NodeData,
+    ) -> Result<bool, DiskError<NodeId, EdgeData>> {
+        let data = postcard::to_allocvec(node_data)?;
+        let slot = self.append(Record::InsertNode(&node_id, data.len() as u64), &data)?;
+        Ok(self.put_node(node_id, slot))
+    }
+
+    /// Insert an edge with data, the data is written to the file
+    /// # Returns
+    /// * Return `Ok(true)` when there is a same edge in `DiskDag` and its data is replaced
+    /// # Errors
+    /// * `Err(Dag(NodeNotFound(id)))` when `from` or `to` is NOT found in `DiskDag`
+    /// * `Err(Dag(HasCycle(from,to,data,path)))` when a cycle is detected,
+    ///   nothing is written
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the data can't be written
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<bool, DiskError<NodeId, EdgeData>> {
+        if self.dag.would_create_cycle(&from, &to)? {
+            // it fails with the path of the cycle, without mutating
+            return match self
+                .dag
+                .insert_edge(from, to, DataSlot { offset: 0, len: 0 })
+            {
+                Err(DagError::HasCycle(from, to, _, path)) => Err(DiskError::Dag(
+                    DagError::HasCycle(from, to, edge_data, path),
+                )),
+                _ => unreachable!("would_create_cycle detected a cycle"),
+            };
+        }
+        let data = postcard::to_allocvec(&edge_data)?;
+        let slot = self.append(Record::InsertEdge(&from, &to, data.len() as u64), &data)?;
+        Ok(self
+            .put_edge(from, to, slot)
+            .unwrap_or_else(|_| unreachable!("proved by would_create_cycle")))
+    }
+
+    /// Remove an edge
+    /// # Returns
+    /// * Return `Ok(true)` when success
+    /// * Return `Ok(false)` when there is no such edge, nothing is written
+    /// # Errors
+    /// * `Err(Dag(NodeNotFound(id)))` when `from` or `to` is NOT found in `DiskDag`
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the removal can't be written
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<bool, DiskError<NodeId, EdgeData>> {
+        if self.dag.get_edge(&from, &to)?.is_none() {
+            return Ok(false);
+        }
+        self.append(Record::RemoveEdge(&from, &to), &[])?;
+        Ok(self.take_edge(from, to)?)
+    }
+
+    /// Remove a node and all edges related
+    /// # Returns
+    /// * Return `Ok(false)` when `node_id` is NOT found in `DiskDag`, nothing is written
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the removal can't be written
+    pub fn remove_node(&mut self, node_id: NodeId) -> Result<bool, DiskError<NodeId, EdgeData>> {
+        if !self.dag.contains_node(&node_id) {
+            return Ok(false);
+        }
+        self.append(Record::RemoveNode(&node_id), &[])?;
+        Ok(self.take_node(node_id))
+    }
+
+    /// Read data of a node from the file
+    /// # Returns
+    /// Return `Ok(None)` if `node_id` is not found in `DiskDag`
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the data can't be read
+    pub fn get_node<Q>(&self, node_id: &Q) -> Result<Option<NodeData>, DiskError<NodeId, EdgeData>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag
+            .get_node(node_id)
+            .map(|slot| self.read(*slot))
+            .transpose()
+    }
+
+    /// Read data of an edge from the file
+    /// # Returns
+    /// Return `Ok(None)` if there is no such edge
+    /// # Errors
+    /// * `Err(Dag(NodeNotFound(id)))` when `from` or `to` is NOT found in `DiskDag`
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the data can't be read
+    pub fn get_edge<Q>(
+        &self,
+        from: &Q,
+        to: &Q,
+    ) -> Result<Option<EdgeData>, DiskError<NodeId, EdgeData>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        self.dag
+            .get_edge(from, to)?
+            .map(|slot| self.read(*slot))
+            .transpose()
+    }
+
+    /// Remove all nodes and edges, the file is truncated to its header
+    /// # Errors
+    /// * `Err(_)` when the file can't be truncated
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.dag.clear();
+        self.file_len = MAGIC.len() as u64;
+        self.garbage_len = 0;
+        self.file
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_len(self.file_len)
+    }
+
+    /// Rewrite the file without garbage
+    /// # Remarks
+    /// * Live data is copied into a new file next to the old one, which replaces it
+    /// * The new file inserts nodes then edges, without removals
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the files can't be read, written or renamed,
+    ///   `DiskDag` is unchanged
+    pub fn compact(&mut self) -> Result<(), DiskError<NodeId, EdgeData>> {
+        let mut compact_path = self.path.clone().into_os_string();
+        compact_path.push(".compact");
+        let compact_path = PathBuf::from(compact_path);
+        let mut compact = BufWriter::new(create_file(&compact_path)?);
+        compact.write_all(MAGIC)?;
+        let file = self.file.get_mut().unwrap_or_else(PoisonError::into_inner);
+        // new slots in the order of nodes, then edges
+        let mut slots = Vec::new();
+        let mut file_len = MAGIC.len() as u64;
+        let nodes = self
+            .dag
+            .nodes()
+            .map(|(node_id, slot)| (Record::InsertNode(node_id, slot.len), *slot));
+        let edges = self
+            .dag
+            .edges()
+            .map(|(from, to, slot)| (Record::InsertEdge(from, to, slot.len), *slot));
+        for (record, slot) in nodes.chain(edges) {
+            let mut data = vec![0; slot.len as usize];
+            file.seek(SeekFrom::Start(slot.offset))?;
+            file.read_exact(&mut data)?;
+            let (bytes, data_offset) = entry(&record, &data)?;
+            compact.write_all(&bytes)?;
+            slots.push(DataSlot {
+                offset: file_len + data_offset,
+                len: slot.len,
+            });
+            file_len += bytes.len() as u64;
+        }
+        let compact = compact.into_inner().map_err(|error| error.into_error())?;
+        fs::rename(&compact_path, &self.path)?;
+
+        let mut slots = slots.into_iter();
+        for ((_, slot), new_slot) in self.dag.nodes_mut().zip(&mut slots) {
+            *slot = new_slot;
+        }
+        for ((_, _, slot), new_slot) in self.dag.edges_mut().zip(&mut slots) {
+            *slot = new_slot;
+        }
+        *file = compact;
+        self.file_len = file_len;
+        self.garbage_len = 0;
+        Ok(())
+    }
+
+    /// Write an entry at the end of the file
+    /// # Returns
+    /// * Return the slot of `data` in the file
+    fn append(
+        &mut self,
+        record: Record<&NodeId>,
+        data: &[u8],
+    ) -> Result<DataSlot, DiskError<NodeId, EdgeData>> {
+        let (bytes, data_offset) = entry(&record, data)?;
+        let file = self.file.get_mut().unwrap_or_else(PoisonError::into_inner);
+        file.seek(SeekFrom::Start(self.file_len))?;
+        if let Err(error) = file.write_all(&bytes) {
+            // drop the partial entry
+            file.set_len(self.file_len)?;
+            return Err(error.into());
+        }
+        let slot = DataSlot {
+            offset: self.file_len + data_offset,
+            len: data.len() as u64,
+        };
+        self.file_len += bytes.len() as u64;
+        Ok(slot)
+    }
+
+    fn read<T: DeserializeOwned>(&self, slot: DataSlot) -> Result<T, DiskError<NodeId, EdgeData>> {
+        let mut bytes = vec![0; slot.len as usize];
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.seek(SeekFrom::Start(slot.offset))?;
+        file.read_exact(&mut bytes)?;
+        Ok(postcard::from_bytes(&bytes)?)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> DiskDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Insert the slot of a node written already, shared by mutations and [`DiskDag::open`]
+    fn put_node(&mut self, node_id: NodeId, slot: DataSlot) -> bool {
+        let old = self.dag.insert_node(node_id, slot);
+        if let Some(old) = old {
+            self.garbage_len += old.len;
+        }
+        old.is_some()
+    }
+
+    /// Insert the slot of an edge written already
+    fn put_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        slot: DataSlot,
+    ) -> Result<bool, DagError<NodeId, DataSlot>> {
+        let old = self.dag.insert_edge(from, to, slot)?;
+        if let Some(old) = old {
+            self.garbage_len += old.len;
+        }
+        Ok(old.is_some())
+    }
+
+    /// Remove an edge, its data becomes garbage
+    fn take_edge(&mut self, from: NodeId, to: NodeId) -> Result<bool, NodeNotFound<NodeId>> {
+        match self.dag.remove_edge(from, to) {
+            Ok(old) => {
+                if let Some(old) = old {
+                    self.garbage_len += old.len;
+                }
+                Ok(old.is_some())
+            }
+            Err(DagError::NodeNotFound(id)) => Err(NodeNotFound(id)),
+            Err(_) => unreachable!("remove_edge returns no other error"),
+        }
+    }
+
+    /// Remove a node and all edges related, their data becomes garbage
+    fn take_node(&mut self, node_id: NodeId) -> bool {
+        let (slot, edges) = self.dag.remove_node(node_id);
+        let removed = slot.is_some();
+        self.garbage_len += slot
+            .into_iter()
+            .chain(edges.into_iter().map(|(_, _, slot)| slot))
+            .map(|slot| slot.len)
+            .sum::<u64>();
+        removed
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for DiskDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, DataSlot, DataSlot>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
+
+/// Encode a record followed by its data
+/// # Returns
+/// * Return the bytes of the entry and the offset of `data` in them
+fn entry<NodeId: Serialize>(
+    record: &Record<NodeId>,
+    data: &[u8],
+) -> Result<(Vec<u8>, u64), postcard::Error> {
+    let encoded = postcard::to_allocvec(record)?;
+    let len = u32::try_from(encoded.len()).map_err(|_| postcard::Error::SerializeBufferFull)?;
+    let mut bytes = len.to_le_bytes().to_vec();
+    bytes.extend(encoded);
+    let data_offset = bytes.len() as u64;
+    bytes.extend(data);
+    Ok((bytes, data_offset))
+}
+
+/// Create or truncate a file for reading and writing
+fn create_file(path: &Path) -> io::Result<File> {
+    File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+}
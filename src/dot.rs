@@ -0,0 +1,653 @@
+//! Graphviz DOT support
+//! # Remarks
+//! * Write `Dag` by [`Dag::to_dot`] or [`Dag::to_dot_with`], parse it by [`parse`]
+//! * Only `digraph` is supported since `Dag` is directed
+//! * Node ids are owned `String`s, quoted ids and attributes are stored without quotes
+//!   and `\"` and `\\` in them are unescaped
+//! * Ports (`a:port`) and graph attributes are ignored
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{Display, Write};
+
+use crate::{Dag, DagError};
+
+/// Attributes of a node or an edge in DOT
+pub type Attributes = BTreeMap<String, String>;
+
+/// `Dag` built from DOT
+pub type DotDag = Dag<String, Attributes, Attributes>;
+
+/// Errors when parsing DOT
+#[derive(Debug, Clone)]
+pub enum DotError {
+    /// Input is NOT valid DOT
+    Syntax { line: usize, message: String },
+    /// The edge statement at `line` makes a cycle which will destroy `Dag`
+    Dag {
+        line: usize,
+        error: DagError<String, Attributes>,
+    },
+}
+
+impl Display for DotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DotError::Syntax { line, message } => {
+                writeln!(f, "Syntax error at line {}: {}", line, message)
+            }
+            DotError::Dag { line, error } => write!(f, "At line {}: {}", line, error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DotError {}
+
+/// Styling of DOT written by [`Dag::to_dot_with`]
+/// # Remarks
+/// * Ids and attributes are written quoted, `\` and `"` in them are escaped
+/// * Empty attributes are omitted
+pub struct DotConfig<'f, NodeId, NodeData, EdgeData> {
+    name: Option<String>,
+    graph: Attributes,
+    node_defaults: Attributes,
+    edge_defaults: Attributes,
+    #[allow(clippy::type_complexity)]
+    node_attributes: Box<dyn FnMut(&NodeId, &NodeData) -> Attributes + 'f>,
+    #[allow(clippy::type_complexity)]
+    edge_attributes: Box<dyn FnMut(&NodeId, &NodeId, &EdgeData) -> Attributes + 'f>,
+}
+
+impl<NodeId, NodeData, EdgeData> core::fmt::Debug for DotConfig<'_, NodeId, NodeData, EdgeData> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DotConfig")
+            .field("name", &self.name)
+            .field("graph", &self.graph)
+            .field("node_defaults", &self.node_defaults)
+            .field("edge_defaults", &self.edge_defaults)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Default for DotConfig<'_, NodeId, NodeData, EdgeData> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'f, NodeId, NodeData, EdgeData> DotConfig<'f, NodeId, NodeData, EdgeData> {
+    /// Create a config writing the bare structure
+    pub fn new() -> Self {
+        DotConfig {
+            name: None,
+            graph: Attributes::new(),
+            node_defaults: Attributes::new(),
+            edge_defaults: Attributes::new(),
+            node_attributes: Box::new(|_, _| Attributes::new()),
+            edge_attributes: Box::new(|_, _, _| Attributes::new()),
+        }
+    }
+
+    /// Set the name of the digraph
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the direction of ranks, like `"TB"` or `"LR"`
+    pub fn rankdir(self, rankdir: impl Into<String>) -> Self {
+        self.graph_attribute("rankdir", rankdir)
+    }
+
+    /// Set an attribute of the digraph, like `bgcolor`
+    pub fn graph_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.graph.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set an attribute of all the nodes, written as `node [key="value"]`
+    pub fn node_default(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.node_defaults.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set an attribute of all the edges, written as `edge [key="value"]`
+    pub fn edge_default(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.edge_defaults.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set attributes of every node by `f(id,data)`, like `label`, `shape` or `color`
+    pub fn node_attributes<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&NodeId, &NodeData) -> Attributes + 'f,
+    {
+        self.node_attributes = Box::new(f);
+        self
+    }
+
+    /// Set attributes of every edge by `f(from,to,data)`
+    pub fn edge_attributes<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&NodeId, &NodeId, &EdgeData) -> Attributes + 'f,
+    {
+        self.edge_attributes = Box::new(f);
+        self
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Display,
+{
+    /// Write `Dag` as a DOT digraph, nodes are named by their ids
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(DotConfig::new())
+    }
+
+    /// Write `Dag` as a DOT digraph styled by `config`
+    /// # Remarks
+    /// * Nodes are written in the order of `NodeId`, then edges
+    /// * The output is accepted by [`parse`]
+    pub fn to_dot_with(&self, mut config: DotConfig<'_, NodeId, NodeData, EdgeData>) -> String {
+        let mut output = String::from("digraph ");
+        if let Some(name) = &config.name {
+            output.push_str(&quote(name));
+            output.push(' ');
+        }
+        output.push_str("{\n");
+        // writing to String never fails
+        for (key, value) in &config.graph {
+            let _ = writeln!(output, "    {}={};", quote(key), quote(value));
+        }
+        if !config.node_defaults.is_empty() {
+            let _ = writeln!(output, "    node{};", attr_list(&config.node_defaults));
+        }
+        if !config.edge_defaults.is_empty() {
+            let _ = writeln!(output, "    edge{};", attr_list(&config.edge_defaults));
+        }
+        for (id, data) in &self.nodes {
+            let attrs = (config.node_attributes)(id, data);
+            let _ = writeln!(
+                output,
+                "    {}{};",
+                quote(&id.to_string()),
+                attr_list(&attrs)
+            );
+        }
+        for (from, children) in &self.edges {
+            for (to, data) in children {
+                let attrs = (config.edge_attributes)(from, to, data);
+                let _ = writeln!(
+                    output,
+                    "    {} -> {}{};",
+                    quote(&from.to_string()),
+                    quote(&to.to_string()),
+                    attr_list(&attrs)
+                );
+            }
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+/// Quote an ID, `\` and `"` in it are escaped
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Write ` [key="value", ...]`, or nothing when `attrs` is empty
+fn attr_list(attrs: &Attributes) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let attrs = attrs
+        .iter()
+        .map(|(key, value)| format!("{}={}", quote(key), quote(value)))
+        .collect::<Vec<_>>();
+    format!(" [{}]", attrs.join(", "))
+}
+
+/// Parse a DOT digraph into `Dag`
+/// # Errors
+/// * `Err(Syntax{..})` when `input` is NOT a valid DOT digraph
+/// * `Err(Dag{error: HasCycle(from,to,attrs,path),..})` when `input` has a cycle
+pub fn parse(input: &str) -> Result<DotDag, DotError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        dag: Dag::new(),
+    };
+    parser.graph()?;
+    Ok(parser.dag)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Id(&'a str),
+    Quoted(&'a str),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Equal,
+    Semicolon,
+    Comma,
+    Colon,
+    Arrow,
+    Line,
+}
+
+impl<'a> Token<'a> {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Id(id) if id.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn syntax_error<T>(line: usize, message: impl Into<String>) -> Result<T, DotError> {
+    Err(DotError::Syntax {
+        line,
+        message: message.into(),
+    })
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token<'_>, usize)>, DotError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut pos = 0;
+    let mut line_start = true;
+    while pos < bytes.len() {
+        let byte = bytes[pos];
+        // preprocessor lines
+        if line_start && byte == b'#' {
+            while pos < bytes.len() && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+            continue;
+        }
+        if byte == b'\n' {
+            line += 1;
+            line_start = true;
+            pos += 1;
+            continue;
+        }
+        if byte.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        line_start = false;
+        let rest = &input[pos..];
+        if rest.starts_with("//") {
+            while pos < bytes.len() && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+            continue;
+        }
+        if rest.starts_with("/*") {
+            let end = match rest.find("*/") {
+                Some(end) => end,
+                None => return syntax_error(line, "unterminated comment"),
+            };
+            line += rest[..end].matches('\n').count();
+            pos += end + 2;
+            continue;
+        }
+        if rest.starts_with("->") {
+            tokens.push((Token::Arrow, line));
+            pos += 2;
+            continue;
+        }
+        if rest.starts_with("--") {
+            tokens.push((Token::Line, line));
+            pos += 2;
+            continue;
+        }
+        let token = match byte {
+            b'{' => Token::LBrace,
+            b'}' => Token::RBrace,
+            b'[' => Token::LBracket,
+            b']' => Token::RBracket,
+            b'=' => Token::Equal,
+            b';' => Token::Semicolon,
+            b',' => Token::Comma,
+            b':' => Token::Colon,
+            b'"' => {
+                let start_line = line;
+                let start = pos + 1;
+                pos = start;
+                while pos < bytes.len() && bytes[pos] != b'"' {
+                    if bytes[pos] == b'\\' {
+                        pos += 1;
+                    }
+                    if pos < bytes.len() && bytes[pos] == b'\n' {
+                        line += 1;
+                    }
+                    pos += 1;
+                }
+                if pos >= bytes.len() {
+                    return syntax_error(start_line, "unterminated string");
+                }
+                tokens.push((Token::Quoted(&input[start..pos]), start_line));
+                pos += 1;
+                continue;
+            }
+            b'<' => {
+                // HTML string
+                let start_line = line;
+                let start = pos + 1;
+                let mut depth = 1;
+                pos = start;
+                while pos < bytes.len() && depth > 0 {
+                    match bytes[pos] {
+                        b'<' => depth += 1,
+                        b'>' => depth -= 1,
+                        b'\n' => line += 1,
+                        _ => {}
+                    }
+                    pos += 1;
+                }
+                if depth > 0 {
+                    return syntax_error(start_line, "unterminated HTML string");
+                }
+                tokens.push((Token::Quoted(&input[start..pos - 1]), start_line));
+                continue;
+            }
+            _ if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'.' || byte >= 0x80 => {
+                let start = pos;
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric()
+                        || bytes[pos] == b'_'
+                        || bytes[pos] == b'.'
+                        || bytes[pos] >= 0x80)
+                {
+                    pos += 1;
+                }
+                tokens.push((Token::Id(&input[start..pos]), line));
+                continue;
+            }
+            b'-' => {
+                // negative numeral
+                let start = pos;
+                pos += 1;
+                while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                    pos += 1;
+                }
+                if pos == start + 1 {
+                    return syntax_error(line, "unexpected '-'");
+                }
+                tokens.push((Token::Id(&input[start..pos]), line));
+                continue;
+            }
+            _ => {
+                return syntax_error(line, format!("unexpected character '{}'", byte as char));
+            }
+        };
+        tokens.push((token, line));
+        pos += 1;
+    }
+    Ok(tokens)
+}
+
+/// Default attributes set by `node [...]` and `edge [...]` in a scope
+#[derive(Debug, Clone, Default)]
+struct Defaults {
+    node: Attributes,
+    edge: Attributes,
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token<'a>, usize)>,
+    pos: usize,
+    dag: DotDag,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).map(|(token, _)| *token)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<Token<'a>> {
+        self.tokens.get(self.pos + n).map(|(token, _)| *token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: Token<'a>) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token<'a>, what: &str) -> Result<(), DotError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            syntax_error(self.line(), format!("expected {}", what))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, DotError> {
+        match self.next() {
+            Some(Token::Id(id)) => Ok(id.to_string()),
+            Some(Token::Quoted(id)) => Ok(unescape(id)),
+            _ => {
+                self.pos -= 1;
+                syntax_error(self.line(), "expected an ID")
+            }
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), DotError> {
+        if self.peek().is_some_and(|token| token.is_keyword("strict")) {
+            self.pos += 1;
+        }
+        match self.next() {
+            Some(token) if token.is_keyword("digraph") => {}
+            Some(token) if token.is_keyword("graph") => {
+                return syntax_error(self.line(), "undirected graph is NOT supported");
+            }
+            _ => return syntax_error(self.line(), "expected 'digraph'"),
+        }
+        if matches!(self.peek(), Some(Token::Id(_)) | Some(Token::Quoted(_))) {
+            self.pos += 1;
+        }
+        self.expect(Token::LBrace, "'{'")?;
+        self.stmt_list(&mut Defaults::default())?;
+        self.expect(Token::RBrace, "'}'")?;
+        if self.peek().is_some() {
+            return syntax_error(self.line(), "unexpected tokens after graph");
+        }
+        Ok(())
+    }
+
+    /// Parse statements until '}', returning all the nodes in this scope
+    fn stmt_list(&mut self, defaults: &mut Defaults) -> Result<Vec<String>, DotError> {
+        let mut nodes = Vec::new();
+        while !matches!(self.peek(), Some(Token::RBrace) | None) {
+            self.stmt(defaults, &mut nodes)?;
+            self.eat(Token::Semicolon);
+        }
+        Ok(nodes)
+    }
+
+    fn stmt(&mut self, defaults: &mut Defaults, nodes: &mut Vec<String>) -> Result<(), DotError> {
+        let token = match self.peek() {
+            Some(token) => token,
+            None => return syntax_error(self.line(), "unexpected end"),
+        };
+        if self.peek_nth(1) == Some(Token::LBracket) {
+            if token.is_keyword("graph") {
+                self.pos += 1;
+                self.attr_list()?;
+                return Ok(());
+            }
+            if token.is_keyword("node") {
+                self.pos += 1;
+                defaults.node.extend(self.attr_list()?);
+                return Ok(());
+            }
+            if token.is_keyword("edge") {
+                self.pos += 1;
+                defaults.edge.extend(self.attr_list()?);
+                return Ok(());
+            }
+        }
+        if self.peek_nth(1) == Some(Token::Equal) {
+            // graph attribute
+            self.id()?;
+            self.pos += 1;
+            self.id()?;
+            return Ok(());
+        }
+        let line = self.line();
+        let lhs = self.endpoint(defaults)?;
+        nodes.extend(lhs.iter().cloned());
+        if matches!(self.peek(), Some(Token::Arrow) | Some(Token::Line)) {
+            return self.edge_stmt(lhs, line, defaults, nodes);
+        }
+        let is_subgraph = token == Token::LBrace || token.is_keyword("subgraph");
+        if !is_subgraph {
+            let attrs = if self.peek() == Some(Token::LBracket) {
+                self.attr_list()?
+            } else {
+                Attributes::new()
+            };
+            let node = self
+                .dag
+                .get_node_mut(&lhs[0])
+                .unwrap_or_else(|| unreachable!("inserted by endpoint"));
+            node.extend(attrs);
+        }
+        Ok(())
+    }
+
+    /// Parse a node id or a subgraph, returning the nodes in it
+    fn endpoint(&mut self, defaults: &Defaults) -> Result<Vec<String>, DotError> {
+        match self.peek() {
+            Some(token) if token == Token::LBrace || token.is_keyword("subgraph") => {
+                if token != Token::LBrace {
+                    self.pos += 1;
+                    if matches!(self.peek(), Some(Token::Id(_)) | Some(Token::Quoted(_))) {
+                        self.pos += 1;
+                    }
+                }
+                self.expect(Token::LBrace, "'{'")?;
+                let nodes = self.stmt_list(&mut defaults.clone())?;
+                self.expect(Token::RBrace, "'}'")?;
+                Ok(nodes)
+            }
+            _ => {
+                let id = self.id()?;
+                // ports are ignored
+                while self.eat(Token::Colon) {
+                    self.id()?;
+                }
+                if !self.dag.contains_node(&id) {
+                    self.dag.insert_node(id.clone(), defaults.node.clone());
+                }
+                Ok(vec![id])
+            }
+        }
+    }
+
+    fn edge_stmt(
+        &mut self,
+        lhs: Vec<String>,
+        line: usize,
+        defaults: &Defaults,
+        nodes: &mut Vec<String>,
+    ) -> Result<(), DotError> {
+        let mut endpoints = vec![lhs];
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Arrow => self.pos += 1,
+                Token::Line => {
+                    return syntax_error(self.line(), "undirected edge is NOT supported");
+                }
+                _ => break,
+            }
+            let rhs = self.endpoint(defaults)?;
+            nodes.extend(rhs.iter().cloned());
+            endpoints.push(rhs);
+        }
+        let mut attrs = defaults.edge.clone();
+        if self.peek() == Some(Token::LBracket) {
+            attrs.extend(self.attr_list()?);
+        }
+        for pair in endpoints.windows(2) {
+            for from in pair[0].iter() {
+                for to in pair[1].iter() {
+                    if let Ok(Some(data)) = self.dag.get_edge_mut(from, to) {
+                        data.extend(attrs.clone());
+                        continue;
+                    }
+                    self.dag
+                        .insert_edge(from.clone(), to.clone(), attrs.clone())
+                        .map_err(|error| DotError::Dag { line, error })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn attr_list(&mut self) -> Result<Attributes, DotError> {
+        let mut attrs = Attributes::new();
+        while self.eat(Token::LBracket) {
+            while !self.eat(Token::RBracket) {
+                let key = self.id()?;
+                self.expect(Token::Equal, "'='")?;
+                let value = self.id()?;
+                attrs.insert(key, value);
+                if !self.eat(Token::Comma) {
+                    self.eat(Token::Semicolon);
+                }
+            }
+        }
+        Ok(attrs)
+    }
+}
+
+/// Unescape `\"` and `\\`, and remove escaped newlines, other escape sequences are kept
+fn unescape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(escaped @ ('"' | '\\')) => output.push(escaped),
+            Some('\n') => {}
+            Some(other) => {
+                output.push('\\');
+                output.push(other);
+            }
+            None => output.push('\\'),
+        }
+    }
+    output
+}
@@ -0,0 +1,221 @@
+use crate::{Dag, DagError};
+
+/// A view into a single edge in `Dag`, which is either vacant or occupied
+/// # Remarks
+/// * Created by [`Dag::edge_entry`]
+pub enum EdgeEntry<'a, NodeId, NodeData, EdgeData> {
+    /// There is no such edge in `Dag`
+    Vacant(VacantEdgeEntry<'a, NodeId, NodeData, EdgeData>),
+    /// The edge is already in `Dag`
+    Occupied(OccupiedEdgeEntry<'a, NodeId, NodeData, EdgeData>),
+}
+
+/// A vacant edge in `Dag`
+pub struct VacantEdgeEntry<'a, NodeId, NodeData, EdgeData> {
+    dag: &'a mut Dag<NodeId, NodeData, EdgeData>,
+    from: NodeId,
+    to: NodeId,
+}
+
+/// An occupied edge in `Dag`
+pub struct OccupiedEdgeEntry<'a, NodeId, NodeData, EdgeData> {
+    dag: &'a mut Dag<NodeId, NodeData, EdgeData>,
+    from: NodeId,
+    to: NodeId,
+}
+
+impl<'a, NodeId, NodeData, EdgeData> EdgeEntry<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the start of the edge
+    pub fn from(&self) -> NodeId {
+        match self {
+            EdgeEntry::Vacant(entry) => entry.from.clone(),
+            EdgeEntry::Occupied(entry) => entry.from.clone(),
+        }
+    }
+
+    /// Get the end of the edge
+    pub fn to(&self) -> NodeId {
+        match self {
+            EdgeEntry::Vacant(entry) => entry.to.clone(),
+            EdgeEntry::Occupied(entry) => entry.to.clone(),
+        }
+    }
+
+    /// Modify the data if the edge is occupied
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut EdgeData),
+    {
+        if let EdgeEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Insert `default` if the edge is vacant, and get the data
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` when inserting the edge makes a cycle
+    pub fn or_insert(
+        self,
+        default: EdgeData,
+    ) -> Result<&'a mut EdgeData, DagError<NodeId, EdgeData>> {
+        match self {
+            EdgeEntry::Vacant(entry) => entry.insert(default),
+            EdgeEntry::Occupied(entry) => Ok(entry.into_mut()),
+        }
+    }
+
+    /// Insert the result of `default` if the edge is vacant, and get the data
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` when inserting the edge makes a cycle
+    pub fn or_insert_with<F>(
+        self,
+        default: F,
+    ) -> Result<&'a mut EdgeData, DagError<NodeId, EdgeData>>
+    where
+        F: FnOnce() -> EdgeData,
+    {
+        match self {
+            EdgeEntry::Vacant(entry) => entry.insert(default()),
+            EdgeEntry::Occupied(entry) => Ok(entry.into_mut()),
+        }
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> EdgeEntry<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    EdgeData: Default,
+{
+    /// Insert the default data if the edge is vacant, and get the data
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` when inserting the edge makes a cycle
+    pub fn or_default(self) -> Result<&'a mut EdgeData, DagError<NodeId, EdgeData>> {
+        self.or_insert_with(EdgeData::default)
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> VacantEdgeEntry<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the start of the edge
+    pub fn from(&self) -> NodeId {
+        self.from.clone()
+    }
+
+    /// Get the end of the edge
+    pub fn to(&self) -> NodeId {
+        self.to.clone()
+    }
+
+    /// Insert the edge with data
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` when inserting the edge makes a cycle
+    pub fn insert(
+        self,
+        edge_data: EdgeData,
+    ) -> Result<&'a mut EdgeData, DagError<NodeId, EdgeData>> {
+        self.dag
+            .insert_edge(self.from.clone(), self.to.clone(), edge_data)?;
+        Ok(self
+            .dag
+            .edges
+            .get_mut(&self.from)
+            .and_then(|children| children.get_mut(&self.to))
+            .unwrap_or_else(|| unreachable!("inserted above")))
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> OccupiedEdgeEntry<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the start of the edge
+    pub fn from(&self) -> NodeId {
+        self.from.clone()
+    }
+
+    /// Get the end of the edge
+    pub fn to(&self) -> NodeId {
+        self.to.clone()
+    }
+
+    /// Get data of the edge
+    pub fn get(&self) -> &EdgeData {
+        self.dag
+            .edges
+            .get(&self.from)
+            .and_then(|children| children.get(&self.to))
+            .unwrap_or_else(|| unreachable!("entry is occupied"))
+    }
+
+    /// Get mutable data of the edge
+    pub fn get_mut(&mut self) -> &mut EdgeData {
+        self.dag
+            .edges
+            .get_mut(&self.from)
+            .and_then(|children| children.get_mut(&self.to))
+            .unwrap_or_else(|| unreachable!("entry is occupied"))
+    }
+
+    /// Convert the entry into mutable data of the edge
+    pub fn into_mut(self) -> &'a mut EdgeData {
+        self.dag
+            .edges
+            .get_mut(&self.from)
+            .and_then(|children| children.get_mut(&self.to))
+            .unwrap_or_else(|| unreachable!("entry is occupied"))
+    }
+
+    /// Replace data of the edge, returning the old data
+    pub fn insert(&mut self, edge_data: EdgeData) -> EdgeData {
+        core::mem::replace(self.get_mut(), edge_data)
+    }
+
+    /// Remove the edge from `Dag`, returning its data
+    pub fn remove(self) -> EdgeData {
+        self.dag
+            .remove_edge(self.from, self.to)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| unreachable!("entry is occupied"))
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the entry of an edge for in-place manipulation
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
+    pub fn edge_entry(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<EdgeEntry<'_, NodeId, NodeData, EdgeData>, DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&from) {
+            return Err(DagError::NodeNotFound(from));
+        }
+        if !self.nodes.contains_key(&to) {
+            return Err(DagError::NodeNotFound(to));
+        }
+        if self.contains_edge(&from, &to) {
+            Ok(EdgeEntry::Occupied(OccupiedEdgeEntry {
+                dag: self,
+                from,
+                to,
+            }))
+        } else {
+            Ok(EdgeEntry::Vacant(VacantEdgeEntry {
+                dag: self,
+                from,
+                to,
+            }))
+        }
+    }
+}
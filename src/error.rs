@@ -1,35 +1,147 @@
-use std::{error::Error, fmt::Display};
+use alloc::vec::Vec;
+use core::fmt::Display;
 
 /// `DAG` errors
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum DagError<NodeId, EdgeData> {
     /// There is no such id in `DAG`
     NodeNotFound(NodeId),
+    /// There is already such id in `DAG`
+    DuplicateNode(NodeId),
+    /// There is no such edge `from -> to` in `DAG`
+    EdgeNotFound(NodeId, NodeId),
+    /// There is already such edge `from -> to` in `DAG`
+    DuplicateEdge(NodeId, NodeId),
     /// Insert this edge will make a cycle which whill destroy the `DAG`
-    HasCycle(NodeId, NodeId, EdgeData),
+    /// # Remarks
+    /// * `HasCycle(from, to, data, path)`, `path` is `to -> ... -> from` already in `DAG`,
+    ///   which makes the cycle with the edge `from -> to`
+    HasCycle(NodeId, NodeId, EdgeData, Vec<NodeId>),
 }
 
 impl<NodeId, EdgeData> Display for DagError<NodeId, EdgeData>
 where
     NodeId: Display,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             DagError::NodeNotFound(id) => {
                 writeln!(f, "Cannot found node in Dag where node_id='{}'.", id)
             }
-            DagError::HasCycle(from, to, _) => writeln!(
-                f,
-                "DAG was destoryed since detected a cycle when insert edge '{}' -> '{}'",
-                from, to
-            ),
+            DagError::DuplicateNode(id) => {
+                writeln!(f, "Node already exists in Dag where node_id='{}'.", id)
+            }
+            DagError::EdgeNotFound(from, to) => {
+                writeln!(
+                    f,
+                    "Cannot found edge in Dag where edge='{}' -> '{}'.",
+                    from, to
+                )
+            }
+            DagError::DuplicateEdge(from, to) => {
+                writeln!(
+                    f,
+                    "Edge already exists in Dag where edge='{}' -> '{}'.",
+                    from, to
+                )
+            }
+            DagError::HasCycle(from, to, _, path) => {
+                write!(
+                    f,
+                    "DAG was destoryed since detected a cycle when insert edge '{}' -> '{}': '{}'",
+                    from, to, from
+                )?;
+                for node_id in path {
+                    write!(f, " -> '{}'", node_id)?;
+                }
+                writeln!(f)
+            }
         }
     }
 }
 
-impl<NodeId, EdgeData> Error for DagError<NodeId, EdgeData>
+#[cfg(feature = "std")]
+impl<NodeId, EdgeData> std::error::Error for DagError<NodeId, EdgeData>
+where
+    NodeId: core::fmt::Debug + Display,
+    EdgeData: core::fmt::Debug,
+{
+}
+
+/// Error of read-only queries, which never carries `EdgeData`
+/// # Remarks
+/// * It converts into [`DagError::NodeNotFound`] by `?`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeNotFound<NodeId>(pub NodeId);
+
+impl<NodeId, EdgeData> From<NodeNotFound<NodeId>> for DagError<NodeId, EdgeData> {
+    fn from(error: NodeNotFound<NodeId>) -> Self {
+        DagError::NodeNotFound(error.0)
+    }
+}
+
+impl<NodeId> Display for NodeNotFound<NodeId>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "Cannot found node in Dag where node_id='{}'.", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<NodeId> std::error::Error for NodeNotFound<NodeId> where NodeId: core::fmt::Debug + Display {}
+
+/// Error of [`Dag::to_tree`](crate::Dag::to_tree), a node has more than one parent
+/// # Remarks
+/// * `MultipleParents(id, parents)`, `parents` are in the order of `NodeId`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipleParents<NodeId>(pub NodeId, pub Vec<NodeId>);
+
+impl<NodeId> Display for MultipleParents<NodeId>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Node has multiple parents in Dag where node_id='{}':",
+            self.0
+        )?;
+        for parent_id in &self.1 {
+            write!(f, " '{}'", parent_id)?;
+        }
+        writeln!(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<NodeId> std::error::Error for MultipleParents<NodeId> where NodeId: core::fmt::Debug + Display {}
+
+/// Error of [`Dag::map_ids`](crate::Dag::map_ids), two nodes are converted to the same id
+/// # Remarks
+/// * `CollisionError(first, second, new_id)`, `first < second`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionError<NodeId, NewId>(pub NodeId, pub NodeId, pub NewId);
+
+impl<NodeId, NewId> Display for CollisionError<NodeId, NewId>
+where
+    NodeId: Display,
+    NewId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "Nodes are converted to the same id where node_id='{}' and '{}', new_id='{}'.",
+            self.0, self.1, self.2
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<NodeId, NewId> std::error::Error for CollisionError<NodeId, NewId>
 where
-    NodeId: std::fmt::Debug + Display,
-    EdgeData: std::fmt::Debug,
+    NodeId: core::fmt::Debug + Display,
+    NewId: core::fmt::Debug + Display,
 {
 }
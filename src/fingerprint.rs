@@ -0,0 +1,125 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::hash::{Hash, Hasher};
+
+use crate::Dag;
+
+/// FNV-1a, which is stable between runs and Rust versions, unlike `DefaultHasher`
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Hash,
+{
+    /// Compute the Merkle-style structural fingerprint of every node
+    /// # Remarks
+    /// * The fingerprint of a node covers its id and all of its descendants,
+    ///   so equal fingerprints mean equal sub-DAGs reachable from the nodes
+    /// * Data stored in nodes and edges is ignored
+    pub fn node_fingerprints(&self) -> BTreeMap<NodeId, u64> {
+        self.merkle(|_, _| {}, |_, _| {})
+    }
+
+    /// Compute the structural fingerprint of `Dag`
+    /// # Remarks
+    /// * Data stored in nodes and edges is ignored
+    pub fn fingerprint(&self) -> u64 {
+        combine(self.node_fingerprints())
+    }
+
+    /// Compute the Merkle-style fingerprint of every node, hashing node and edge data too
+    pub fn node_fingerprints_with_data(&self) -> BTreeMap<NodeId, u64>
+    where
+        NodeData: Hash,
+        EdgeData: Hash,
+    {
+        self.merkle(
+            |data, hasher| data.hash(hasher),
+            |data, hasher| data.hash(hasher),
+        )
+    }
+
+    /// Compute the fingerprint of `Dag`, hashing node and edge data too
+    pub fn fingerprint_with_data(&self) -> u64
+    where
+        NodeData: Hash,
+        EdgeData: Hash,
+    {
+        combine(self.node_fingerprints_with_data())
+    }
+
+    fn merkle<FN, FE>(&self, hash_node: FN, hash_edge: FE) -> BTreeMap<NodeId, u64>
+    where
+        FN: Fn(&NodeData, &mut StableHasher),
+        FE: Fn(&EdgeData, &mut StableHasher),
+    {
+        let mut fingerprints = BTreeMap::new();
+        // children first
+        for node_id in self.topological_order().into_iter().rev() {
+            let mut hasher = StableHasher::new();
+            node_id.hash(&mut hasher);
+            if let Some(data) = self.nodes.get(&node_id) {
+                hash_node(data, &mut hasher);
+            }
+            // children are ordered by id
+            let children = self
+                .children(&node_id)
+                .map(|(child_id, data)| {
+                    let mut hasher = StableHasher::new();
+                    hasher.write_u64(fingerprints[&child_id]);
+                    hash_edge(data, &mut hasher);
+                    hasher.finish()
+                })
+                .collect::<Vec<_>>();
+            children.hash(&mut hasher);
+            fingerprints.insert(node_id, hasher.finish());
+        }
+        fingerprints
+    }
+}
+
+/// Combine fingerprints in the order of node ids
+fn combine<NodeId>(fingerprints: BTreeMap<NodeId, u64>) -> u64 {
+    let fingerprints = fingerprints.into_values().collect::<Vec<_>>();
+    let mut hasher = StableHasher::new();
+    fingerprints.hash(&mut hasher);
+    hasher.finish()
+}
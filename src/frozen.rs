@@ -0,0 +1,221 @@
+use alloc::{borrow::ToOwned, vec, vec::Vec};
+use core::borrow::Borrow;
+
+use crate::{Dag, NodeNotFound};
+
+/// A read-only DAG compacted into CSR (compressed sparse row) arrays
+/// # Remarks
+/// * Created by [`Dag::freeze`]
+/// * Nodes are addressed by a dense index in `0..nodes_len()`, in the order of `NodeId`
+/// * Children and parents of a node are contiguous slices of indices, O(1) to get
+/// * Use [`FrozenDag::index_of`] and [`FrozenDag::node_ids`] to convert between ids and indices
+#[derive(Debug, Clone)]
+pub struct FrozenDag<NodeId, NodeData, EdgeData> {
+    ids: Vec<NodeId>,
+    node_data: Vec<NodeData>,
+    // children of node `i` are `child_targets[child_offsets[i]..child_offsets[i + 1]]`
+    child_offsets: Vec<usize>,
+    child_targets: Vec<usize>,
+    edge_data: Vec<EdgeData>,
+    // parents of node `i` are `parent_sources[parent_offsets[i]..parent_offsets[i + 1]]`
+    parent_offsets: Vec<usize>,
+    parent_sources: Vec<usize>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Compact `Dag` into a read-only [`FrozenDag`]
+    /// # Remarks
+    /// * Children of each node keep the order of `NodeId`, the same as [`Dag::children`]
+    pub fn freeze(self) -> FrozenDag<NodeId, NodeData, EdgeData> {
+        let (nodes, edges) = self.into_parts();
+        let (ids, node_data): (Vec<_>, Vec<_>) = nodes.unzip();
+        let index_of = |id: &NodeId| {
+            ids.binary_search(id)
+                .unwrap_or_else(|_| unreachable!("Dag ensures edges refer to existing nodes"))
+        };
+
+        // edges come grouped by `from` in order, so children are already in CSR order
+        let mut child_offsets = vec![0; ids.len() + 1];
+        let mut child_targets = Vec::new();
+        let mut edge_data = Vec::new();
+        let mut in_degrees = vec![0; ids.len()];
+        for (from, to, data) in edges {
+            let (from, to) = (index_of(&from), index_of(&to));
+            child_offsets[from + 1] += 1;
+            in_degrees[to] += 1;
+            child_targets.push(to);
+            edge_data.push(data);
+        }
+        for i in 0..ids.len() {
+            child_offsets[i + 1] += child_offsets[i];
+        }
+
+        // counting sort of edges by `to`
+        let mut parent_offsets = vec![0; ids.len() + 1];
+        for (i, degree) in in_degrees.iter().enumerate() {
+            parent_offsets[i + 1] = parent_offsets[i] + degree;
+        }
+        let mut cursors = parent_offsets.clone();
+        let mut parent_sources = vec![0; child_targets.len()];
+        for from in 0..ids.len() {
+            for &to in &child_targets[child_offsets[from]..child_offsets[from + 1]] {
+                parent_sources[cursors[to]] = from;
+                cursors[to] += 1;
+            }
+        }
+
+        FrozenDag {
+            ids,
+            node_data,
+            child_offsets,
+            child_targets,
+            edge_data,
+            parent_offsets,
+            parent_sources,
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> FrozenDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the index of `node_id`
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `FrozenDag`
+    pub fn index_of<Q>(&self, node_id: &Q) -> Option<usize>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.ids
+            .binary_search_by(|id| id.borrow().cmp(node_id))
+            .ok()
+    }
+
+    /// Get all the node ids, indexed by node index
+    pub fn node_ids(&self) -> &[NodeId] {
+        &self.ids
+    }
+
+    /// Get all the node data, indexed by node index
+    pub fn node_data(&self) -> &[NodeData] {
+        &self.node_data
+    }
+
+    /// Get the count of nodes
+    pub fn nodes_len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Get the count of edges
+    pub fn edges_len(&self) -> usize {
+        self.child_targets.len()
+    }
+
+    /// Get the indices of all the children of node `index`
+    /// # Panics
+    /// Panics if `index >= nodes_len()`
+    pub fn children(&self, index: usize) -> &[usize] {
+        &self.child_targets[self.child_offsets[index]..self.child_offsets[index + 1]]
+    }
+
+    /// Get the data of edges to all the children of node `index`,
+    /// in the same order as [`FrozenDag::children`]
+    /// # Panics
+    /// Panics if `index >= nodes_len()`
+    pub fn children_data(&self, index: usize) -> &[EdgeData] {
+        &self.edge_data[self.child_offsets[index]..self.child_offsets[index + 1]]
+    }
+
+    /// Get the indices of all the parents of node `index`
+    /// # Panics
+    /// Panics if `index >= nodes_len()`
+    pub fn parents(&self, index: usize) -> &[usize] {
+        &self.parent_sources[self.parent_offsets[index]..self.parent_offsets[index + 1]]
+    }
+
+    /// Check if a `node_id` is contained in `FrozenDag`
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.index_of(node_id).is_some()
+    }
+
+    /// Check if an `edge` is contained in `FrozenDag`
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match (self.index_of(from), self.index_of(to)) {
+            (Some(from), Some(to)) => self.children(from).binary_search(&to).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Get data from node
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `FrozenDag`
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<&NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.index_of(node_id).map(|index| &self.node_data[index])
+    }
+
+    /// Get data from edge
+    /// # Returns
+    /// Return `Ok(Some(data))` if success
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `FrozenDag`
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Result<Option<&EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        let from = self
+            .index_of(from)
+            .ok_or_else(|| NodeNotFound(from.to_owned()))?;
+        let to = self
+            .index_of(to)
+            .ok_or_else(|| NodeNotFound(to.to_owned()))?;
+        // children are sorted by index
+        Ok(self
+            .children(from)
+            .binary_search(&to)
+            .ok()
+            .map(|i| &self.children_data(from)[i]))
+    }
+
+    /// Get all the nodes in `FrozenDag`
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.ids.iter().cloned().zip(self.node_data.iter())
+    }
+
+    /// Get all the edges in `FrozenDag`
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &'_ EdgeData)> {
+        (0..self.nodes_len()).flat_map(move |from| {
+            self.children(from)
+                .iter()
+                .zip(self.children_data(from))
+                .map(move |(&to, data)| (self.ids[from].clone(), self.ids[to].clone(), data))
+        })
+    }
+
+    /// Get the indices of all the roots in `FrozenDag`
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes_len()).filter(|&index| self.parents(index).is_empty())
+    }
+
+    /// Get the indices of all the leaves in `FrozenDag`
+    pub fn leaves(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes_len()).filter(|&index| self.children(index).is_empty())
+    }
+}
@@ -0,0 +1,576 @@
+//! GraphML support, enabled by feature `graphml`
+//! # Remarks
+//! * Node and edge data are mapped to `<data>` through serde.
+//!   Fields of a struct or a map become GraphML attributes,
+//!   any other value is stored in an attribute named `data`
+//! * Nested structs, maps and sequences in data are NOT supported
+//! * `()` and `None` store nothing
+//! * Node ids are written by `Display` and read by `FromStr`
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+use quick_xml::{
+    escape::resolve_predefined_entity,
+    events::{BytesDecl, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use serde::{de::DeserializeOwned, ser::Error as _, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{Dag, DagError};
+
+const XMLNS: &str = "http://graphml.graphdrawing.org/xmlns";
+
+/// Errors when reading or writing GraphML
+#[derive(Debug)]
+pub enum GraphMlError<NodeId, EdgeData> {
+    /// IO error from the underlying reader or writer
+    Io(std::io::Error),
+    /// Input is NOT well-formed XML
+    Xml(quick_xml::Error),
+    /// Input is NOT valid GraphML
+    Syntax(String),
+    /// Node or edge data can't be mapped to GraphML attributes
+    Data(serde_json::Error),
+    /// Input is NOT a DAG
+    Dag(DagError<NodeId, EdgeData>),
+}
+
+impl<NodeId, EdgeData> Display for GraphMlError<NodeId, EdgeData>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GraphMlError::Io(error) => writeln!(f, "IO error: {}", error),
+            GraphMlError::Xml(error) => writeln!(f, "XML error: {}", error),
+            GraphMlError::Syntax(message) => writeln!(f, "Invalid GraphML: {}", message),
+            GraphMlError::Data(error) => writeln!(f, "Invalid data: {}", error),
+            GraphMlError::Dag(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl<NodeId, EdgeData> Error for GraphMlError<NodeId, EdgeData>
+where
+    NodeId: core::fmt::Debug + Display,
+    EdgeData: core::fmt::Debug,
+{
+}
+
+impl<NodeId, EdgeData> From<std::io::Error> for GraphMlError<NodeId, EdgeData> {
+    fn from(error: std::io::Error) -> Self {
+        GraphMlError::Io(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<quick_xml::Error> for GraphMlError<NodeId, EdgeData> {
+    fn from(error: quick_xml::Error) -> Self {
+        GraphMlError::Xml(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<serde_json::Error> for GraphMlError<NodeId, EdgeData> {
+    fn from(error: serde_json::Error) -> Self {
+        GraphMlError::Data(error)
+    }
+}
+
+/// A `<key>` declaration to write
+struct Key {
+    id: String,
+    domain: &'static str,
+    name: String,
+    ty: &'static str,
+}
+
+/// A `<key>` declaration read from input
+struct KeyDecl {
+    domain: String,
+    name: String,
+    ty: String,
+    default: Option<String>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Display,
+    NodeData: Serialize,
+    EdgeData: Serialize,
+{
+    /// Write `Dag` as GraphML
+    /// # Errors
+    /// * `Err(Data(_))` when node or edge data can't be mapped to GraphML attributes
+    /// * `Err(Io(_))` when writing failed
+    pub fn write_graphml<W>(&self, writer: W) -> Result<(), GraphMlError<NodeId, EdgeData>>
+    where
+        W: Write,
+    {
+        let mut keys = Vec::new();
+        let mut key_indices = BTreeMap::new();
+        let mut register = |domain: &'static str, attrs: &[(String, Value)]| {
+            for (name, value) in attrs {
+                let ty = value_type(value);
+                let index = *key_indices
+                    .entry((domain, name.clone()))
+                    .or_insert_with(|| {
+                        keys.push(Key {
+                            id: format!("d{}", keys.len()),
+                            domain,
+                            name: name.clone(),
+                            ty,
+                        });
+                        keys.len() - 1
+                    });
+                let key: &mut Key = &mut keys[index];
+                key.ty = merge_types(key.ty, ty);
+            }
+        };
+
+        let mut nodes = Vec::with_capacity(self.nodes_len());
+        for (node_id, data) in self.nodes() {
+            let attrs = flatten(data)?;
+            register("node", &attrs);
+            nodes.push((node_id, attrs));
+        }
+        let mut edges = Vec::new();
+        for (from, to, data) in self.edges() {
+            let attrs = flatten(data)?;
+            register("edge", &attrs);
+            edges.push((from, to, attrs));
+        }
+        let key_ids = keys
+            .iter()
+            .map(|key| ((key.domain, key.name.as_str()), key.id.as_str()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut writer = Writer::new_with_indent(writer, b' ', 2);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        writer
+            .create_element("graphml")
+            .with_attribute(("xmlns", XMLNS))
+            .write_inner_content(|writer| {
+                for key in keys.iter() {
+                    writer
+                        .create_element("key")
+                        .with_attribute(("id", key.id.as_str()))
+                        .with_attribute(("for", key.domain))
+                        .with_attribute(("attr.name", key.name.as_str()))
+                        .with_attribute(("attr.type", key.ty))
+                        .write_empty()?;
+                }
+                writer
+                    .create_element("graph")
+                    .with_attribute(("id", "G"))
+                    .with_attribute(("edgedefault", "directed"))
+                    .write_inner_content(|writer| {
+                        for (node_id, attrs) in nodes.iter() {
+                            let id = node_id.to_string();
+                            let element = writer
+                                .create_element("node")
+                                .with_attribute(("id", id.as_str()));
+                            write_data(element, &key_ids, "node", attrs)?;
+                        }
+                        for (from, to, attrs) in edges.iter() {
+                            let source = from.to_string();
+                            let target = to.to_string();
+                            let element = writer
+                                .create_element("edge")
+                                .with_attribute(("source", source.as_str()))
+                                .with_attribute(("target", target.as_str()));
+                            write_data(element, &key_ids, "edge", attrs)?;
+                        }
+                        Ok(())
+                    })?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + FromStr,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    /// Read `Dag` from GraphML
+    /// # Errors
+    /// * `Err(Xml(_))` or `Err(Syntax(_))` when input is NOT valid GraphML
+    /// * `Err(Data(_))` when attributes can't be mapped to node or edge data
+    /// * `Err(Dag(NodeNotFound(id)))` when an edge refers to a node NOT found
+    /// * `Err(Dag(HasCycle(from,to,data,path)))` when input has a cycle
+    pub fn read_graphml<R>(reader: R) -> Result<Self, GraphMlError<NodeId, EdgeData>>
+    where
+        R: BufRead,
+    {
+        let mut reader = Reader::from_reader(reader);
+        let mut buf = Vec::new();
+        let mut keys = BTreeMap::new();
+        let mut edgedefault_directed = true;
+        // key id of the current <key>
+        let mut current_key = None;
+        // the current <node> or <edge>
+        let mut element: Option<(Element<NodeId>, BTreeMap<String, String>)> = None;
+        // key id and text of the current <data> or <default>
+        let mut text: Option<(String, String)> = None;
+        let mut depth_in_element = 0;
+        let mut dag = Dag::new();
+        let mut edges = Vec::new();
+
+        loop {
+            let event = reader.read_event_into(&mut buf)?;
+            let is_empty = matches!(event, Event::Empty(_));
+            match event {
+                Event::Start(start) | Event::Empty(start) => {
+                    if element.is_some() {
+                        depth_in_element += 1;
+                    }
+                    match start.local_name().as_ref() {
+                        b"key" => {
+                            let id = attribute(&reader, &start, "id")?
+                                .ok_or_else(|| syntax("<key> without id"))?;
+                            let name = attribute(&reader, &start, "attr.name")?
+                                .unwrap_or_else(|| id.clone());
+                            let ty = attribute(&reader, &start, "attr.type")?
+                                .unwrap_or_else(|| "string".to_string());
+                            let domain = attribute(&reader, &start, "for")?
+                                .unwrap_or_else(|| "all".to_string());
+                            let decl = KeyDecl {
+                                domain,
+                                name,
+                                ty,
+                                default: None,
+                            };
+                            keys.insert(id.clone(), decl);
+                            if !is_empty {
+                                current_key = Some(id);
+                            }
+                        }
+                        b"default" => {
+                            if let Some(id) = current_key.clone() {
+                                text = Some((id, String::new()));
+                            }
+                        }
+                        b"graph" if element.is_none() => {
+                            edgedefault_directed = attribute(&reader, &start, "edgedefault")?
+                                .as_deref()
+                                != Some("undirected");
+                        }
+                        b"node" if element.is_none() => {
+                            let id = attribute(&reader, &start, "id")?
+                                .ok_or_else(|| syntax("<node> without id"))?;
+                            let node_id = parse_id(&id)?;
+                            element = Some((Element::Node(node_id), BTreeMap::new()));
+                            depth_in_element = 1;
+                        }
+                        b"edge" if element.is_none() => {
+                            let directed = attribute(&reader, &start, "directed")?
+                                .map_or(edgedefault_directed, |value| value == "true");
+                            if !directed {
+                                return Err(syntax("undirected edge is NOT supported"));
+                            }
+                            let source = attribute(&reader, &start, "source")?
+                                .ok_or_else(|| syntax("<edge> without source"))?;
+                            let target = attribute(&reader, &start, "target")?
+                                .ok_or_else(|| syntax("<edge> without target"))?;
+                            element = Some((
+                                Element::Edge(parse_id(&source)?, parse_id(&target)?),
+                                BTreeMap::new(),
+                            ));
+                            depth_in_element = 1;
+                        }
+                        b"data" if depth_in_element == 2 => {
+                            let key = attribute(&reader, &start, "key")?
+                                .ok_or_else(|| syntax("<data> without key"))?;
+                            text = Some((key, String::new()));
+                        }
+                        _ => {}
+                    }
+                    if is_empty {
+                        if let Some((key, value)) = text.take() {
+                            store(&mut keys, &mut element, current_key.is_some(), key, value);
+                        }
+                        if element.is_some() {
+                            depth_in_element -= 1;
+                        }
+                        if depth_in_element == 0 {
+                            if let Some(element) = element.take() {
+                                finish(&mut dag, &mut edges, &keys, element)?;
+                            }
+                        }
+                    }
+                }
+                Event::End(end) => {
+                    match end.local_name().as_ref() {
+                        b"key" => current_key = None,
+                        b"data" | b"default" => {
+                            if let Some((key, value)) = text.take() {
+                                store(&mut keys, &mut element, current_key.is_some(), key, value);
+                            }
+                        }
+                        _ => {}
+                    }
+                    if element.is_some() {
+                        depth_in_element -= 1;
+                        if depth_in_element == 0 {
+                            let element = element
+                                .take()
+                                .unwrap_or_else(|| unreachable!("checked by is_some"));
+                            finish(&mut dag, &mut edges, &keys, element)?;
+                        }
+                    }
+                }
+                Event::Text(content) => {
+                    if let Some((_, text)) = text.as_mut() {
+                        let content = content
+                            .xml_content()
+                            .map_err(|error| GraphMlError::Xml(error.into()))?;
+                        text.push_str(&content);
+                    }
+                }
+                Event::CData(content) => {
+                    if let Some((_, text)) = text.as_mut() {
+                        let content = content
+                            .decode()
+                            .map_err(|error| GraphMlError::Xml(error.into()))?;
+                        text.push_str(&content);
+                    }
+                }
+                Event::GeneralRef(reference) => {
+                    if let Some((_, text)) = text.as_mut() {
+                        if let Some(ch) = reference.resolve_char_ref()? {
+                            text.push(ch);
+                        } else {
+                            let name = reference
+                                .decode()
+                                .map_err(|error| GraphMlError::Xml(error.into()))?;
+                            let value = resolve_predefined_entity(&name).ok_or_else(|| {
+                                syntax(format!("unknown entity '&{};'", name).as_str())
+                            })?;
+                            text.push_str(value);
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        for (from, to, data) in edges {
+            if dag.contains_edge(&from, &to) {
+                return Err(syntax("duplicate edge"));
+            }
+            dag.insert_edge(from, to, data).map_err(GraphMlError::Dag)?;
+        }
+        Ok(dag)
+    }
+}
+
+enum Element<NodeId> {
+    Node(NodeId),
+    Edge(NodeId, NodeId),
+}
+
+fn syntax<NodeId, EdgeData>(message: &str) -> GraphMlError<NodeId, EdgeData> {
+    GraphMlError::Syntax(message.to_string())
+}
+
+fn parse_id<NodeId, EdgeData>(id: &str) -> Result<NodeId, GraphMlError<NodeId, EdgeData>>
+where
+    NodeId: FromStr,
+{
+    id.parse()
+        .map_err(|_| syntax(format!("invalid node id '{}'", id).as_str()))
+}
+
+fn attribute<R, NodeId, EdgeData>(
+    reader: &Reader<R>,
+    start: &BytesStart<'_>,
+    name: &str,
+) -> Result<Option<String>, GraphMlError<NodeId, EdgeData>> {
+    match start
+        .try_get_attribute(name)
+        .map_err(|error| GraphMlError::Xml(error.into()))?
+    {
+        Some(attr) => Ok(Some(
+            attr.decode_and_unescape_value(reader.decoder())?
+                .into_owned(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Store the text of `<data>` into the current element or the text of `<default>` into keys
+fn store<NodeId>(
+    keys: &mut BTreeMap<String, KeyDecl>,
+    element: &mut Option<(Element<NodeId>, BTreeMap<String, String>)>,
+    in_key: bool,
+    key: String,
+    value: String,
+) {
+    if in_key {
+        if let Some(decl) = keys.get_mut(&key) {
+            decl.default = Some(value);
+        }
+    } else if let Some((_, data)) = element.as_mut() {
+        data.insert(key, value);
+    }
+}
+
+/// Insert a node or keep an edge to insert after all the nodes
+fn finish<NodeId, NodeData, EdgeData>(
+    dag: &mut Dag<NodeId, NodeData, EdgeData>,
+    edges: &mut Vec<(NodeId, NodeId, EdgeData)>,
+    keys: &BTreeMap<String, KeyDecl>,
+    (element, data): (Element<NodeId>, BTreeMap<String, String>),
+) -> Result<(), GraphMlError<NodeId, EdgeData>>
+where
+    NodeId: Clone + Ord,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    let domain = match element {
+        Element::Node(_) => "node",
+        Element::Edge(_, _) => "edge",
+    };
+    let mut map = Map::new();
+    for decl in keys.values() {
+        if decl.domain != domain && decl.domain != "all" {
+            continue;
+        }
+        if let Some(default) = decl.default.as_ref() {
+            map.insert(decl.name.clone(), typed_value(&decl.ty, default)?);
+        }
+    }
+    for (key_id, text) in data.iter() {
+        let decl = keys
+            .get(key_id)
+            .ok_or_else(|| syntax(format!("undeclared key '{}'", key_id).as_str()))?;
+        map.insert(decl.name.clone(), typed_value(&decl.ty, text)?);
+    }
+    match element {
+        Element::Node(node_id) => {
+            if dag.contains_node(&node_id) {
+                return Err(syntax("duplicate node"));
+            }
+            dag.insert_node(node_id, unflatten(map)?);
+        }
+        Element::Edge(from, to) => edges.push((from, to, unflatten(map)?)),
+    }
+    Ok(())
+}
+
+fn write_data<W>(
+    element: quick_xml::writer::ElementWriter<'_, W>,
+    key_ids: &BTreeMap<(&'static str, &str), &str>,
+    domain: &'static str,
+    attrs: &[(String, Value)],
+) -> std::io::Result<()>
+where
+    W: Write,
+{
+    if attrs.is_empty() {
+        element.write_empty()?;
+        return Ok(());
+    }
+    element.write_inner_content(|writer| {
+        for (name, value) in attrs {
+            let text = match value {
+                Value::String(text) => text.clone(),
+                value => value.to_string(),
+            };
+            writer
+                .create_element("data")
+                .with_attribute(("key", key_ids[&(domain, name.as_str())]))
+                .write_text_content(BytesText::new(&text))?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Map data to GraphML attributes
+fn flatten<T>(data: &T) -> Result<Vec<(String, Value)>, serde_json::Error>
+where
+    T: Serialize,
+{
+    let attrs = match serde_json::to_value(data)? {
+        Value::Null => Vec::new(),
+        Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .collect(),
+        value => vec![("data".to_string(), value)],
+    };
+    if attrs
+        .iter()
+        .any(|(_, value)| value.is_object() || value.is_array())
+    {
+        return Err(serde_json::Error::custom(
+            "nested values are NOT supported by GraphML",
+        ));
+    }
+    Ok(attrs)
+}
+
+/// Map GraphML attributes to data
+fn unflatten<T>(mut map: Map<String, Value>) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned,
+{
+    if map.is_empty() {
+        if let Ok(data) = serde_json::from_value(Value::Null) {
+            return Ok(data);
+        }
+    }
+    let error = match serde_json::from_value(Value::Object(map.clone())) {
+        Ok(data) => return Ok(data),
+        Err(error) => error,
+    };
+    if map.len() == 1 {
+        if let Some(value) = map.remove("data") {
+            if let Ok(data) = serde_json::from_value(value) {
+                return Ok(data);
+            }
+        }
+    }
+    Err(error)
+}
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "double",
+        Value::Number(_) => "long",
+        _ => "string",
+    }
+}
+
+fn merge_types(a: &'static str, b: &'static str) -> &'static str {
+    match (a, b) {
+        (a, b) if a == b => a,
+        ("long", "double") | ("double", "long") => "double",
+        _ => "string",
+    }
+}
+
+fn typed_value<NodeId, EdgeData>(
+    ty: &str,
+    text: &str,
+) -> Result<Value, GraphMlError<NodeId, EdgeData>> {
+    let invalid = || syntax(format!("invalid {} '{}'", ty, text).as_str());
+    let value = match ty {
+        "boolean" => Value::Bool(text.trim().parse().map_err(|_| invalid())?),
+        "int" | "long" => Value::from(text.trim().parse::<i64>().map_err(|_| invalid())?),
+        "float" | "double" => Value::from(text.trim().parse::<f64>().map_err(|_| invalid())?),
+        _ => Value::String(text.to_string()),
+    };
+    Ok(value)
+}
@@ -0,0 +1,474 @@
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    order::{find_path, IdMap, Order},
+    Dag, DagError, NodeNotFound,
+};
+
+impl<NodeId, V> IdMap<NodeId, V> for HashMap<NodeId, V>
+where
+    NodeId: Hash + Eq,
+{
+    fn get(&self, key: &NodeId) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: NodeId, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &NodeId) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a NodeId, &'a V)>
+    where
+        NodeId: 'a,
+        V: 'a,
+    {
+        HashMap::iter(self)
+    }
+}
+
+/// DAG stored by `HashMap` and `HashSet`
+/// # Remarks
+/// * It has the same core API as [`Dag`], with O(1) lookups
+/// * Cycles are checked by the same incremental topological order as [`Dag`]
+/// * It CANNOT ensure the order of nodes, children and edges
+/// * Convert it into `Dag` to use the algorithms, which need `NodeId: Ord`
+/// * `NodeId` must be `Clone + Hash + Eq`
+#[derive(Debug, Clone)]
+pub struct HashDag<NodeId, NodeData, EdgeData> {
+    nodes: HashMap<NodeId, NodeData>,
+    edges: HashMap<NodeId, HashMap<NodeId, EdgeData>>,
+    back_edges: HashMap<NodeId, HashSet<NodeId>>,
+    // a topological order of nodes, kept by `insert_edge`
+    order: Order<NodeId, HashMap<NodeId, usize>>,
+    edges_len: usize,
+}
+
+impl<NodeId, NodeData, EdgeData> Default for HashDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> PartialEq for HashDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Hash + Eq,
+    NodeData: PartialEq,
+    EdgeData: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // back_edges and order are derived from edges
+        self.nodes == other.nodes && self.edges == other.edges
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Eq for HashDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Hash + Eq,
+    NodeData: Eq,
+    EdgeData: Eq,
+{
+}
+
+impl<NodeId, NodeData, EdgeData> HashDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Hash + Eq,
+{
+    /// Create an empty DAG
+    pub fn new() -> Self {
+        HashDag {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+            back_edges: HashMap::new(),
+            order: Order::default(),
+            edges_len: 0,
+        }
+    }
+
+    /// Check if a `node_id` is contained in `HashDag`
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.nodes.contains_key(node_id)
+    }
+
+    /// Check a node is root
+    pub fn is_root<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.parents(node_id).count() == 0
+    }
+
+    /// Insert a node with data
+    /// # Returns
+    /// * Return `Some(data)` when `node_id` is already in `HashDag`
+    pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) -> Option<NodeData> {
+        self.edges.entry(node_id.clone()).or_default();
+        self.back_edges.entry(node_id.clone()).or_default();
+        if !self.nodes.contains_key(&node_id) {
+            self.order.push(node_id.clone());
+        }
+        self.nodes.insert(node_id, node_data)
+    }
+
+    /// Check if an `edge` is contained in `HashDag`
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(children) = self.edges.get(from) {
+            return children.contains_key(to);
+        }
+        false
+    }
+
+    /// Insert an edge with data in `HashDag`
+    /// # Return
+    /// * Return `Ok(Some(data))` when there is a same edge in `HashDag`
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `HashDag`
+    /// * `Err(HasCycle(from,to,data,path))` when a cycle is detected
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&from) {
+            return Err(DagError::NodeNotFound(from));
+        }
+        if !self.nodes.contains_key(&to) {
+            return Err(DagError::NodeNotFound(to));
+        }
+        // only the nodes between `to` and `from` in the order are visited, like `Dag`
+        let (edges, back_edges) = (&self.edges, &self.back_edges);
+        if !self.contains_edge(&from, &to)
+            && !self.order.reorder(
+                &from,
+                &to,
+                |id| edges[id].keys(),
+                |id| back_edges[id].iter(),
+            )
+        {
+            let path = find_path(to.clone(), from.clone(), HashMap::new(), |id| {
+                self.children(id).map(|(id, _)| id)
+            })
+            .unwrap_or_else(|| unreachable!("reorder found a path"));
+            return Err(DagError::HasCycle(from, to, edge_data, path));
+        }
+        let result = self
+            .edges
+            .get_mut(&from)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .insert(to.clone(), edge_data);
+        self.back_edges
+            .get_mut(&to)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .insert(from);
+        if result.is_none() {
+            self.edges_len += 1;
+        }
+        Ok(result)
+    }
+
+    /// Remove an edge from `HashDag`
+    /// # Returns
+    /// * Return `Ok(Some(data))` when success
+    /// * Return `Ok(None)` when there is no such edge
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `HashDag`
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&from) {
+            return Err(DagError::NodeNotFound(from));
+        }
+        if !self.nodes.contains_key(&to) {
+            return Err(DagError::NodeNotFound(to));
+        }
+        let result = self
+            .edges
+            .get_mut(&from)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .remove(&to);
+        self.back_edges
+            .get_mut(&to)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .remove(&from);
+        if result.is_some() {
+            self.edges_len -= 1;
+        }
+        Ok(result)
+    }
+
+    /// remove a node and all edges related
+    /// # Returns
+    /// * Return `(Some(data),edges)` if succeeded, `edges` are `(from,to,data)` of removed edges,
+    ///   edges to children first
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &mut self,
+        node_id: NodeId,
+    ) -> (Option<NodeData>, Vec<(NodeId, NodeId, EdgeData)>) {
+        let node_data = match self.nodes.remove(&node_id) {
+            Some(data) => data,
+            None => return (None, Vec::new()),
+        };
+        let mut edge_data = Vec::new();
+        let children = self
+            .edges
+            .remove(&node_id)
+            .unwrap_or_else(|| unreachable!("HashDag ensures this node exists in edges"));
+        for (child_id, data) in children {
+            if let Some(parents) = self.back_edges.get_mut(&child_id) {
+                parents.remove(&node_id);
+            }
+            edge_data.push((node_id.clone(), child_id, data));
+        }
+        let parents = self
+            .back_edges
+            .remove(&node_id)
+            .unwrap_or_else(|| unreachable!("HashDag ensures this node exists in back_edges"));
+        for parent_id in parents {
+            let data = self
+                .edges
+                .get_mut(&parent_id)
+                .and_then(|children| children.remove(&node_id))
+                .unwrap_or_else(|| unreachable!("back_edges is consistent with edges"));
+            edge_data.push((parent_id, node_id.clone(), data));
+        }
+        self.order.remove(&node_id);
+        self.edges_len -= edge_data.len();
+        (Some(node_data), edge_data)
+    }
+
+    /// Get an iterator of all the children of given `node_id`
+    pub fn children<Q>(&self, node_id: &Q) -> impl Iterator<Item = (NodeId, &'_ EdgeData)>
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.edges
+            .get(node_id)
+            .into_iter()
+            .flat_map(|map| map.iter().map(|(id, data)| (id.clone(), data)))
+    }
+
+    /// Get an iterator of all the children of given `node_id`
+    pub fn children_mut<Q>(
+        &mut self,
+        node_id: &Q,
+    ) -> impl Iterator<Item = (NodeId, &'_ mut EdgeData)>
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.edges
+            .get_mut(node_id)
+            .into_iter()
+            .flat_map(|map| map.iter_mut().map(|(id, data)| (id.clone(), data)))
+    }
+
+    /// Get an iterator of all the parents of given `node_id`
+    pub fn parents<Q>(&self, node_id: &Q) -> impl Iterator<Item = NodeId> + '_
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.back_edges
+            .get(node_id)
+            .into_iter()
+            .flat_map(|set| set.iter().cloned())
+    }
+
+    /// Get the count of nodes
+    pub fn nodes_len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get the count of edges
+    pub fn edges_len(&self) -> usize {
+        self.edges_len
+    }
+
+    /// Check if `HashDag` has no node
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get all the nodes in `HashDag`
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.nodes.iter().map(|(id, data)| (id.clone(), data))
+    }
+
+    /// Get all the nodes in `HashDag`
+    pub fn nodes_mut(&mut self) -> impl Iterator<Item = (NodeId, &'_ mut NodeData)> {
+        self.nodes.iter_mut().map(|(id, data)| (id.clone(), data))
+    }
+
+    /// Get all the edges in `HashDag`
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &'_ EdgeData)> {
+        self.edges.iter().flat_map(|(from, children)| {
+            children
+                .iter()
+                .map(move |(to, data)| (from.clone(), to.clone(), data))
+        })
+    }
+
+    /// Get all the edges in `HashDag`
+    pub fn edges_mut(&mut self) -> impl Iterator<Item = (NodeId, NodeId, &'_ mut EdgeData)> {
+        self.edges.iter_mut().flat_map(|(from, children)| {
+            children
+                .iter_mut()
+                .map(move |(to, data)| (from.clone(), to.clone(), data))
+        })
+    }
+
+    /// Get all the leaves in `HashDag`
+    pub fn leaves(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.nodes()
+            .filter(|(id, _)| self.children(id).next().is_none())
+    }
+
+    /// Get all the roots in `HashDag`
+    pub fn roots(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.nodes()
+            .filter(|(id, _)| self.parents(id).next().is_none())
+    }
+
+    /// Get data from node
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `HashDag`
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<&NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.nodes.get(node_id)
+    }
+
+    /// Get mutable data from node
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `HashDag`
+    pub fn get_node_mut<Q>(&mut self, node_id: &Q) -> Option<&mut NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.nodes.get_mut(node_id)
+    }
+
+    /// Get data from edge
+    /// # Returns
+    /// Return `Ok(Some(data))` if success
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `HashDag`
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Result<Option<&EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        if !self.nodes.contains_key(from) {
+            return Err(NodeNotFound(from.to_owned()));
+        }
+        if !self.nodes.contains_key(to) {
+            return Err(NodeNotFound(to.to_owned()));
+        }
+        Ok(self.edges.get(from).and_then(|children| children.get(to)))
+    }
+
+    /// Get mutable data from edge
+    /// # Returns
+    /// Return `Ok(Some(data))` if success
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `HashDag`
+    pub fn get_edge_mut<Q>(
+        &mut self,
+        from: &Q,
+        to: &Q,
+    ) -> Result<Option<&mut EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        if !self.nodes.contains_key(from) {
+            return Err(NodeNotFound(from.to_owned()));
+        }
+        if !self.nodes.contains_key(to) {
+            return Err(NodeNotFound(to.to_owned()));
+        }
+        Ok(self
+            .edges
+            .get_mut(from)
+            .and_then(|children| children.get_mut(to)))
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> From<HashDag<NodeId, NodeData, EdgeData>>
+    for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Hash,
+{
+    fn from(dag: HashDag<NodeId, NodeData, EdgeData>) -> Self {
+        Dag {
+            nodes: dag.nodes.into_iter().collect(),
+            edges: dag
+                .edges
+                .into_iter()
+                .map(|(id, children)| (id, children.into_iter().collect()))
+                .collect(),
+            back_edges: dag
+                .back_edges
+                .into_iter()
+                .map(|(id, parents)| (id, parents.into_iter().collect()))
+                .collect(),
+            order: Order::from_positions(dag.order.positions.into_iter().collect(), dag.order.next),
+            edges_len: dag.edges_len,
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> From<Dag<NodeId, NodeData, EdgeData>>
+    for HashDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Hash,
+{
+    fn from(dag: Dag<NodeId, NodeData, EdgeData>) -> Self {
+        HashDag {
+            nodes: dag.nodes.into_iter().collect(),
+            edges: dag
+                .edges
+                .into_iter()
+                .map(|(id, children)| (id, children.into_iter().collect()))
+                .collect(),
+            back_edges: dag
+                .back_edges
+                .into_iter()
+                .map(|(id, parents)| (id, parents.into_iter().collect()))
+                .collect(),
+            order: Order::from_positions(dag.order.positions.into_iter().collect(), dag.order.next),
+            edges_len: dag.edges_len,
+        }
+    }
+}
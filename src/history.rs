@@ -0,0 +1,225 @@
+use alloc::vec::Vec;
+use core::{mem, ops::Deref};
+
+use crate::{Dag, DagError};
+
+/// A `Dag` which records every mutation to undo and redo it
+/// # Remarks
+/// * Created by [`Dag::into_history`], reads go to `Dag` by `Deref`
+/// * Every mutation is one step, [`HistoryDag::remove_node`] and [`HistoryDag::clear`]
+///   restore all the removed edges as well
+/// * A new mutation drops the steps to redo
+/// * Removed data is kept to undo, so removing methods don't return it
+#[derive(Debug, Clone)]
+pub struct HistoryDag<NodeId, NodeData, EdgeData> {
+    dag: Dag<NodeId, NodeData, EdgeData>,
+    undo: Vec<Vec<Op<NodeId, NodeData, EdgeData>>>,
+    redo: Vec<Vec<Op<NodeId, NodeData, EdgeData>>>,
+}
+
+/// A primitive mutation, which is the inverse of another one
+#[derive(Debug, Clone)]
+enum Op<NodeId, NodeData, EdgeData> {
+    /// Set data of a node, `None` removes it without edges
+    Node(NodeId, Option<NodeData>),
+    /// Set data of an edge, `None` removes it
+    Edge(NodeId, NodeId, Option<EdgeData>),
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Turn `Dag` into a [`HistoryDag`] to undo and redo its mutations
+    pub fn into_history(self) -> HistoryDag<NodeId, NodeData, EdgeData> {
+        HistoryDag {
+            dag: self,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> HistoryDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the `Dag` back, the history is dropped
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Check if there is a step to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Check if there is a step to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Drop all the steps to undo and redo
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Insert a node with data
+    /// # Returns
+    /// * Return `true` when `node_id` is already in `HistoryDag` and its data is replaced
+    pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) -> bool {
+        let old = self.dag.insert_node(node_id.clone(), node_data);
+        let replaced = old.is_some();
+        self.record(Vec::from([Op::Node(node_id, old)]));
+        replaced
+    }
+
+    /// Insert an edge with data
+    /// # Returns
+    /// * Return `Ok(true)` when there is a same edge in `HistoryDag` and its data is replaced
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `HistoryDag`
+    /// * `Err(HasCycle(from,to,data,path))` when a cycle is detected
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<bool, DagError<NodeId, EdgeData>> {
+        let old = self.dag.insert_edge(from.clone(), to.clone(), edge_data)?;
+        let replaced = old.is_some();
+        self.record(Vec::from([Op::Edge(from, to, old)]));
+        Ok(replaced)
+    }
+
+    /// Remove an edge
+    /// # Returns
+    /// * Return `Ok(true)` when success
+    /// * Return `Ok(false)` when there is no such edge, nothing is recorded
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `HistoryDag`
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<bool, DagError<NodeId, EdgeData>> {
+        match self.dag.remove_edge(from.clone(), to.clone())? {
+            Some(data) => {
+                self.record(Vec::from([Op::Edge(from, to, Some(data))]));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Remove a node and all edges related as one step
+    /// # Returns
+    /// * Return `false` when `node_id` is NOT found in `HistoryDag`, nothing is recorded
+    pub fn remove_node(&mut self, node_id: NodeId) -> bool {
+        let (data, edges) = self.dag.remove_node(node_id.clone());
+        let Some(data) = data else {
+            return false;
+        };
+        // ops of a step are applied in reverse order, so the node comes back before its edges
+        let mut step = edges
+            .into_iter()
+            .map(|(from, to, data)| Op::Edge(from, to, Some(data)))
+            .collect::<Vec<_>>();
+        step.push(Op::Node(node_id, Some(data)));
+        self.record(step);
+        true
+    }
+
+    /// Remove all nodes and edges as one step
+    pub fn clear(&mut self) {
+        if self.dag.is_empty() {
+            return;
+        }
+        let (nodes, edges) = mem::take(&mut self.dag).into_parts();
+        let step = edges
+            .map(|(from, to, data)| Op::Edge(from, to, Some(data)))
+            .chain(nodes.map(|(id, data)| Op::Node(id, Some(data))))
+            .collect();
+        self.record(step);
+    }
+
+    /// Undo the last step
+    /// # Returns
+    /// * Return `false` when there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(step) => {
+                let step = self.apply(step);
+                self.redo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone step
+    /// # Returns
+    /// * Return `false` when there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(step) => {
+                let step = self.apply(step);
+                self.undo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn record(&mut self, step: Vec<Op<NodeId, NodeData, EdgeData>>) {
+        self.undo.push(step);
+        self.redo.clear();
+    }
+
+    /// Apply ops of a step in reverse order
+    /// # Returns
+    /// Return the inverse step, whose ops are in the applied order so they are undone in reverse
+    fn apply(
+        &mut self,
+        step: Vec<Op<NodeId, NodeData, EdgeData>>,
+    ) -> Vec<Op<NodeId, NodeData, EdgeData>> {
+        step.into_iter()
+            .rev()
+            .map(|op| match op {
+                Op::Node(node_id, Some(data)) => {
+                    let old = self.dag.insert_node(node_id.clone(), data);
+                    Op::Node(node_id, old)
+                }
+                Op::Node(node_id, None) => {
+                    // edges of the node were removed by the ops before
+                    let (old, _) = self.dag.remove_node(node_id.clone());
+                    Op::Node(node_id, old)
+                }
+                Op::Edge(from, to, Some(data)) => {
+                    // every state in the history is acyclic
+                    let old = self
+                        .dag
+                        .insert_edge(from.clone(), to.clone(), data)
+                        .unwrap_or_else(|_| unreachable!("the edge was in the history"));
+                    Op::Edge(from, to, old)
+                }
+                Op::Edge(from, to, None) => {
+                    let old = self
+                        .dag
+                        .remove_edge(from.clone(), to.clone())
+                        .unwrap_or_else(|_| unreachable!("the edge was in the history"));
+                    Op::Edge(from, to, old)
+                }
+            })
+            .collect()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for HistoryDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
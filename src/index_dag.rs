@@ -0,0 +1,189 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::{fmt::Display, ops::Deref};
+
+use crate::{Dag, DagError, NodeNotFound};
+
+/// A dense node id allocated by [`IndexDag`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeIndex(usize);
+
+impl NodeIndex {
+    /// Get the index as `usize`
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Display for NodeIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A [`NodeIndex`] with the generation of its slot, which detects use-after-remove
+/// # Remarks
+/// * Created by [`IndexDag::insert_node_with_handle`] or [`IndexDag::handle`]
+/// * Resolve it by [`IndexDag::resolve`], a handle of a removed node never resolves
+///   even if its index is reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeHandle {
+    index: NodeIndex,
+    generation: u32,
+}
+
+impl NodeHandle {
+    /// Get the index, which may refer to another node after removal
+    pub fn index(self) -> NodeIndex {
+        self.index
+    }
+}
+
+/// A `Dag` which allocates node ids by itself
+/// # Remarks
+/// * [`IndexDag::insert_node`] returns a fresh [`NodeIndex`], the smallest one NOT in use
+/// * Indices of removed nodes are recycled, so a stale index may refer to a new node.
+///   Hold a [`NodeHandle`] instead to detect it
+/// * Reads go to `Dag` by `Deref`
+#[derive(Debug, Clone)]
+pub struct IndexDag<NodeData, EdgeData> {
+    dag: Dag<NodeIndex, NodeData, EdgeData>,
+    free: BTreeSet<usize>,
+    // bumped when the node of an index is removed
+    generations: Vec<u32>,
+}
+
+impl<NodeData, EdgeData> Default for IndexDag<NodeData, EdgeData> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<NodeData, EdgeData> IndexDag<NodeData, EdgeData> {
+    /// Create an empty DAG
+    pub fn new() -> Self {
+        IndexDag {
+            dag: Dag::new(),
+            free: BTreeSet::new(),
+            generations: Vec::new(),
+        }
+    }
+
+    /// Get the `Dag` back
+    pub fn into_inner(self) -> Dag<NodeIndex, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Insert a node with data
+    /// # Returns
+    /// Return the index allocated for the node
+    pub fn insert_node(&mut self, node_data: NodeData) -> NodeIndex {
+        let index = NodeIndex(
+            self.free
+                .pop_first()
+                .unwrap_or_else(|| self.dag.nodes_len()),
+        );
+        if index.0 == self.generations.len() {
+            self.generations.push(0);
+        }
+        self.dag.insert_node(index, node_data);
+        index
+    }
+
+    /// Insert a node with data
+    /// # Returns
+    /// Return the handle of the node
+    pub fn insert_node_with_handle(&mut self, node_data: NodeData) -> NodeHandle {
+        let index = self.insert_node(node_data);
+        NodeHandle {
+            index,
+            generation: self.generations[index.0],
+        }
+    }
+
+    /// Get the handle of a node
+    /// # Returns
+    /// Return `None` if `index` is not found in `IndexDag`
+    pub fn handle(&self, index: NodeIndex) -> Option<NodeHandle> {
+        self.dag.contains_node(&index).then(|| NodeHandle {
+            index,
+            generation: self.generations[index.0],
+        })
+    }
+
+    /// Get the index of the node which `handle` refers to
+    /// # Returns
+    /// Return `None` if the node was removed, even if its index is reused
+    pub fn resolve(&self, handle: NodeHandle) -> Option<NodeIndex> {
+        (self.handle(handle.index) == Some(handle)).then_some(handle.index)
+    }
+
+    /// Insert an edge with data, see [`Dag::insert_edge`]
+    pub fn insert_edge(
+        &mut self,
+        from: NodeIndex,
+        to: NodeIndex,
+        edge_data: EdgeData,
+    ) -> Result<Option<EdgeData>, DagError<NodeIndex, EdgeData>> {
+        self.dag.insert_edge(from, to, edge_data)
+    }
+
+    /// Remove an edge, see [`Dag::remove_edge`]
+    pub fn remove_edge(
+        &mut self,
+        from: NodeIndex,
+        to: NodeIndex,
+    ) -> Result<Option<EdgeData>, DagError<NodeIndex, EdgeData>> {
+        self.dag.remove_edge(from, to)
+    }
+
+    /// Remove a node and all edges related, see [`Dag::remove_node`]
+    /// # Remarks
+    /// * The index of the node will be reused by later insertions
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &mut self,
+        index: NodeIndex,
+    ) -> (Option<NodeData>, Vec<(NodeIndex, NodeIndex, EdgeData)>) {
+        let result = self.dag.remove_node(index);
+        if result.0.is_some() {
+            self.free.insert(index.0);
+            self.generations[index.0] = self.generations[index.0].wrapping_add(1);
+        }
+        result
+    }
+
+    /// Get mutable data from node
+    /// # Returns
+    /// Return `None` if `index` is not found in `IndexDag`
+    pub fn get_node_mut(&mut self, index: NodeIndex) -> Option<&mut NodeData> {
+        self.dag.get_node_mut(&index)
+    }
+
+    /// Get mutable data from edge, see [`Dag::get_edge_mut`]
+    pub fn get_edge_mut(
+        &mut self,
+        from: NodeIndex,
+        to: NodeIndex,
+    ) -> Result<Option<&mut EdgeData>, NodeNotFound<NodeIndex>> {
+        self.dag.get_edge_mut(&from, &to)
+    }
+
+    /// Remove all nodes and edges, indices are allocated from 0 again
+    /// # Remarks
+    /// * Handles of the removed nodes never resolve
+    pub fn clear(&mut self) {
+        for (index, _) in self.dag.nodes() {
+            self.generations[index.0] = self.generations[index.0].wrapping_add(1);
+        }
+        self.dag.clear();
+        self.free.clear();
+    }
+}
+
+impl<NodeData, EdgeData> Deref for IndexDag<NodeData, EdgeData> {
+    type Target = Dag<NodeIndex, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
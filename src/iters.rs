@@ -1,7 +1,9 @@
 mod children;
 mod edges;
+mod nodes;
 mod parents;
 
 pub use children::{ChildrenIter, ChildrenIterMut};
-pub use edges::{EdgesIter, EdgesIterMut};
-pub use parents::ParentsIter;
+pub use edges::{EdgesIter, EdgesIterMut, IntoEdges};
+pub use nodes::IntoNodes;
+pub use parents::{ParentEdgesIter, ParentEdgesIterMut, ParentsIter};
@@ -1,17 +1,17 @@
 /// iterator of the children in `DAG`
 pub struct ChildrenIter<'a, NodeId, EdgeData> {
-    pub(crate) iter: Option<std::collections::hash_map::Iter<'a, NodeId, EdgeData>>,
+    pub(crate) iter: Option<alloc::collections::btree_map::Iter<'a, NodeId, EdgeData>>,
 }
 
 impl<'a, NodeId, EdgeData> Iterator for ChildrenIter<'a, NodeId, EdgeData>
 where
-    NodeId: Copy,
+    NodeId: Clone,
 {
     type Item = (NodeId, &'a EdgeData);
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(iter) = &mut self.iter {
-            iter.next().map(|(id, data)| (*id, data))
+            iter.next().map(|(id, data)| (id.clone(), data))
         } else {
             None
         }
@@ -27,24 +27,24 @@ where
 }
 
 impl<'a, NodeId, EdgeData> ExactSizeIterator for ChildrenIter<'a, NodeId, EdgeData> where
-    NodeId: Copy
+    NodeId: Clone
 {
 }
 
 /// iterator of the children in `DAG`
 pub struct ChildrenIterMut<'a, NodeId, EdgeData> {
-    pub(crate) iter: Option<std::collections::hash_map::IterMut<'a, NodeId, EdgeData>>,
+    pub(crate) iter: Option<alloc::collections::btree_map::IterMut<'a, NodeId, EdgeData>>,
 }
 
 impl<'a, NodeId, EdgeData> Iterator for ChildrenIterMut<'a, NodeId, EdgeData>
 where
-    NodeId: Copy,
+    NodeId: Clone,
 {
     type Item = (NodeId, &'a mut EdgeData);
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(iter) = &mut self.iter {
-            iter.next().map(|(id, data)| (*id, data))
+            iter.next().map(|(id, data)| (id.clone(), data))
         } else {
             None
         }
@@ -60,6 +60,6 @@ where
 }
 
 impl<'a, NodeId, EdgeData> ExactSizeIterator for ChildrenIterMut<'a, NodeId, EdgeData> where
-    NodeId: Copy
+    NodeId: Clone
 {
 }
@@ -0,0 +1,18 @@
+/// owning iterator of the nodes in `DAG`
+pub struct IntoNodes<NodeId, NodeData> {
+    pub(crate) iter: alloc::collections::btree_map::IntoIter<NodeId, NodeData>,
+}
+
+impl<NodeId, NodeData> Iterator for IntoNodes<NodeId, NodeData> {
+    type Item = (NodeId, NodeData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<NodeId, NodeData> ExactSizeIterator for IntoNodes<NodeId, NodeData> {}
@@ -1,17 +1,17 @@
 /// iterator of the parents in `DAG`
 pub struct ParentsIter<'a, NodeId> {
-    pub(crate) iter: Option<std::collections::hash_set::Iter<'a, NodeId>>,
+    pub(crate) iter: Option<alloc::collections::btree_set::Iter<'a, NodeId>>,
 }
 
 impl<'a, NodeId> Iterator for ParentsIter<'a, NodeId>
 where
-    NodeId: Copy,
+    NodeId: Clone,
 {
     type Item = NodeId;
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(iter) = &mut self.iter {
-            iter.next().copied()
+            iter.next().cloned()
         } else {
             None
         }
@@ -26,4 +26,95 @@ where
     }
 }
 
-impl<'a, NodeId> ExactSizeIterator for ParentsIter<'a, NodeId> where NodeId: Copy {}
+impl<'a, NodeId> ExactSizeIterator for ParentsIter<'a, NodeId> where NodeId: Clone {}
+
+/// iterator of the parents in `DAG` with data of edges pointing into the node
+pub struct ParentEdgesIter<'a, NodeId, EdgeData> {
+    pub(crate) iter: Option<(&'a NodeId, alloc::collections::btree_set::Iter<'a, NodeId>)>,
+    pub(crate) edges:
+        &'a alloc::collections::BTreeMap<NodeId, alloc::collections::BTreeMap<NodeId, EdgeData>>,
+}
+
+impl<'a, NodeId, EdgeData> Iterator for ParentEdgesIter<'a, NodeId, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    type Item = (NodeId, &'a EdgeData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node_id, iter) = self.iter.as_mut()?;
+        let parent_id = iter.next()?.clone();
+        let data = self
+            .edges
+            .get(&parent_id)
+            .and_then(|children| children.get(*node_id))
+            .unwrap_or_else(|| unreachable!("back_edges is consistent with edges"));
+        Some((parent_id, data))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if let Some((_, iter)) = &self.iter {
+            iter.size_hint()
+        } else {
+            (0, Some(0))
+        }
+    }
+}
+
+impl<'a, NodeId, EdgeData> ExactSizeIterator for ParentEdgesIter<'a, NodeId, EdgeData> where
+    NodeId: Clone + Ord
+{
+}
+
+/// iterator of the parents in `DAG` with mutable data of edges pointing into the node
+/// # Remarks
+/// * It walks `edges` from the first parent to the last one,
+///   skipping the nodes which are not parents
+pub struct ParentEdgesIterMut<'a, NodeId, EdgeData> {
+    pub(crate) iter: Option<(&'a NodeId, alloc::collections::btree_set::Iter<'a, NodeId>)>,
+    pub(crate) edges: Option<
+        alloc::collections::btree_map::RangeMut<
+            'a,
+            NodeId,
+            alloc::collections::BTreeMap<NodeId, EdgeData>,
+        >,
+    >,
+}
+
+impl<'a, NodeId, EdgeData> Iterator for ParentEdgesIterMut<'a, NodeId, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    type Item = (NodeId, &'a mut EdgeData);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node_id, iter) = self.iter.as_mut()?;
+        let parent_id = iter.next()?.clone();
+        let edges = self
+            .edges
+            .as_mut()
+            .unwrap_or_else(|| unreachable!("edges is set when there are parents"));
+        for (id, children) in edges {
+            if *id == parent_id {
+                let data = children
+                    .get_mut(*node_id)
+                    .unwrap_or_else(|| unreachable!("back_edges is consistent with edges"));
+                return Some((parent_id, data));
+            }
+        }
+        unreachable!("back_edges is consistent with edges")
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if let Some((_, iter)) = &self.iter {
+            iter.size_hint()
+        } else {
+            (0, Some(0))
+        }
+    }
+}
+
+impl<'a, NodeId, EdgeData> ExactSizeIterator for ParentEdgesIterMut<'a, NodeId, EdgeData> where
+    NodeId: Clone + Ord
+{
+}
@@ -0,0 +1,142 @@
+//! JSON adjacency-list format, enabled by feature `json`
+//! # Schema
+//! ```json
+//! {
+//!   "nodes": [
+//!     { "id": 1, "data": "A" },
+//!     { "id": 2, "data": "B" }
+//!   ],
+//!   "edges": [
+//!     { "from": 1, "to": 2, "data": "a" }
+//!   ]
+//! }
+//! ```
+//! # Remarks
+//! * `data` can be omitted when it is `null`, e.g. for `()` or `None`
+//! * The order of nodes and edges is NOT specified
+use std::{error::Error, fmt::Display};
+
+use serde::{de::DeserializeOwned, de::Error as _, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Dag, DagError};
+
+/// Errors when reading the JSON adjacency-list format
+#[derive(Debug)]
+pub enum JsonError<NodeId, EdgeData> {
+    /// Input is NOT valid JSON, or it doesn't match the schema
+    Json(serde_json::Error),
+    /// Input is NOT a DAG
+    Dag(DagError<NodeId, EdgeData>),
+}
+
+impl<NodeId, EdgeData> Display for JsonError<NodeId, EdgeData>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            JsonError::Json(error) => writeln!(f, "Invalid JSON: {}", error),
+            JsonError::Dag(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl<NodeId, EdgeData> Error for JsonError<NodeId, EdgeData>
+where
+    NodeId: core::fmt::Debug + Display,
+    EdgeData: core::fmt::Debug,
+{
+}
+
+impl<NodeId, EdgeData> From<serde_json::Error> for JsonError<NodeId, EdgeData> {
+    fn from(error: serde_json::Error) -> Self {
+        JsonError::Json(error)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Adjacency<NodeId> {
+    nodes: Vec<Node<NodeId>>,
+    edges: Vec<Edge<NodeId>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Node<NodeId> {
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    data: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Edge<NodeId> {
+    from: NodeId,
+    to: NodeId,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    data: Value,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize,
+    NodeData: Serialize,
+    EdgeData: Serialize,
+{
+    /// Write `Dag` in the JSON adjacency-list format
+    /// # Errors
+    /// * `Err(_)` when node ids or data can't be serialized to JSON
+    pub fn to_json_adjacency(&self) -> Result<String, serde_json::Error> {
+        let nodes = self
+            .nodes()
+            .map(|(id, data)| {
+                Ok(Node {
+                    id,
+                    data: serde_json::to_value(data)?,
+                })
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()?;
+        let edges = self
+            .edges()
+            .map(|(from, to, data)| {
+                Ok(Edge {
+                    from,
+                    to,
+                    data: serde_json::to_value(data)?,
+                })
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()?;
+        serde_json::to_string_pretty(&Adjacency { nodes, edges })
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + DeserializeOwned,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    /// Read `Dag` from the JSON adjacency-list format
+    /// # Errors
+    /// * `Err(Json(_))` when input doesn't match the schema, or it has duplicate nodes or edges
+    /// * `Err(Dag(NodeNotFound(id)))` when an edge refers to a node NOT found
+    /// * `Err(Dag(HasCycle(from,to,data,path)))` when input has a cycle
+    pub fn from_json_adjacency(input: &str) -> Result<Self, JsonError<NodeId, EdgeData>> {
+        let adjacency: Adjacency<NodeId> = serde_json::from_str(input)?;
+        let mut dag = Dag::new();
+        for node in adjacency.nodes {
+            let data = serde_json::from_value(node.data)?;
+            if dag.insert_node(node.id, data).is_some() {
+                return Err(serde_json::Error::custom("duplicate node").into());
+            }
+        }
+        for edge in adjacency.edges {
+            if dag.contains_edge(&edge.from, &edge.to) {
+                return Err(serde_json::Error::custom("duplicate edge").into());
+            }
+            let data = serde_json::from_value(edge.data)?;
+            dag.insert_edge(edge.from, edge.to, data)
+                .map_err(JsonError::Dag)?;
+        }
+        Ok(dag)
+    }
+}
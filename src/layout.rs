@@ -0,0 +1,168 @@
+//! A basic Sugiyama-style layered layout, enabled by feature `layout`
+//! # Steps
+//! 1. Layer assignment by the longest path, see [`Dag::depth_map`]
+//! 2. Crossing reduction by the barycenter heuristic, with dummy vertices on long edges
+//! 3. X-coordinate assignment, centering every layer
+//! # Remarks
+//! * `y` grows with the layer, roots are at `y = 0`
+//! * The layout is deterministic for the same `Dag` and options
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::Dag;
+
+/// Options of [`Dag::layout`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    /// Distance between two adjacent layers, default to `1.0`
+    pub layer_spacing: f32,
+    /// Distance between two adjacent nodes in a layer, default to `1.0`
+    pub node_spacing: f32,
+    /// Count of barycenter sweeps, alternately down and up, default to `4`
+    pub sweeps: usize,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions {
+            layer_spacing: 1.0,
+            node_spacing: 1.0,
+            sweeps: 4,
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Compute 2D coordinates of all the nodes by a layered layout
+    /// # Returns
+    /// Return `(id, (x, y))` of all the nodes in the order of `NodeId`
+    pub fn layout(&self, options: &LayoutOptions) -> Vec<(NodeId, (f32, f32))> {
+        let layers = self.depth_map();
+        let index = layers
+            .keys()
+            .enumerate()
+            .map(|(index, id)| (id.clone(), index))
+            .collect::<BTreeMap<_, _>>();
+        // vertices are the nodes in the order of `NodeId`, then dummies
+        let mut layer_of = layers.values().copied().collect::<Vec<_>>();
+        let nodes_len = layer_of.len();
+        let mut down = vec![Vec::new(); nodes_len];
+        let mut up = vec![Vec::new(); nodes_len];
+        for (from, to, _) in self.edges() {
+            let (mut last, to) = (index[&from], index[&to]);
+            // an edge spanning many layers goes through a dummy in each layer between
+            for layer in layer_of[last] + 1..layer_of[to] {
+                let dummy = layer_of.len();
+                layer_of.push(layer);
+                down.push(Vec::new());
+                up.push(vec![last]);
+                down[last].push(dummy);
+                last = dummy;
+            }
+            down[last].push(to);
+            up[to].push(last);
+        }
+
+        let layers_len = layer_of.iter().max().map_or(0, |layer| layer + 1);
+        let mut order = vec![Vec::new(); layers_len];
+        for (vertex, layer) in layer_of.iter().enumerate() {
+            order[*layer].push(vertex);
+        }
+        let mut best_crossings = crossings(&order, &down);
+        let mut best = order.clone();
+        for sweep in 0..options.sweeps {
+            if sweep % 2 == 0 {
+                for layer in 1..layers_len {
+                    reorder(&mut order, layer, &up);
+                }
+            } else {
+                for layer in (0..layers_len.saturating_sub(1)).rev() {
+                    reorder(&mut order, layer, &down);
+                }
+            }
+            let current = crossings(&order, &down);
+            if current < best_crossings {
+                best_crossings = current;
+                best.clone_from(&order);
+            }
+        }
+
+        let mut coordinates = vec![(0.0, 0.0); nodes_len];
+        for (layer, vertices) in best.iter().enumerate() {
+            let offset = (vertices.len() as f32 - 1.0) / 2.0;
+            for (position, vertex) in vertices.iter().enumerate() {
+                if *vertex < nodes_len {
+                    coordinates[*vertex] = (
+                        (position as f32 - offset) * options.node_spacing,
+                        layer as f32 * options.layer_spacing,
+                    );
+                }
+            }
+        }
+        layers.into_keys().zip(coordinates).collect()
+    }
+}
+
+/// Get the position of every vertex in its layer
+fn positions(order: &[Vec<usize>]) -> Vec<usize> {
+    let mut positions = vec![0; order.iter().map(Vec::len).sum()];
+    for vertices in order {
+        for (position, vertex) in vertices.iter().enumerate() {
+            positions[*vertex] = position;
+        }
+    }
+    positions
+}
+
+/// Sort `layer` by the barycenter of `neighbors`, which are all in an adjacent layer
+/// # Remarks
+/// * A vertex without neighbors keeps its position
+fn reorder(order: &mut [Vec<usize>], layer: usize, neighbors: &[Vec<usize>]) {
+    let positions = positions(order);
+    let mut keys = order[layer]
+        .iter()
+        .map(|vertex| {
+            let neighbors = &neighbors[*vertex];
+            let key = if neighbors.is_empty() {
+                positions[*vertex] as f32
+            } else {
+                let sum = neighbors
+                    .iter()
+                    .map(|neighbor| positions[*neighbor])
+                    .sum::<usize>();
+                sum as f32 / neighbors.len() as f32
+            };
+            (key, *vertex)
+        })
+        .collect::<Vec<_>>();
+    // stable, so ties keep their order
+    keys.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    order[layer] = keys.into_iter().map(|(_, vertex)| vertex).collect();
+}
+
+/// Count the crossings of edges between all the adjacent layers
+fn crossings(order: &[Vec<usize>], down: &[Vec<usize>]) -> usize {
+    let positions = positions(order);
+    let mut count = 0;
+    for vertices in order {
+        let edges = vertices
+            .iter()
+            .flat_map(|from| {
+                down[*from]
+                    .iter()
+                    .map(|to| (positions[*from], positions[*to]))
+            })
+            .collect::<Vec<_>>();
+        for (i, (from_a, to_a)) in edges.iter().enumerate() {
+            for (from_b, to_b) in &edges[i + 1..] {
+                if (from_a < from_b && to_a > to_b) || (from_a > from_b && to_a < to_b) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
@@ -1,10 +1,35 @@
 //! # XDag
 //! A simple DAG (Directed Acyclic Graph) libarary
 //! # Note
-//! This lib just provides a data-structure to store DAG with checking.
-//! It doesn't contain any algorithm about DAG
+//! This lib provides a data-structure to store DAG with checking.
+//! Some common algorithms about DAG are in [`algo`]
 //! # Details
 //! XDAG stores DAG by BTreeMap. Because it can ensure the order of edges and nodes.
+//! `HashDag` stores DAG by HashMap for O(1) lookups, but it CANNOT ensure the order.
+//! [`Dag::freeze`] compacts a DAG into a read-only [`FrozenDag`] for fast traversal.
+//! [`IndexDag`] allocates dense node ids by itself, for data without natural keys.
+//! [`Dag::into_shared`] makes a copy-on-write [`SharedDag`] which hands out cheap snapshots.
+//! # Features
+//! * `std` (default): `std::error::Error` for errors, [`HashDag`] stored by `HashMap`
+//!   and [`SyncDag`] shared by threads.
+//!   Without it this lib is `no_std` and only needs `alloc`
+//! * `serde`: `Serialize` and `Deserialize` for `Dag`. Deserialization rebuilds back edges
+//!   and rejects cycles and edges referring to missing nodes
+//! * `binary`: a compact binary form by postcard, see `Dag::to_bytes`
+//! * `graphml`: read and write GraphML, see [`graphml`]
+//! * `json`: a human-editable JSON adjacency-list format, see [`json`]
+//! * `csv`: read node and edge lists with typed data from CSV, see [`csv`]
+//! * `sqlite`: save and load `Dag` in a two-table SQLite schema, see [`sqlite`]
+//! * `rayon`: parallel iterators of nodes and edges, see `Dag::par_nodes`
+//! * `layout`: a layered layout giving 2D coordinates of nodes, see [`layout`]
+//! * `crdt`: a replicated `Dag` converging without coordination, see [`crdt`]
+//! * `proptest`: `Arbitrary` for `Dag` generating random valid DAGs, which shrink
+//!   toward fewer nodes and edges
+//! * `persistent`: [`PersistentDag`] whose mutations return new versions sharing structure
+//! * `watch`: [`WatchDag`] publishing snapshots to readers which load them wait-free
+//! * `disk`: [`DiskDag`] keeping data of nodes and edges in a file, only the structure in memory
+//! * `wal`: [`WalDag`] logging every mutation before applying it, see `Dag::recover`
+//! * `rand`: random topological orders by a `rand_core::Rng`, see `Dag::random_topological_order`
 //! # Some Examples
 //! ```Rust
 //! // Create a new DAG
@@ -25,66 +50,319 @@
 //!     assert!(leaves.contains(id))
 //! }
 //! ```
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
+
+pub mod algo;
+#[cfg(feature = "proptest")]
+mod arbitrary;
+mod ascii;
+#[cfg(feature = "binary")]
+mod binary;
+mod brand;
+mod builder;
+mod cached;
+mod cascade;
+#[cfg(feature = "crdt")]
+pub mod crdt;
+#[cfg(feature = "csv")]
+pub mod csv;
+mod diff;
+#[cfg(feature = "disk")]
+mod disk;
+pub mod dot;
+mod entry;
 mod error;
+mod fingerprint;
+mod frozen;
+#[cfg(feature = "graphml")]
+pub mod graphml;
+#[cfg(feature = "std")]
+mod hash_dag;
+mod history;
+mod index_dag;
 pub mod iters;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "layout")]
+pub mod layout;
+mod map;
+mod merge;
+mod mermaid;
+pub mod metrics;
+mod observe;
+mod order;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "persistent")]
+mod persistent;
+mod propagate;
+mod rewrite;
+mod scheduler;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+mod stats;
+mod subgraph;
+#[cfg(feature = "std")]
+mod sync_dag;
 #[cfg(test)]
 mod tests;
+mod transaction;
+mod tree;
+mod view;
+#[cfg(feature = "wal")]
+mod wal;
+#[cfg(feature = "watch")]
+mod watch;
+
+use alloc::{
+    borrow::ToOwned,
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{borrow::Borrow, fmt::Display};
 
-use std::{collections::{HashMap, HashSet}, hash::Hash};
+pub use brand::{BrandedDag, BrandedId};
+pub use builder::DagBuilder;
+pub use cached::CachedDag;
+pub use cascade::Removed;
+pub use diff::DagPatch;
+#[cfg(feature = "disk")]
+pub use disk::{DataSlot, DiskDag, DiskError};
+pub use entry::{EdgeEntry, OccupiedEdgeEntry, VacantEdgeEntry};
+pub use error::{CollisionError, DagError, MultipleParents, NodeNotFound};
+pub use frozen::FrozenDag;
+#[cfg(feature = "std")]
+pub use hash_dag::HashDag;
+pub use history::HistoryDag;
+pub use index_dag::{IndexDag, NodeHandle, NodeIndex};
+use iters::{
+    ChildrenIter, ChildrenIterMut, EdgesIter, EdgesIterMut, IntoEdges, IntoNodes, ParentEdgesIter,
+    ParentEdgesIterMut, ParentsIter,
+};
+pub use merge::MergeConflict;
+pub use observe::{DagEvent, ObservedDag, ObserverId};
+use order::Order;
+#[cfg(feature = "persistent")]
+pub use persistent::PersistentDag;
+pub use propagate::Propagation;
+pub use scheduler::Scheduler;
+pub use snapshot::{DagSnapshot, SharedDag};
+pub use stats::DagStats;
+#[cfg(feature = "std")]
+pub use sync_dag::SyncDag;
+pub use transaction::Transaction;
+pub use tree::Tree;
+pub use view::{DagView, Transposed};
+#[cfg(feature = "wal")]
+pub use wal::{WalDag, WalError, WalOptions};
+#[cfg(feature = "watch")]
+pub use watch::{DagReader, WatchDag};
 
-pub use error::DagError;
-use iters::{ChildrenIter, ChildrenIterMut, EdgesIter, EdgesIterMut, ParentsIter};
+/// Direction of an edge seen from a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    /// The edge goes from the node to a child
+    Outgoing,
+    /// The edge comes from a parent to the node
+    Incoming,
+}
 
 /// DAG
 /// # Remarks
 /// * You can store data in 'Node' or 'Edge'
-/// * `NodeId` must be `Copy + Hash + Eq` because DAG is stored by `HashMap` and `HashSet`
+/// * `NodeId` must be `Clone + Ord` because DAG is stored by `BTreeMap` and `BTreeSet`
+/// * Lookups take `&Q` where `NodeId: Borrow<Q>` like `BTreeMap::get`, e.g. `&str` for `String` ids
+/// * Ids are yielded by value, so prefer cheap-to-clone ids such as `Arc<str>`
 #[derive(Debug, Clone)]
 pub struct Dag<NodeId, NodeData, EdgeData> {
-    nodes: HashMap<NodeId, NodeData>,
-    edges: HashMap<NodeId, HashMap<NodeId, EdgeData>>,
-    back_edges: HashMap<NodeId, HashSet<NodeId>>,
+    nodes: BTreeMap<NodeId, NodeData>,
+    edges: BTreeMap<NodeId, BTreeMap<NodeId, EdgeData>>,
+    back_edges: BTreeMap<NodeId, BTreeSet<NodeId>>,
+    // a topological order of nodes, kept by `insert_edge`
+    order: Order<NodeId, BTreeMap<NodeId, usize>>,
+    edges_len: usize,
+}
+
+impl<NodeId, NodeData, EdgeData> Default for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> PartialEq for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: PartialEq,
+    EdgeData: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // back_edges and order are derived from edges
+        self.nodes == other.nodes && self.edges == other.edges
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Eq for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Eq,
+    EdgeData: Eq,
+{
+}
+
+impl<NodeId, NodeData, EdgeData> FromIterator<(NodeId, NodeData)>
+    for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    fn from_iter<T: IntoIterator<Item = (NodeId, NodeData)>>(iter: T) -> Self {
+        let mut dag = Dag::new();
+        dag.extend(iter);
+        dag
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> IntoIterator for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    type Item = (NodeId, NodeData);
+    type IntoIter = IntoNodes<NodeId, NodeData>;
+
+    /// Consume `Dag` and get all the nodes, edges are dropped
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_nodes()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Extend<(NodeId, NodeData)> for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Insert nodes, the data of existing nodes is replaced
+    fn extend<T: IntoIterator<Item = (NodeId, NodeData)>>(&mut self, iter: T) {
+        for (node_id, node_data) in iter {
+            self.insert_node(node_id, node_data);
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Display for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Display,
+    NodeData: Display,
+    EdgeData: Display,
+{
+    /// Write nodes with their children in the order of `NodeId`
+    /// # Remarks
+    /// * `{}` writes one line like `{1: A -> [2: a, 3: b], 2: B, 3: C}`
+    /// * `{:#}` writes one node per line, followed by a line `    -> to: data` per child
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let alternate = f.alternate();
+        if !alternate {
+            write!(f, "{{")?;
+        }
+        // every node has an entry in edges, so they are zipped in the same order
+        for (i, ((node_id, node_data), children)) in
+            self.nodes.iter().zip(self.edges.values()).enumerate()
+        {
+            if i > 0 {
+                write!(f, "{}", if alternate { "\n" } else { ", " })?;
+            }
+            write!(f, "{}: {}", node_id, node_data)?;
+            if alternate {
+                for (to, edge_data) in children {
+                    write!(f, "\n    -> {}: {}", to, edge_data)?;
+                }
+            } else if !children.is_empty() {
+                write!(f, " -> [")?;
+                for (j, (to, edge_data)) in children.iter().enumerate() {
+                    if j > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", to, edge_data)?;
+                }
+                write!(f, "]")?;
+            }
+        }
+        if !alternate {
+            write!(f, "}}")?;
+        }
+        Ok(())
+    }
 }
 
 impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
 where
-    NodeId: Copy + Hash + Eq
+    NodeId: Clone + Ord,
 {
     /// Create an empty DAG
     pub fn new() -> Self {
         Dag {
-            nodes: HashMap::new(),
-            edges: HashMap::new(),
-            back_edges: HashMap::new(),
+            nodes: BTreeMap::new(),
+            edges: BTreeMap::new(),
+            back_edges: BTreeMap::new(),
+            order: Order::default(),
+            edges_len: 0,
         }
     }
 
-    /// Check if a node is in a cycle, this will destroy DAG
-    fn in_cycle(&self, node_id: NodeId) -> bool {
-        // DFS
-        let mut visited = HashSet::new();
-        let mut stack = vec![node_id];
+    /// Create a DAG from edges, nodes are created by `Default` when first seen
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` for the first edge which makes a cycle
+    pub fn from_edges<I>(edges: I) -> Result<Self, DagError<NodeId, EdgeData>>
+    where
+        I: IntoIterator<Item = (NodeId, NodeId, EdgeData)>,
+        NodeData: Default,
+    {
+        Self::from_edges_with(edges, |_| NodeData::default())
+    }
 
-        while let Some(top) = stack.pop() {
-            if visited.contains(&top) {
-                return true;
-            }
-            visited.insert(top);
-            for child_id in self.children(top).map(|(id, _)| id) {
-                stack.push(child_id)
+    /// Create a DAG from edges, nodes are created by `node_factory(id)` when first seen
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` for the first edge which makes a cycle
+    pub fn from_edges_with<I, F>(
+        edges: I,
+        mut node_factory: F,
+    ) -> Result<Self, DagError<NodeId, EdgeData>>
+    where
+        I: IntoIterator<Item = (NodeId, NodeId, EdgeData)>,
+        F: FnMut(NodeId) -> NodeData,
+    {
+        let mut dag = Self::new();
+        let edges = edges.into_iter().collect::<Vec<_>>();
+        for (from, to, _) in edges.iter() {
+            for node_id in [from, to] {
+                if !dag.nodes.contains_key(node_id) {
+                    dag.insert_node(node_id.clone(), node_factory(node_id.clone()));
+                }
             }
         }
-
-        false
+        dag.insert_edges(edges)?;
+        Ok(dag)
     }
 
     /// Check if a `node_id` is contained in `Dag`
-    pub fn contains_node(&self, node_id: NodeId) -> bool {
-        self.nodes.contains_key(&node_id)
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.nodes.contains_key(node_id)
     }
 
     /// Check a node is root
-    pub fn is_root(&self, node_id: NodeId) -> bool {
+    pub fn is_root<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         self.parents(node_id).count() == 0
     }
 
@@ -92,19 +370,22 @@ where
     /// # Returns
     /// * Return `Some(data)` when `node_id` is already in `Dag`
     pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) -> Option<NodeData> {
-        if !self.edges.contains_key(&node_id) {
-            self.edges.insert(node_id, HashMap::new());
-        }
-        if !self.back_edges.contains_key(&node_id) {
-            self.back_edges.insert(node_id, HashSet::new());
+        self.edges.entry(node_id.clone()).or_default();
+        self.back_edges.entry(node_id.clone()).or_default();
+        if !self.nodes.contains_key(&node_id) {
+            self.order.push(node_id.clone());
         }
         self.nodes.insert(node_id, node_data)
     }
 
     /// Check if an `edge` is contained in `Dag`
-    pub fn contains_edge(&self, from: NodeId, to: NodeId) -> bool {
-        if let Some(children) = self.edges.get(&from) {
-            return children.contains_key(&to);
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Some(children) = self.edges.get(from) {
+            return children.contains_key(to);
         }
         false
     }
@@ -112,9 +393,12 @@ where
     /// Insert an edge with data in `Dag`
     /// # Return
     /// * Return `Ok(Some(data))` when there is a same edge in `Dag`
+    /// # Remarks
+    /// * `Dag` keeps a topological order incrementally (Pearce-Kelly), so only the nodes
+    ///   between `to` and `from` in that order are visited to check cycles
     /// # Errors
     /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
-    /// * `Err(HasCycle(from,to,data))` when a cycle is detected
+    /// * `Err(HasCycle(from,to,data,path))` when a cycle is detected
     pub fn insert_edge(
         &mut self,
         from: NodeId,
@@ -127,22 +411,19 @@ where
         if !self.nodes.contains_key(&to) {
             return Err(DagError::NodeNotFound(to));
         }
+        if !self.contains_edge(&from, &to) && !self.reorder(&from, &to) {
+            let path = self
+                .find_path(to.clone(), from.clone())
+                .unwrap_or_else(|| unreachable!("reorder found a path"));
+            return Err(DagError::HasCycle(from, to, edge_data, path));
+        }
         let children = self
             .edges
             .get_mut(&from)
             .unwrap_or_else(|| unreachable!("proved by contains_key"));
-        let result = children.insert(to, edge_data);
-        if self.in_cycle(from) {
-            // roll back
-            // remove that edge
-            let children = self
-                .edges
-                .get_mut(&from)
-                .unwrap_or_else(|| unreachable!("proved by contains_key"));
-            let data = children
-                .remove(&to)
-                .unwrap_or_else(|| unreachable!("proved by contains_key"));
-            return Err(DagError::HasCycle(from, to, data));
+        let result = children.insert(to.clone(), edge_data);
+        if result.is_none() {
+            self.edges_len += 1;
         }
         // added back edge
         let parents = self
@@ -153,6 +434,112 @@ where
         Ok(result)
     }
 
+    /// Insert many edges with data in `Dag`
+    /// # Remarks
+    /// * Edges are inserted one by one, it stops at the first error
+    ///   and the edges inserted before are kept
+    /// * The data of existing edges is replaced
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` of an edge is NOT found in `Dag`
+    /// * `Err(HasCycle(from,to,data,path))` when an edge makes a cycle
+    pub fn extend_edges<I>(&mut self, edges: I) -> Result<(), DagError<NodeId, EdgeData>>
+    where
+        I: IntoIterator<Item = (NodeId, NodeId, EdgeData)>,
+    {
+        for (from, to, edge_data) in edges {
+            self.insert_edge(from, to, edge_data)?;
+        }
+        Ok(())
+    }
+
+    /// Insert many edges with data in `Dag` at once
+    /// # Remarks
+    /// * All the edges are inserted or none of them is, `Dag` is NOT modified on error
+    /// * Cycles are checked once for the whole batch in O(V+E),
+    ///   prefer [`Dag::insert_edge`] for a few edges in a large `Dag`
+    /// * The data of existing edges is replaced
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` of an edge is NOT found in `Dag`
+    /// * `Err(HasCycle(from,to,data,path))` for the first edge in the batch which makes a cycle
+    pub fn insert_edges<I>(&mut self, edges: I) -> Result<(), DagError<NodeId, EdgeData>>
+    where
+        I: IntoIterator<Item = (NodeId, NodeId, EdgeData)>,
+    {
+        let edges = edges.into_iter().collect::<Vec<_>>();
+        for (from, to, _) in edges.iter() {
+            if !self.nodes.contains_key(from) {
+                return Err(DagError::NodeNotFound(from.clone()));
+            }
+            if !self.nodes.contains_key(to) {
+                return Err(DagError::NodeNotFound(to.clone()));
+            }
+        }
+        let mut replaced = Vec::with_capacity(edges.len());
+        for (from, to, edge_data) in edges {
+            let old = self
+                .edges
+                .get_mut(&from)
+                .unwrap_or_else(|| unreachable!("proved by contains_key"))
+                .insert(to.clone(), edge_data);
+            self.back_edges
+                .get_mut(&to)
+                .unwrap_or_else(|| unreachable!("proved by contains_key"))
+                .insert(from.clone());
+            if old.is_none() {
+                self.edges_len += 1;
+            }
+            replaced.push((from, to, old));
+        }
+        if self.rebuild_order() {
+            return Ok(());
+        }
+
+        // roll back, then insert one by one to find the edge making a cycle
+        let edges = self.restore_edges(replaced);
+        let mut replaced = Vec::new();
+        for (from, to, edge_data) in edges {
+            match self.insert_edge(from.clone(), to.clone(), edge_data) {
+                Ok(old) => replaced.push((from, to, old)),
+                Err(error) => {
+                    self.restore_edges(replaced);
+                    return Err(error);
+                }
+            }
+        }
+        unreachable!("the batch has a cycle")
+    }
+
+    /// Undo inserted edges, restoring the data they replaced
+    /// # Returns
+    /// * The removed edges in the order they were inserted
+    fn restore_edges(
+        &mut self,
+        replaced: Vec<(NodeId, NodeId, Option<EdgeData>)>,
+    ) -> Vec<(NodeId, NodeId, EdgeData)> {
+        let mut edges = Vec::with_capacity(replaced.len());
+        for (from, to, old) in replaced.into_iter().rev() {
+            let children = self
+                .edges
+                .get_mut(&from)
+                .unwrap_or_else(|| unreachable!("edges were inserted"));
+            let edge_data = match old {
+                Some(old) => children.insert(to.clone(), old),
+                None => {
+                    self.edges_len -= 1;
+                    self.back_edges
+                        .get_mut(&to)
+                        .unwrap_or_else(|| unreachable!("edges were inserted"))
+                        .remove(&from);
+                    children.remove(&to)
+                }
+            }
+            .unwrap_or_else(|| unreachable!("edges were inserted"));
+            edges.push((from, to, edge_data));
+        }
+        edges.reverse();
+        edges
+    }
+
     /// Remove an edge from `Dag`
     /// # Returns
     /// * Return `Ok(Some(data))` when success
@@ -175,6 +562,9 @@ where
             .get_mut(&from)
             .unwrap_or_else(|| unreachable!("proved by contains_key"));
         let result = children.remove(&to);
+        if result.is_some() {
+            self.edges_len -= 1;
+        }
         let parents = self
             .back_edges
             .get_mut(&to)
@@ -185,17 +575,25 @@ where
 
     /// remove a node and all edges related
     /// # Returns
-    /// * Return `(Some(data),edges_data)` if succeeded
-    pub fn remove_node(&mut self, node_id: NodeId) -> (Option<NodeData>, Vec<EdgeData>) {
-        if !self.contains_node(node_id) {
+    /// * Return `(Some(data),edges)` if succeeded, `edges` are `(from,to,data)` of removed edges,
+    ///   edges to children first
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &mut self,
+        node_id: NodeId,
+    ) -> (Option<NodeData>, Vec<(NodeId, NodeId, EdgeData)>) {
+        if !self.nodes.contains_key(&node_id) {
             return (None, Vec::new());
         }
         let mut edge_data = Vec::new();
         // remove children edges
-        let ids = self.children(node_id).map(|(id, _)| id).collect::<Vec<_>>();
+        let ids = self
+            .children(&node_id)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
         for child_id in ids {
             let data = self
-                .remove_edge(node_id, child_id)
+                .remove_edge(node_id.clone(), child_id.clone())
                 .unwrap_or_else(|_| {
                     unreachable!(
                         "Xdag ensures this node exists both in nodes and edges at the same time"
@@ -204,13 +602,13 @@ where
                 .unwrap_or_else(|| {
                     unreachable!("data is from self.children, so there must be such an edge")
                 });
-            edge_data.push(data);
+            edge_data.push((node_id.clone(), child_id, data));
         }
         // remove parents edges
-        let ids = self.parents(node_id).collect::<Vec<_>>();
+        let ids = self.parents(&node_id).collect::<Vec<_>>();
         for parent_id in ids {
             let data = self
-                .remove_edge(parent_id, node_id)
+                .remove_edge(parent_id.clone(), node_id.clone())
                 .unwrap_or_else(|_| {
                     unreachable!(
                         "Xdag ensures this node exists both in nodes and edges at the same time"
@@ -219,47 +617,232 @@ where
                 .unwrap_or_else(|| {
                     unreachable!("data is from self.parents, so there must be such an edge")
                 });
-            edge_data.push(data)
+            edge_data.push((parent_id, node_id.clone(), data))
         }
         // remove node
+        self.edges.remove(&node_id);
+        self.back_edges.remove(&node_id);
+        self.order.remove(&node_id);
         let node_data = self.nodes.remove(&node_id);
         (node_data, edge_data)
     }
 
+    /// Remove all the nodes NOT matching `predicate` and all edges related
+    pub fn retain_nodes<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(NodeId, &NodeData) -> bool,
+    {
+        let mut removed = Vec::new();
+        self.nodes.retain(|id, data| {
+            let keep = predicate(id.clone(), data);
+            if !keep {
+                removed.push(id.clone());
+            }
+            keep
+        });
+        for node_id in removed {
+            let children = self
+                .edges
+                .remove(&node_id)
+                .unwrap_or_else(|| unreachable!("every node has an entry in edges"));
+            for child_id in children.keys() {
+                self.back_edges
+                    .get_mut(child_id)
+                    .unwrap_or_else(|| unreachable!("edges is consistent with back_edges"))
+                    .remove(&node_id);
+            }
+            let parents = self
+                .back_edges
+                .remove(&node_id)
+                .unwrap_or_else(|| unreachable!("every node has an entry in back_edges"));
+            for parent_id in parents.iter() {
+                self.edges
+                    .get_mut(parent_id)
+                    .unwrap_or_else(|| unreachable!("back_edges is consistent with edges"))
+                    .remove(&node_id);
+            }
+            self.edges_len -= children.len() + parents.len();
+            self.order.remove(&node_id);
+        }
+    }
+
+    /// Remove all the edges NOT matching `predicate`
+    pub fn retain_edges<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(NodeId, NodeId, &EdgeData) -> bool,
+    {
+        let back_edges = &mut self.back_edges;
+        let edges_len = &mut self.edges_len;
+        for (from, children) in self.edges.iter_mut() {
+            children.retain(|to, data| {
+                let keep = predicate(from.clone(), to.clone(), data);
+                if !keep {
+                    back_edges
+                        .get_mut(to)
+                        .unwrap_or_else(|| unreachable!("edges is consistent with back_edges"))
+                        .remove(from);
+                    *edges_len -= 1;
+                }
+                keep
+            });
+        }
+    }
+
     /// Get an iterator of all the children of given `node_id`
-    pub fn children(&self, node_id: NodeId) -> ChildrenIter<'_, NodeId, EdgeData> {
+    pub fn children<Q>(&self, node_id: &Q) -> ChildrenIter<'_, NodeId, EdgeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         ChildrenIter {
-            iter: self.edges.get(&node_id).map(|map| map.iter()),
+            iter: self.edges.get(node_id).map(|map| map.iter()),
         }
     }
 
     /// Get an iterator of all the children of given `node_id`
-    pub fn children_mut(&mut self, node_id: NodeId) -> ChildrenIterMut<'_, NodeId, EdgeData> {
+    pub fn children_mut<Q>(&mut self, node_id: &Q) -> ChildrenIterMut<'_, NodeId, EdgeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         ChildrenIterMut {
-            iter: self.edges.get_mut(&node_id).map(|map| map.iter_mut()),
+            iter: self.edges.get_mut(node_id).map(|map| map.iter_mut()),
         }
     }
 
-    /// Get an iterator of all the children of given `node_id`
-    pub fn parents(&self, node_id: NodeId) -> ParentsIter<'_, NodeId> {
+    /// Get an iterator of all the parents of given `node_id`
+    pub fn parents<Q>(&self, node_id: &Q) -> ParentsIter<'_, NodeId>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         ParentsIter {
-            iter: self.back_edges.get(&node_id).map(|set| set.iter()),
+            iter: self.back_edges.get(node_id).map(|set| set.iter()),
+        }
+    }
+
+    /// Get an iterator of all the nodes adjacent to given `node_id` ignoring direction,
+    /// with data of the edges
+    /// # Remarks
+    /// * Children come first tagged by [`Direction::Outgoing`],
+    ///   then parents tagged by [`Direction::Incoming`]
+    pub fn neighbors<Q>(
+        &self,
+        node_id: &Q,
+    ) -> impl Iterator<Item = (Direction, NodeId, &'_ EdgeData)>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.children(node_id)
+            .map(|(id, data)| (Direction::Outgoing, id, data))
+            .chain(
+                self.parent_edges(node_id)
+                    .map(|(id, data)| (Direction::Incoming, id, data)),
+            )
+    }
+
+    /// Get an iterator of all the nodes sharing at least one parent with given `node_id`
+    /// # Remarks
+    /// * `node_id` itself is excluded, siblings are yielded once in the order of `NodeId`
+    pub fn siblings<Q>(&self, node_id: &Q) -> impl Iterator<Item = NodeId>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut siblings = BTreeSet::new();
+        for parent_id in self.parents(node_id) {
+            siblings.extend(self.children::<NodeId>(&parent_id).map(|(id, _)| id));
+        }
+        siblings.retain(|id| id.borrow() != node_id);
+        siblings.into_iter()
+    }
+
+    /// Get an iterator of all the parents of given `node_id` with data of the edges
+    pub fn parent_edges<Q>(&self, node_id: &Q) -> ParentEdgesIter<'_, NodeId, EdgeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        ParentEdgesIter {
+            iter: self
+                .back_edges
+                .get_key_value(node_id)
+                .map(|(id, set)| (id, set.iter())),
+            edges: &self.edges,
+        }
+    }
+
+    /// Get an iterator of all the parents of given `node_id` with mutable data of the edges
+    pub fn parent_edges_mut<Q>(&mut self, node_id: &Q) -> ParentEdgesIterMut<'_, NodeId, EdgeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let parents = self.back_edges.get_key_value(node_id);
+        let range = parents
+            .and_then(|(_, set)| Some((set.first()?, set.last()?)))
+            .map(|(first, last)| self.edges.range_mut::<NodeId, _>(first..=last));
+        ParentEdgesIterMut {
+            iter: parents.map(|(id, set)| (id, set.iter())),
+            edges: range,
         }
     }
 
+    /// Get the count of parents of given `node_id`
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `Dag`
+    pub fn in_degree<Q>(&self, node_id: &Q) -> Option<usize>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.back_edges.get(node_id).map(BTreeSet::len)
+    }
+
+    /// Get the count of children of given `node_id`
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `Dag`
+    pub fn out_degree<Q>(&self, node_id: &Q) -> Option<usize>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.edges.get(node_id).map(BTreeMap::len)
+    }
+
     /// Get the count of nodes
     pub fn nodes_len(&self) -> usize {
         self.nodes.len()
     }
 
+    /// Get the count of edges
+    pub fn edges_len(&self) -> usize {
+        self.edges_len
+    }
+
+    /// Check if there is no node in `Dag`
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Remove all the nodes and edges
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.back_edges.clear();
+        self.order = Order::default();
+        self.edges_len = 0;
+    }
+
     /// Get all the nodes in `Dag`
     pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
-        self.nodes.iter().map(|(id, data)| (*id, data))
+        self.nodes.iter().map(|(id, data)| (id.clone(), data))
     }
 
     /// Get all the nodes in `Dag`
     pub fn nodes_mut(&mut self) -> impl Iterator<Item = (NodeId, &'_ mut NodeData)> {
-        self.nodes.iter_mut().map(|(id, data)| (*id, data))
+        self.nodes.iter_mut().map(|(id, data)| (id.clone(), data))
     }
 
     /// Get all the edges in `Dag`
@@ -278,28 +861,84 @@ where
         }
     }
 
+    /// Consume `Dag` and get all the nodes, edges are dropped
+    pub fn into_nodes(self) -> IntoNodes<NodeId, NodeData> {
+        IntoNodes {
+            iter: self.nodes.into_iter(),
+        }
+    }
+
+    /// Consume `Dag` and get all the edges, nodes are dropped
+    pub fn into_edges(self) -> IntoEdges<NodeId, EdgeData> {
+        IntoEdges {
+            from_iter: self.edges.into_iter(),
+            to_iter: None,
+        }
+    }
+
+    /// Consume `Dag` and get all the nodes and edges
+    pub fn into_parts(self) -> (IntoNodes<NodeId, NodeData>, IntoEdges<NodeId, EdgeData>) {
+        (
+            IntoNodes {
+                iter: self.nodes.into_iter(),
+            },
+            IntoEdges {
+                from_iter: self.edges.into_iter(),
+                to_iter: None,
+            },
+        )
+    }
+
     /// Get all the leaves in `Dag`
     pub fn leaves(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
-        self.nodes().filter(|(id, _)| self.children(*id).len() == 0)
+        self.nodes().filter(|(id, _)| self.children(id).len() == 0)
     }
 
     /// Get all the roots in `Dag`
     pub fn roots(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
-        self.nodes().filter(|(id, _)| self.parents(*id).len() == 0)
+        self.nodes().filter(|(id, _)| self.parents(id).len() == 0)
+    }
+
+    /// Get all the leaves in `Dag` with mutable data
+    pub fn leaves_mut(&mut self) -> impl Iterator<Item = (NodeId, &'_ mut NodeData)> {
+        // every node has an entry in edges, so they are zipped in the same order
+        self.nodes
+            .iter_mut()
+            .zip(self.edges.values())
+            .filter(|(_, children)| children.is_empty())
+            .map(|((id, data), _)| (id.clone(), data))
+    }
+
+    /// Get all the roots in `Dag` with mutable data
+    pub fn roots_mut(&mut self) -> impl Iterator<Item = (NodeId, &'_ mut NodeData)> {
+        // every node has an entry in back_edges, so they are zipped in the same order
+        self.nodes
+            .iter_mut()
+            .zip(self.back_edges.values())
+            .filter(|(_, parents)| parents.is_empty())
+            .map(|((id, data), _)| (id.clone(), data))
     }
 
     /// Get data from node
     /// # Returns
     /// Return `None` if `node_id` is not found in `Dag`
-    pub fn get_node(&self, node_id: NodeId) -> Option<&NodeData> {
-        self.nodes.get(&node_id)
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<&NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.nodes.get(node_id)
     }
 
     /// Get mutable data from node
     /// # Returns
     /// Return `None` if `node_id` is not found in `Dag`
-    pub fn get_node_mut(&mut self, node_id: NodeId) -> Option<&mut NodeData> {
-        self.nodes.get_mut(&node_id)
+    pub fn get_node_mut<Q>(&mut self, node_id: &Q) -> Option<&mut NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.nodes.get_mut(node_id)
     }
 
     /// Get data from edge
@@ -307,22 +946,22 @@ where
     /// Return `Ok(Some(data))` if success
     /// # Errors
     /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
-    pub fn get_edge(
-        &self,
-        from: NodeId,
-        to: NodeId,
-    ) -> Result<Option<&EdgeData>, DagError<NodeId, EdgeData>> {
-        if !self.nodes.contains_key(&from) {
-            return Err(DagError::NodeNotFound(from));
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Result<Option<&EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        if !self.nodes.contains_key(from) {
+            return Err(NodeNotFound(from.to_owned()));
         }
-        if !self.nodes.contains_key(&to) {
-            return Err(DagError::NodeNotFound(to));
+        if !self.nodes.contains_key(to) {
+            return Err(NodeNotFound(to.to_owned()));
         }
         let children = self
             .edges
-            .get(&from)
+            .get(from)
             .unwrap_or_else(|| unreachable!("proved by contains_key"));
-        Ok(children.get(&to))
+        Ok(children.get(to))
     }
 
     /// Get mutable data from edge
@@ -330,21 +969,25 @@ where
     /// Return `Ok(Some(data))` if success
     /// # Errors
     /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT stored in `Dag`
-    pub fn get_edge_mut(
+    pub fn get_edge_mut<Q>(
         &mut self,
-        from: NodeId,
-        to: NodeId,
-    ) -> Result<Option<&mut EdgeData>, DagError<NodeId, EdgeData>> {
-        if !self.nodes.contains_key(&from) {
-            return Err(DagError::NodeNotFound(from));
+        from: &Q,
+        to: &Q,
+    ) -> Result<Option<&mut EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        if !self.nodes.contains_key(from) {
+            return Err(NodeNotFound(from.to_owned()));
         }
-        if !self.nodes.contains_key(&to) {
-            return Err(DagError::NodeNotFound(to));
+        if !self.nodes.contains_key(to) {
+            return Err(NodeNotFound(to.to_owned()));
         }
         let children = self
             .edges
-            .get_mut(&from)
+            .get_mut(from)
             .unwrap_or_else(|| unreachable!("proved by contains_key"));
-        Ok(children.get_mut(&to))
+        Ok(children.get_mut(to))
     }
 }
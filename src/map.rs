@@ -0,0 +1,161 @@
+use alloc::collections::BTreeMap;
+
+use crate::{order::Order, CollisionError, Dag};
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Convert data of all the nodes and edges, keeping the structure
+    /// # Remarks
+    /// * `node_map(id, data)` converts data of a node
+    /// * `edge_map(from, to, data)` converts data of an edge
+    /// * No cycle checking is needed since the structure is NOT changed
+    pub fn map<N2, E2, FN, FE>(self, mut node_map: FN, mut edge_map: FE) -> Dag<NodeId, N2, E2>
+    where
+        FN: FnMut(NodeId, NodeData) -> N2,
+        FE: FnMut(NodeId, NodeId, EdgeData) -> E2,
+    {
+        Dag {
+            nodes: self
+                .nodes
+                .into_iter()
+                .map(|(id, data)| (id.clone(), node_map(id, data)))
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|(from, children)| {
+                    let children = children
+                        .into_iter()
+                        .map(|(to, data)| (to.clone(), edge_map(from.clone(), to, data)))
+                        .collect();
+                    (from, children)
+                })
+                .collect(),
+            back_edges: self.back_edges,
+            order: self.order,
+            edges_len: self.edges_len,
+        }
+    }
+
+    /// Convert data of all the nodes and edges, dropping those converted to `None`
+    /// # Remarks
+    /// * `node_map(id, data)` converts data of a node, the node and all edges related
+    ///   are dropped when it returns `None`
+    /// * `edge_map(from, to, data)` converts data of an edge between kept nodes,
+    ///   the edge is dropped when it returns `None`
+    /// * No cycle checking is needed since only nodes and edges are dropped
+    pub fn filter_map<N2, E2, FN, FE>(
+        self,
+        mut node_map: FN,
+        mut edge_map: FE,
+    ) -> Dag<NodeId, N2, E2>
+    where
+        FN: FnMut(NodeId, NodeData) -> Option<N2>,
+        FE: FnMut(NodeId, NodeId, EdgeData) -> Option<E2>,
+    {
+        let mut dag = Dag::new();
+        for (node_id, data) in self.nodes {
+            if let Some(data) = node_map(node_id.clone(), data) {
+                dag.insert_node(node_id, data);
+            }
+        }
+        for (from, children) in self.edges {
+            if !dag.nodes.contains_key(&from) {
+                continue;
+            }
+            for (to, data) in children {
+                if !dag.nodes.contains_key(&to) {
+                    continue;
+                }
+                if let Some(data) = edge_map(from.clone(), to.clone(), data) {
+                    dag.edges
+                        .get_mut(&from)
+                        .unwrap_or_else(|| unreachable!("proved by contains_node"))
+                        .insert(to.clone(), data);
+                    dag.back_edges
+                        .get_mut(&to)
+                        .unwrap_or_else(|| unreachable!("proved by contains_node"))
+                        .insert(from.clone());
+                    dag.edges_len += 1;
+                }
+            }
+        }
+        // a subset of a topological order is still a topological order
+        let positions = self
+            .order
+            .positions
+            .into_iter()
+            .filter(|(id, _)| dag.nodes.contains_key(id))
+            .collect();
+        dag.order = Order::from_positions(positions, self.order.next);
+        dag
+    }
+
+    /// Convert all the node ids, keeping data and the structure
+    /// # Returns
+    /// Return the `Dag` keyed by the new ids
+    /// # Errors
+    /// * `Err(CollisionError(first, second, new_id))` when two nodes are converted to the same id,
+    ///   `first < second`
+    /// # Remarks
+    /// * `id_map(id)` is called once for every node
+    /// * No cycle checking is needed since the structure is NOT changed
+    pub fn map_ids<NewId, F>(
+        self,
+        mut id_map: F,
+    ) -> Result<Dag<NewId, NodeData, EdgeData>, CollisionError<NodeId, NewId>>
+    where
+        NewId: Clone + Ord,
+        F: FnMut(&NodeId) -> NewId,
+    {
+        let mut new_ids = BTreeMap::new();
+        let mut old_ids = BTreeMap::new();
+        for node_id in self.nodes.keys() {
+            let new_id = id_map(node_id);
+            if let Some(first) = old_ids.insert(new_id.clone(), node_id.clone()) {
+                return Err(CollisionError(first, node_id.clone(), new_id));
+            }
+            new_ids.insert(node_id.clone(), new_id);
+        }
+        let new_id = |id: &NodeId| {
+            new_ids
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| unreachable!("every node has a new id"))
+        };
+        Ok(Dag {
+            nodes: self
+                .nodes
+                .into_iter()
+                .map(|(id, data)| (new_id(&id), data))
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|(from, children)| {
+                    let children = children
+                        .into_iter()
+                        .map(|(to, data)| (new_id(&to), data))
+                        .collect();
+                    (new_id(&from), children)
+                })
+                .collect(),
+            back_edges: self
+                .back_edges
+                .into_iter()
+                .map(|(to, parents)| (new_id(&to), parents.iter().map(new_id).collect()))
+                .collect(),
+            order: Order::from_positions(
+                self.order
+                    .positions
+                    .into_iter()
+                    .map(|(id, position)| (new_id(&id), position))
+                    .collect(),
+                self.order.next,
+            ),
+            edges_len: self.edges_len,
+        })
+    }
+}
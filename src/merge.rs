@@ -0,0 +1,226 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use crate::{Dag, DagError};
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Merge `other` into `Dag`
+    /// # Remarks
+    /// * Nodes and edges are matched by `NodeId`
+    /// * `node_merge(id, ours, theirs)` resolves a node stored in both DAGs
+    /// * `edge_merge(from, to, ours, theirs)` resolves an edge stored in both DAGs
+    /// * `Dag` is NOT modified when an error is returned
+    /// * Cycles are checked once for all the edges of `other` in O(V+E), like [`Dag::insert_edges`]
+    /// # Errors
+    /// * `Err(HasCycle(from,to,data,path))` when the edge `from -> to` of `other` makes a cycle
+    pub fn merge<FN, FE>(
+        &mut self,
+        other: Self,
+        mut node_merge: FN,
+        mut edge_merge: FE,
+    ) -> Result<(), DagError<NodeId, EdgeData>>
+    where
+        FN: FnMut(NodeId, NodeData, NodeData) -> NodeData,
+        FE: FnMut(NodeId, NodeId, EdgeData, EdgeData) -> EdgeData,
+    {
+        // splice the structure in first, data is merged only when there is no cycle
+        let mut new_nodes = Vec::new();
+        let mut shared_nodes = Vec::new();
+        for (node_id, theirs) in other.nodes {
+            if self.nodes.contains_key(&node_id) {
+                shared_nodes.push((node_id, theirs));
+            } else {
+                new_nodes.push(node_id.clone());
+                self.insert_node(node_id, theirs);
+            }
+        }
+        let mut new_edges = Vec::new();
+        let mut shared_edges = Vec::new();
+        for (from, children) in other.edges {
+            for (to, theirs) in children {
+                if self.contains_edge(&from, &to) {
+                    shared_edges.push((from.clone(), to, theirs));
+                } else {
+                    new_edges.push((from.clone(), to, theirs));
+                }
+            }
+        }
+        // cycles are checked once for all the edges, and located only on failure
+        if let Err(error) = self.insert_edges(new_edges) {
+            for node_id in new_nodes {
+                self.remove_node(node_id);
+            }
+            return Err(error);
+        }
+
+        for (node_id, theirs) in shared_nodes {
+            let ours = self
+                .nodes
+                .remove(&node_id)
+                .unwrap_or_else(|| unreachable!("shared nodes are in Dag"));
+            let data = node_merge(node_id.clone(), ours, theirs);
+            self.nodes.insert(node_id, data);
+        }
+        for (from, to, theirs) in shared_edges {
+            let children = self
+                .edges
+                .get_mut(&from)
+                .unwrap_or_else(|| unreachable!("shared edges are in Dag"));
+            let ours = children
+                .remove(&to)
+                .unwrap_or_else(|| unreachable!("shared edges are in Dag"));
+            let data = edge_merge(from, to.clone(), ours, theirs);
+            children.insert(to, data);
+        }
+        Ok(())
+    }
+}
+
+/// A conflict of [`Dag::merge3`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeConflict<NodeId, NodeData, EdgeData> {
+    /// Both sides changed a node differently, `None` means missing or removed
+    /// # Remarks
+    /// * The merged node is `ours` if it is `Some`, otherwise `theirs`,
+    ///   so an edit wins over a removal
+    Node {
+        id: NodeId,
+        base: Option<NodeData>,
+        ours: Option<NodeData>,
+        theirs: Option<NodeData>,
+    },
+    /// Both sides changed an edge differently, resolved like [`MergeConflict::Node`]
+    Edge {
+        from: NodeId,
+        to: NodeId,
+        base: Option<EdgeData>,
+        ours: Option<EdgeData>,
+        theirs: Option<EdgeData>,
+    },
+    /// The merged edge refers to a node removed by the merge, it is dropped
+    DanglingEdge {
+        from: NodeId,
+        to: NodeId,
+        data: EdgeData,
+    },
+    /// The merged edge makes a cycle with edges of the other side, it is dropped
+    /// # Remarks
+    /// * `path` is `to -> ... -> from` in the merged `Dag`
+    Cycle {
+        from: NodeId,
+        to: NodeId,
+        data: EdgeData,
+        path: Vec<NodeId>,
+    },
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone + PartialEq,
+    EdgeData: Clone + PartialEq,
+{
+    /// Merge the changes of `ours` and `theirs` since `base`, like a three-way merge of a VCS
+    /// # Returns
+    /// Return the merged `Dag` and all the conflicts, nodes and edges in conflict are resolved
+    /// as described in [`MergeConflict`]
+    /// # Remarks
+    /// * A node or an edge changed by one side only takes that change
+    /// * Edges of `ours` are inserted first, so a cycle is always broken by dropping
+    ///   an edge which is NOT in `ours`
+    #[allow(clippy::type_complexity)]
+    pub fn merge3(
+        base: &Self,
+        ours: &Self,
+        theirs: &Self,
+    ) -> (Self, Vec<MergeConflict<NodeId, NodeData, EdgeData>>) {
+        let mut merged = Dag::new();
+        let mut conflicts = Vec::new();
+        let ids = base
+            .nodes
+            .keys()
+            .chain(ours.nodes.keys())
+            .chain(theirs.nodes.keys())
+            .collect::<BTreeSet<_>>();
+        for id in ids {
+            let sides = [base, ours, theirs].map(|dag| dag.nodes.get(id));
+            let data = merge_value(sides, |base, ours, theirs| {
+                conflicts.push(MergeConflict::Node {
+                    id: id.clone(),
+                    base,
+                    ours,
+                    theirs,
+                })
+            });
+            if let Some(data) = data {
+                merged.insert_node(id.clone(), data);
+            }
+        }
+
+        let edges = base
+            .edges()
+            .chain(ours.edges())
+            .chain(theirs.edges())
+            .map(|(from, to, _)| (from, to))
+            .collect::<BTreeSet<_>>();
+        let mut merged_edges = Vec::new();
+        for (from, to) in edges {
+            let sides = [base, ours, theirs]
+                .map(|dag| dag.edges.get(&from).and_then(|children| children.get(&to)));
+            let data = merge_value(sides, |base, ours, theirs| {
+                conflicts.push(MergeConflict::Edge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    base,
+                    ours,
+                    theirs,
+                })
+            });
+            let Some(data) = data else {
+                continue;
+            };
+            if !merged.contains_node(&from) || !merged.contains_node(&to) {
+                conflicts.push(MergeConflict::DanglingEdge { from, to, data });
+            } else {
+                merged_edges.push((!ours.contains_edge(&from, &to), from, to, data));
+            }
+        }
+        // edges of `ours` first, they never make a cycle together
+        merged_edges.sort_by_key(|(theirs_only, _, _, _)| *theirs_only);
+        for (_, from, to, data) in merged_edges {
+            if let Err(DagError::HasCycle(from, to, data, path)) =
+                merged.insert_edge(from, to, data)
+            {
+                conflicts.push(MergeConflict::Cycle {
+                    from,
+                    to,
+                    data,
+                    path,
+                });
+            }
+        }
+        (merged, conflicts)
+    }
+}
+
+/// Merge a value of three sides, `None` means missing
+/// # Remarks
+/// * `conflict(base, ours, theirs)` is called when both sides changed it differently,
+///   then `ours` is taken if it is `Some`, otherwise `theirs`
+fn merge_value<T, F>(sides: [Option<&T>; 3], conflict: F) -> Option<T>
+where
+    T: Clone + PartialEq,
+    F: FnOnce(Option<T>, Option<T>, Option<T>),
+{
+    let [base, ours, theirs] = sides;
+    if ours == theirs || theirs == base {
+        ours.cloned()
+    } else if ours == base {
+        theirs.cloned()
+    } else {
+        conflict(base.cloned(), ours.cloned(), theirs.cloned());
+        ours.or(theirs).cloned()
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+};
+use core::fmt::{Display, Write};
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Write `Dag` as a Mermaid flowchart, nodes are labeled by their ids
+    pub fn to_mermaid(&self) -> String
+    where
+        NodeId: Display,
+    {
+        self.to_mermaid_with(|id, _| id.to_string(), |_, _, _| None)
+    }
+
+    /// Write `Dag` as a Mermaid flowchart with custom labels
+    /// # Remarks
+    /// * Nodes are labeled by `node_label(id,data)`
+    /// * Edges are labeled by `edge_label(from,to,data)`, `None` means no label
+    /// * Mermaid ids are `n0`, `n1`... in the order of `NodeId`, labels are quoted
+    ///   and `"` in them is escaped
+    pub fn to_mermaid_with<FN, FE>(&self, mut node_label: FN, mut edge_label: FE) -> String
+    where
+        FN: FnMut(&NodeId, &NodeData) -> String,
+        FE: FnMut(&NodeId, &NodeId, &EdgeData) -> Option<String>,
+    {
+        let mut output = String::from("graph TD\n");
+        let mut index = BTreeMap::new();
+        for (i, (id, data)) in self.nodes.iter().enumerate() {
+            let label = escape(&node_label(id, data));
+            // writing to String never fails
+            let _ = writeln!(output, "    n{}[\"{}\"]", i, label);
+            index.insert(id, i);
+        }
+        for (from, children) in &self.edges {
+            for (to, data) in children {
+                let _ = match edge_label(from, to, data) {
+                    Some(label) => writeln!(
+                        output,
+                        "    n{} -->|\"{}\"| n{}",
+                        index[from],
+                        escape(&label),
+                        index[to]
+                    ),
+                    None => writeln!(output, "    n{} --> n{}", index[from], index[to]),
+                };
+            }
+        }
+        output
+    }
+}
+
+/// Escape `"` by the entity code of Mermaid
+fn escape(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
@@ -0,0 +1,117 @@
+//! Importance scores of nodes, exploiting acyclicity where possible
+//! # Remarks
+//! * All the scores are `f64`, keyed by `NodeId` in a `BTreeMap`
+//! * Edges `from -> to` are seen as "`from` depends on `to`", so importance flows to `to`
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Count the paths which go through every node, as a betweenness over all paths
+    /// # Returns
+    /// Return the count of paths `a -> ... -> node -> ... -> b` for every node, where
+    /// `node` is neither `a` nor `b`
+    /// # Remarks
+    /// * Unlike [`Dag::betweenness_centrality`], all the paths are counted rather than
+    ///   the shortest ones, which is exact in one pass over the topological order
+    /// * The count is the product of the paths ending at the node and the ones starting
+    ///   from it, roots and leaves are always 0
+    pub fn path_betweenness(&self) -> BTreeMap<NodeId, f64> {
+        let order = self.topological_order();
+        let mut ending = BTreeMap::new();
+        for node_id in &order {
+            let count = self
+                .parents(node_id)
+                .map(|parent_id| ending[&parent_id] + 1.0)
+                .sum::<f64>();
+            ending.insert(node_id.clone(), count);
+        }
+        let mut starting = BTreeMap::new();
+        for node_id in order.iter().rev() {
+            let count = self
+                .children(node_id)
+                .map(|(child_id, _)| starting[&child_id] + 1.0)
+                .sum::<f64>();
+            starting.insert(node_id.clone(), count);
+        }
+        ending
+            .into_iter()
+            .zip(starting.into_values())
+            .map(|((node_id, ending), starting)| (node_id, ending * starting))
+            .collect()
+    }
+
+    /// Compute the betweenness centrality of every node by Brandes' algorithm
+    /// # Returns
+    /// Return the sum of `paths through node / paths` of all the pairs `a -> ... -> b`,
+    /// over the shortest paths by the count of edges, `node` is neither `a` nor `b`
+    /// # Remarks
+    /// * It runs a BFS from every node, in O(V·E)
+    /// * Scores are NOT normalized
+    pub fn betweenness_centrality(&self) -> BTreeMap<NodeId, f64> {
+        let (ids, children) = self.dense_children();
+        let len = ids.len();
+        let mut scores = vec![0.0; len];
+        for source in 0..len {
+            // count of shortest paths from `source`, and the distance
+            let mut paths = vec![0.0; len];
+            let mut distances = vec![usize::MAX; len];
+            let mut predecessors = vec![Vec::new(); len];
+            paths[source] = 1.0;
+            distances[source] = 0;
+            // BFS order, which is also the queue
+            let mut visited = vec![source];
+            let mut head = 0;
+            while let Some(&node) = visited.get(head) {
+                head += 1;
+                for &child in &children[node] {
+                    if distances[child] == usize::MAX {
+                        distances[child] = distances[node] + 1;
+                        visited.push(child);
+                    }
+                    if distances[child] == distances[node] + 1 {
+                        paths[child] += paths[node];
+                        predecessors[child].push(node);
+                    }
+                }
+            }
+            let mut dependencies = vec![0.0; len];
+            for &node in visited.iter().rev() {
+                for &predecessor in &predecessors[node] {
+                    dependencies[predecessor] +=
+                        paths[predecessor] / paths[node] * (1.0 + dependencies[node]);
+                }
+                if node != source {
+                    scores[node] += dependencies[node];
+                }
+            }
+        }
+        ids.into_iter().zip(scores).collect()
+    }
+
+    /// Compute a PageRank-style score of every node in one pass
+    /// # Returns
+    /// Return `(1 - damping) / n + damping * sum(score(parent) / children(parent))` of every node
+    /// # Remarks
+    /// * It is exact in one pass over the topological order since there is no cycle
+    /// * The score of leaves is NOT redistributed, so scores don't sum to 1
+    /// * `damping` is usually `0.85`
+    pub fn pagerank(&self, damping: f64) -> BTreeMap<NodeId, f64> {
+        let base = (1.0 - damping) / self.nodes_len() as f64;
+        let mut scores = BTreeMap::new();
+        for node_id in self.topological_order() {
+            let score = self
+                .parents(&node_id)
+                .map(|parent_id| {
+                    let out_degree = self.edges[&parent_id].len() as f64;
+                    scores[&parent_id] / out_degree
+                })
+                .sum::<f64>();
+            scores.insert(node_id, base + damping * score);
+        }
+        scores
+    }
+}
@@ -0,0 +1,219 @@
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::{borrow::Borrow, fmt::Debug, ops::Deref};
+
+use crate::{Dag, DagError};
+
+/// A mutation of [`ObservedDag`], sent to its observers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DagEvent<NodeId> {
+    /// A new node is inserted
+    NodeInserted(NodeId),
+    /// Data of a node is replaced or updated
+    NodeUpdated(NodeId),
+    /// A node is removed, after all of its edges
+    NodeRemoved(NodeId),
+    /// A new edge `from -> to` is inserted
+    EdgeInserted(NodeId, NodeId),
+    /// Data of an edge `from -> to` is replaced or updated
+    EdgeUpdated(NodeId, NodeId),
+    /// An edge `from -> to` is removed
+    EdgeRemoved(NodeId, NodeId),
+    /// All the nodes and edges are removed at once
+    Cleared,
+}
+
+/// An id of an observer registered by [`ObservedDag::observe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObserverId(usize);
+
+type Observer<NodeId, NodeData, EdgeData> =
+    Box<dyn FnMut(&Dag<NodeId, NodeData, EdgeData>, &DagEvent<NodeId>)>;
+
+/// A `Dag` which notifies observers of every mutation
+/// # Remarks
+/// * Created by [`Dag::into_observed`], reads go to `Dag` by `Deref`
+/// * Observers are called after the mutation with the mutated `Dag`,
+///   in the order of registration
+/// * Failed mutations send nothing
+pub struct ObservedDag<NodeId, NodeData, EdgeData> {
+    dag: Dag<NodeId, NodeData, EdgeData>,
+    observers: BTreeMap<ObserverId, Observer<NodeId, NodeData, EdgeData>>,
+    next_observer: usize,
+}
+
+impl<NodeId, NodeData, EdgeData> Debug for ObservedDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Debug,
+    NodeData: Debug,
+    EdgeData: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ObservedDag")
+            .field("dag", &self.dag)
+            .field("observers", &self.observers.keys())
+            .finish()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Turn `Dag` into an [`ObservedDag`] to observe its mutations
+    pub fn into_observed(self) -> ObservedDag<NodeId, NodeData, EdgeData> {
+        ObservedDag {
+            dag: self,
+            observers: BTreeMap::new(),
+            next_observer: 0,
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> ObservedDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the `Dag` back, all the observers are dropped
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Register an observer called on every mutation
+    pub fn observe<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&Dag<NodeId, NodeData, EdgeData>, &DagEvent<NodeId>) + 'static,
+    {
+        let id = ObserverId(self.next_observer);
+        self.next_observer += 1;
+        self.observers.insert(id, Box::new(observer));
+        id
+    }
+
+    /// Unregister an observer
+    /// # Returns
+    /// Return `false` if `id` is not registered
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(&id).is_some()
+    }
+
+    /// Insert a node with data, see [`Dag::insert_node`]
+    pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) -> Option<NodeData> {
+        let old = self.dag.insert_node(node_id.clone(), node_data);
+        self.notify(match old {
+            Some(_) => DagEvent::NodeUpdated(node_id),
+            None => DagEvent::NodeInserted(node_id),
+        });
+        old
+    }
+
+    /// Insert an edge with data, see [`Dag::insert_edge`]
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        let old = self.dag.insert_edge(from.clone(), to.clone(), edge_data)?;
+        self.notify(match old {
+            Some(_) => DagEvent::EdgeUpdated(from, to),
+            None => DagEvent::EdgeInserted(from, to),
+        });
+        Ok(old)
+    }
+
+    /// Remove an edge, see [`Dag::remove_edge`]
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        let old = self.dag.remove_edge(from.clone(), to.clone())?;
+        if old.is_some() {
+            self.notify(DagEvent::EdgeRemoved(from, to));
+        }
+        Ok(old)
+    }
+
+    /// Remove a node and all edges related, see [`Dag::remove_node`]
+    /// # Remarks
+    /// * An `EdgeRemoved` is sent for every removed edge before the `NodeRemoved`
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &mut self,
+        node_id: NodeId,
+    ) -> (Option<NodeData>, Vec<(NodeId, NodeId, EdgeData)>) {
+        let (data, edges) = self.dag.remove_node(node_id.clone());
+        if data.is_some() {
+            for (from, to, _) in &edges {
+                self.notify(DagEvent::EdgeRemoved(from.clone(), to.clone()));
+            }
+            self.notify(DagEvent::NodeRemoved(node_id));
+        }
+        (data, edges)
+    }
+
+    /// Update data of a node in place
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `ObservedDag`
+    pub fn update_node<Q, R>(
+        &mut self,
+        node_id: &Q,
+        f: impl FnOnce(&mut NodeData) -> R,
+    ) -> Option<R>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (node_id, _) = self.dag.nodes.get_key_value(node_id)?;
+        let node_id = node_id.clone();
+        let result = f(self.dag.nodes.get_mut::<NodeId>(&node_id)?);
+        self.notify(DagEvent::NodeUpdated(node_id));
+        Some(result)
+    }
+
+    /// Update data of an edge `from -> to` in place
+    /// # Returns
+    /// Return `None` if the edge is not found in `ObservedDag`
+    pub fn update_edge<Q, R>(
+        &mut self,
+        from: &Q,
+        to: &Q,
+        f: impl FnOnce(&mut EdgeData) -> R,
+    ) -> Option<R>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (from, children) = self.dag.edges.get_key_value(from)?;
+        let (to, _) = children.get_key_value(to)?;
+        let (from, to) = (from.clone(), to.clone());
+        let data = self
+            .dag
+            .edges
+            .get_mut::<NodeId>(&from)?
+            .get_mut::<NodeId>(&to)?;
+        let result = f(data);
+        self.notify(DagEvent::EdgeUpdated(from, to));
+        Some(result)
+    }
+
+    /// Remove all nodes and edges
+    pub fn clear(&mut self) {
+        self.dag.clear();
+        self.notify(DagEvent::Cleared);
+    }
+
+    fn notify(&mut self, event: DagEvent<NodeId>) {
+        for observer in self.observers.values_mut() {
+            observer(&self.dag, &event);
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for ObservedDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
@@ -0,0 +1,448 @@
+use alloc::{
+    borrow::ToOwned,
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+use core::borrow::Borrow;
+
+use crate::{Dag, NodeNotFound};
+
+const FORWARD: u8 = 1;
+const BACKWARD: u8 = 2;
+// small graphs are NOT compacted, see `Order::push`
+const COMPACT_SLACK: usize = 64;
+
+/// A map keyed by node ids, so the search and order code is shared by the storages of DAGs
+/// # Remarks
+/// * Implemented by `BTreeMap` for [`Dag`], `HashMap` for `HashDag`
+///   and `im::OrdMap` for `PersistentDag`
+pub(crate) trait IdMap<NodeId, V> {
+    fn get(&self, key: &NodeId) -> Option<&V>;
+    fn insert(&mut self, key: NodeId, value: V) -> Option<V>;
+    fn remove(&mut self, key: &NodeId) -> Option<V>;
+    fn len(&self) -> usize;
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a NodeId, &'a V)>
+    where
+        NodeId: 'a,
+        V: 'a;
+}
+
+impl<NodeId, V> IdMap<NodeId, V> for BTreeMap<NodeId, V>
+where
+    NodeId: Ord,
+{
+    fn get(&self, key: &NodeId) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: NodeId, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &NodeId) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a NodeId, &'a V)>
+    where
+        NodeId: 'a,
+        V: 'a,
+    {
+        BTreeMap::iter(self)
+    }
+}
+
+/// Find a path `from -> ... -> to` by DFS along `children`
+/// # Returns
+/// * Return `None` if there is no such path, `Some(vec![from])` if `from == to`
+/// # Remarks
+/// * `previous` is an empty map, which records the node each visited node was reached from
+pub(crate) fn find_path<NodeId, M, F, I>(
+    from: NodeId,
+    to: NodeId,
+    mut previous: M,
+    mut children: F,
+) -> Option<Vec<NodeId>>
+where
+    NodeId: Clone + Eq,
+    M: IdMap<NodeId, NodeId>,
+    F: FnMut(&NodeId) -> I,
+    I: IntoIterator<Item = NodeId>,
+{
+    let mut stack = vec![from.clone()];
+    while let Some(top) = stack.pop() {
+        if top == to {
+            let mut path = vec![to];
+            while let Some(prev) = previous.get(path.last()?) {
+                path.push(prev.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for child_id in children(&top) {
+            if child_id != from && previous.get(&child_id).is_none() {
+                previous.insert(child_id.clone(), top.clone());
+                stack.push(child_id);
+            }
+        }
+    }
+    None
+}
+
+/// Buffers reused by [`Order::reorder`], so inserting edges doesn't allocate in the long run
+#[derive(Debug)]
+struct Scratch<NodeId> {
+    // `FORWARD` and `BACKWARD` marks of visited nodes by `position - lower`, all zero when idle
+    marks: Vec<u8>,
+    forward: Vec<(usize, NodeId)>,
+    backward: Vec<(usize, NodeId)>,
+    forward_stack: Vec<NodeId>,
+    backward_stack: Vec<NodeId>,
+    positions: Vec<usize>,
+}
+
+impl<NodeId> Default for Scratch<NodeId> {
+    fn default() -> Self {
+        Scratch {
+            marks: Vec::new(),
+            forward: Vec::new(),
+            backward: Vec::new(),
+            forward_stack: Vec::new(),
+            backward_stack: Vec::new(),
+            positions: Vec::new(),
+        }
+    }
+}
+
+impl<NodeId> Clone for Scratch<NodeId> {
+    /// Buffers are NOT cloned, they are empty when idle
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// A topological order of the nodes of a DAG, kept incrementally by Pearce-Kelly
+/// # Remarks
+/// * `M` maps node ids to their positions, every node of the DAG has one
+/// * Positions are handed out by [`Order::push`] and only grow,
+///   freed ones are reclaimed by renumbering
+#[derive(Debug, Clone)]
+pub(crate) struct Order<NodeId, M> {
+    pub(crate) positions: M,
+    pub(crate) next: usize,
+    // buffers of the cycle check in `reorder`
+    scratch: Scratch<NodeId>,
+}
+
+impl<NodeId, M> Default for Order<NodeId, M>
+where
+    M: Default,
+{
+    fn default() -> Self {
+        Order {
+            positions: M::default(),
+            next: 0,
+            scratch: Scratch::default(),
+        }
+    }
+}
+
+impl<NodeId, M> Order<NodeId, M>
+where
+    NodeId: Clone + Eq,
+    M: IdMap<NodeId, usize>,
+{
+    /// Create an order from node ids which are already in a topological order
+    pub(crate) fn from_sorted<I>(node_ids: I) -> Self
+    where
+        I: IntoIterator<Item = NodeId>,
+        M: Default,
+    {
+        let mut order = Self::default();
+        for node_id in node_ids {
+            order.positions.insert(node_id, order.next);
+            order.next += 1;
+        }
+        order
+    }
+
+    /// Create an order from the positions of another one, `next` is greater than all of them
+    pub(crate) fn from_positions(positions: M, next: usize) -> Self {
+        Order {
+            positions,
+            next,
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// Get the position of `node_id`
+    pub(crate) fn position(&self, node_id: &NodeId) -> usize {
+        *self
+            .positions
+            .get(node_id)
+            .unwrap_or_else(|| unreachable!("every node has an order"))
+    }
+
+    /// Put a new node at the end, it has no edges so it can be the last one
+    /// # Remarks
+    /// * The buffers of [`Order::reorder`] are sized by the gap between two positions,
+    ///   so positions are renumbered to `0..len` when removed nodes left too many gaps.
+    ///   It keeps both bounded by the count of nodes, in amortized O(log V) per node
+    pub(crate) fn push(&mut self, node_id: NodeId) {
+        self.positions.insert(node_id, self.next);
+        self.next += 1;
+        if self.next < 2 * self.positions.len() + COMPACT_SLACK {
+            return;
+        }
+        let mut node_ids = self
+            .positions
+            .iter()
+            .map(|(id, position)| (*position, id.clone()))
+            .collect::<Vec<_>>();
+        node_ids.sort_unstable_by_key(|(position, _)| *position);
+        self.next = node_ids.len();
+        for (position, (_, node_id)) in node_ids.into_iter().enumerate() {
+            self.positions.insert(node_id, position);
+        }
+        // marks are all zero when idle, so the tail can be dropped
+        self.scratch.marks.truncate(self.next);
+        self.scratch.marks.shrink_to_fit();
+    }
+
+    /// Remove a node, its position is freed
+    pub(crate) fn remove(&mut self, node_id: &NodeId) {
+        self.positions.remove(node_id);
+    }
+
+    /// Give the position of `old_id` to `new_id`
+    pub(crate) fn rename(&mut self, old_id: &NodeId, new_id: NodeId) {
+        let position = self
+            .positions
+            .remove(old_id)
+            .unwrap_or_else(|| unreachable!("every node has an order"));
+        self.positions.insert(new_id, position);
+    }
+
+    /// Keep the order valid for a new edge `from -> to` by Pearce-Kelly
+    /// # Returns
+    /// * Return `false` when the edge makes a cycle, the order is NOT modified then
+    /// # Remarks
+    /// * Only nodes between `to` and `from` in the order are visited,
+    ///   so it is O(1) when `from` is already before `to`
+    /// * A cycle means a path `to -> ... -> from`, it is searched from both ends at once
+    ///   and stops as soon as the two searches meet
+    /// * Buffers of the search are kept and reused by later calls
+    pub(crate) fn reorder<'a, C, P, IC, IP>(
+        &mut self,
+        from: &NodeId,
+        to: &NodeId,
+        children: C,
+        parents: P,
+    ) -> bool
+    where
+        NodeId: 'a,
+        C: Fn(&NodeId) -> IC,
+        P: Fn(&NodeId) -> IP,
+        IC: Iterator<Item = &'a NodeId>,
+        IP: Iterator<Item = &'a NodeId>,
+    {
+        let lower = self.position(to);
+        let upper = self.position(from);
+        if upper < lower {
+            return true;
+        }
+        if from == to {
+            return false;
+        }
+        let mut scratch = core::mem::take(&mut self.scratch);
+        let acyclic = self.search(&mut scratch, from, to, lower, upper, children, parents);
+        for (position, _) in scratch.forward.iter().chain(scratch.backward.iter()) {
+            scratch.marks[position - lower] = 0;
+        }
+        scratch.forward_stack.clear();
+        scratch.backward_stack.clear();
+        if acyclic {
+            // reuse the positions of both sets, putting `backward` before `forward`
+            scratch
+                .backward
+                .sort_unstable_by_key(|(position, _)| *position);
+            scratch
+                .forward
+                .sort_unstable_by_key(|(position, _)| *position);
+            scratch.positions.extend(
+                scratch
+                    .backward
+                    .iter()
+                    .chain(scratch.forward.iter())
+                    .map(|(position, _)| *position),
+            );
+            scratch.positions.sort_unstable();
+            let nodes = scratch.backward.drain(..).chain(scratch.forward.drain(..));
+            for ((_, node_id), position) in nodes.zip(scratch.positions.drain(..)) {
+                self.positions.insert(node_id, position);
+            }
+        }
+        scratch.forward.clear();
+        scratch.backward.clear();
+        self.scratch = scratch;
+        acyclic
+    }
+
+    /// Collect the nodes to move for a new edge `from -> to` into `scratch`
+    /// # Returns
+    /// * Return `false` when there is a path `to -> ... -> from`
+    #[allow(clippy::too_many_arguments)]
+    fn search<'a, C, P, IC, IP>(
+        &self,
+        scratch: &mut Scratch<NodeId>,
+        from: &NodeId,
+        to: &NodeId,
+        lower: usize,
+        upper: usize,
+        children: C,
+        parents: P,
+    ) -> bool
+    where
+        NodeId: 'a,
+        C: Fn(&NodeId) -> IC,
+        P: Fn(&NodeId) -> IP,
+        IC: Iterator<Item = &'a NodeId>,
+        IP: Iterator<Item = &'a NodeId>,
+    {
+        // forward: nodes reachable from `to` which are NOT after `from`
+        // backward: nodes reaching `from` which are NOT before `to`
+        // a node in both of them is on a path `to -> ... -> from`
+        // all of them are between `lower` and `upper`, so they are marked by `position - lower`
+        if scratch.marks.len() <= upper - lower {
+            scratch.marks.resize(upper - lower + 1, 0);
+        }
+        scratch.forward_stack.push(to.clone());
+        scratch.backward_stack.push(from.clone());
+        while !scratch.forward_stack.is_empty() || !scratch.backward_stack.is_empty() {
+            if let Some(top) = scratch.forward_stack.pop() {
+                let position = self.position(&top);
+                let mark = &mut scratch.marks[position - lower];
+                if *mark & BACKWARD != 0 || top == *from {
+                    return false;
+                }
+                if *mark & FORWARD == 0 {
+                    *mark |= FORWARD;
+                    for child_id in children(&top) {
+                        let position = self.position(child_id);
+                        if position <= upper && scratch.marks[position - lower] & FORWARD == 0 {
+                            scratch.forward_stack.push(child_id.clone());
+                        }
+                    }
+                    scratch.forward.push((position, top));
+                }
+            }
+            if let Some(top) = scratch.backward_stack.pop() {
+                let position = self.position(&top);
+                let mark = &mut scratch.marks[position - lower];
+                if *mark & FORWARD != 0 || top == *to {
+                    return false;
+                }
+                if *mark & BACKWARD == 0 {
+                    *mark |= BACKWARD;
+                    for parent_id in parents(&top) {
+                        let position = self.position(parent_id);
+                        if position >= lower && scratch.marks[position - lower] & BACKWARD == 0 {
+                            scratch.backward_stack.push(parent_id.clone());
+                        }
+                    }
+                    scratch.backward.push((position, top));
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the position of `node_id` in the topological order kept by `Dag`
+    pub(crate) fn order_of(&self, node_id: &NodeId) -> usize {
+        self.order.position(node_id)
+    }
+
+    /// Keep the topological order valid for a new edge `from -> to`, see [`Order::reorder`]
+    /// # Returns
+    /// * Return `false` when the edge makes a cycle, the order is NOT modified then
+    pub(crate) fn reorder(&mut self, from: &NodeId, to: &NodeId) -> bool {
+        let (edges, back_edges) = (&self.edges, &self.back_edges);
+        self.order
+            .reorder(from, to, |id| edges[id].keys(), |id| back_edges[id].iter())
+    }
+
+    /// Check if inserting the edge `from -> to` would make a cycle, without modifying `Dag`
+    /// # Returns
+    /// * Return `Ok(false)` when the edge is already in `Dag`, inserting it again only
+    ///   replaces its data
+    /// # Remarks
+    /// * It is O(1) when `from` is already before `to` in the topological order
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
+    pub fn would_create_cycle<Q>(&self, from: &Q, to: &Q) -> Result<bool, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        let (from, _) = self
+            .nodes
+            .get_key_value(from)
+            .ok_or_else(|| NodeNotFound(from.to_owned()))?;
+        let (to, _) = self
+            .nodes
+            .get_key_value(to)
+            .ok_or_else(|| NodeNotFound(to.to_owned()))?;
+        let upper = self.order_of(from);
+        if self.contains_edge::<NodeId>(from, to) || upper < self.order_of(to) {
+            return Ok(false);
+        }
+        // nodes after `from` in the order can't reach it
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![to.clone()];
+        while let Some(top) = stack.pop() {
+            if top == *from {
+                return Ok(true);
+            }
+            if !visited.contains(&top) {
+                stack.extend(
+                    self.children::<NodeId>(&top)
+                        .map(|(id, _)| id)
+                        .filter(|id| self.order_of(id) <= upper && !visited.contains(id)),
+                );
+                visited.insert(top);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Recompute the topological order after edges were inserted without [`Dag::reorder`]
+    /// # Returns
+    /// * Return `false` when `Dag` has a cycle
+    pub(crate) fn rebuild_order(&mut self) -> bool {
+        let order = self.topological_order();
+        if order.len() != self.nodes_len() {
+            return false;
+        }
+        self.order = Order::from_sorted(order);
+        true
+    }
+
+    /// Find a path `from -> ... -> to`
+    /// # Returns
+    /// * Return `None` if there is no such path, `Some(vec![from])` if `from == to`
+    pub(crate) fn find_path(&self, from: NodeId, to: NodeId) -> Option<Vec<NodeId>> {
+        find_path(from, to, BTreeMap::new(), |id| {
+            self.children::<NodeId>(id).map(|(id, _)| id)
+        })
+    }
+}
@@ -0,0 +1,40 @@
+use rayon::prelude::*;
+
+use crate::Dag;
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Send + Sync,
+{
+    /// Get a parallel iterator of all the nodes in `Dag`
+    pub fn par_nodes(&self) -> impl ParallelIterator<Item = (NodeId, &'_ NodeData)>
+    where
+        NodeData: Sync,
+    {
+        self.nodes.par_iter().map(|(id, data)| (id.clone(), data))
+    }
+
+    /// Get a parallel iterator of all the nodes in `Dag`
+    /// # Remarks
+    /// * Use it to update expensive node data on all cores
+    pub fn par_nodes_mut(&mut self) -> impl ParallelIterator<Item = (NodeId, &'_ mut NodeData)>
+    where
+        NodeData: Send,
+    {
+        self.nodes
+            .par_iter_mut()
+            .map(|(id, data)| (id.clone(), data))
+    }
+
+    /// Get a parallel iterator of all the edges in `Dag`
+    pub fn par_edges(&self) -> impl ParallelIterator<Item = (NodeId, NodeId, &'_ EdgeData)>
+    where
+        EdgeData: Sync,
+    {
+        self.edges.par_iter().flat_map_iter(|(from, children)| {
+            children
+                .iter()
+                .map(move |(to, data)| (from.clone(), to.clone(), data))
+        })
+    }
+}
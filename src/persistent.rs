@@ -0,0 +1,419 @@
+use std::{borrow::Borrow, collections::BTreeMap, fmt::Debug};
+
+use im::{OrdMap, OrdSet};
+
+use crate::{
+    order::{find_path, IdMap, Order},
+    Dag, DagError, NodeNotFound,
+};
+
+impl<NodeId, V> IdMap<NodeId, V> for OrdMap<NodeId, V>
+where
+    NodeId: Clone + Ord,
+    V: Clone,
+{
+    fn get(&self, key: &NodeId) -> Option<&V> {
+        OrdMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: NodeId, value: V) -> Option<V> {
+        OrdMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &NodeId) -> Option<V> {
+        OrdMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        OrdMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a NodeId, &'a V)>
+    where
+        NodeId: 'a,
+        V: 'a,
+    {
+        OrdMap::iter(self)
+    }
+}
+
+/// DAG whose mutations return a new version, sharing structure with the old one
+/// # Remarks
+/// * Enabled by feature `persistent`, it is stored by persistent maps of `im`
+/// * Cloning is O(1), a mutation copies O(log n) nodes of the maps, so keeping many
+///   versions costs much less than cloning `Dag`
+/// * The old version is unchanged, so removed data can still be read from it
+/// * `NodeId`, `NodeData` and `EdgeData` must be `Clone` to be shared
+/// * Convert it into `Dag` to use the algorithms
+#[derive(Clone)]
+pub struct PersistentDag<NodeId, NodeData, EdgeData> {
+    nodes: OrdMap<NodeId, NodeData>,
+    edges: OrdMap<NodeId, OrdMap<NodeId, EdgeData>>,
+    back_edges: OrdMap<NodeId, OrdSet<NodeId>>,
+    // a topological order of nodes, kept by `insert_edge` like `Dag`
+    order: Order<NodeId, OrdMap<NodeId, usize>>,
+    edges_len: usize,
+}
+
+impl<NodeId, NodeData, EdgeData> Debug for PersistentDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Ord + Debug,
+    NodeData: Debug,
+    EdgeData: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PersistentDag")
+            .field("nodes", &self.nodes)
+            .field("edges", &self.edges)
+            .field("back_edges", &self.back_edges)
+            .field("edges_len", &self.edges_len)
+            .finish()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Default for PersistentDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> PartialEq for PersistentDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone + PartialEq,
+    EdgeData: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // back_edges are derived from edges
+        self.nodes == other.nodes && self.edges == other.edges
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Eq for PersistentDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone + Eq,
+    EdgeData: Clone + Eq,
+{
+}
+
+impl<NodeId, NodeData, EdgeData> PersistentDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    /// Create an empty DAG
+    pub fn new() -> Self {
+        PersistentDag {
+            nodes: OrdMap::new(),
+            edges: OrdMap::new(),
+            back_edges: OrdMap::new(),
+            order: Order::default(),
+            edges_len: 0,
+        }
+    }
+
+    /// Check if a `node_id` is contained in `PersistentDag`
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.nodes.contains_key(node_id)
+    }
+
+    /// Check a node is root
+    pub fn is_root<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.parents(node_id).next().is_none()
+    }
+
+    /// Check if an `edge` is contained in `PersistentDag`
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.edges
+            .get(from)
+            .is_some_and(|children| children.contains_key(to))
+    }
+
+    /// Insert a node with data
+    /// # Returns
+    /// Return the new version, the data is replaced when `node_id` is already in `PersistentDag`
+    pub fn insert_node(&self, node_id: NodeId, node_data: NodeData) -> Self {
+        let mut dag = self.clone();
+        if !dag.nodes.contains_key(&node_id) {
+            dag.edges.insert(node_id.clone(), OrdMap::new());
+            dag.back_edges.insert(node_id.clone(), OrdSet::new());
+            dag.order.push(node_id.clone());
+        }
+        dag.nodes.insert(node_id, node_data);
+        dag
+    }
+
+    /// Insert an edge with data
+    /// # Returns
+    /// Return `Ok(dag)` with the new version, the data is replaced when there is a same edge
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `PersistentDag`
+    /// * `Err(HasCycle(from,to,data,path))` when a cycle is detected
+    pub fn insert_edge(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<Self, DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&from) {
+            return Err(DagError::NodeNotFound(from));
+        }
+        if !self.nodes.contains_key(&to) {
+            return Err(DagError::NodeNotFound(to));
+        }
+        let replaced = self.contains_edge(&from, &to);
+        let mut dag = self.clone();
+        // only the nodes between `to` and `from` in the order are visited, like `Dag`
+        let (edges, back_edges) = (&dag.edges, &dag.back_edges);
+        if !replaced
+            && !dag.order.reorder(
+                &from,
+                &to,
+                |id| edges[id].keys(),
+                |id| back_edges[id].iter(),
+            )
+        {
+            let path = find_path(to.clone(), from.clone(), BTreeMap::new(), |id| {
+                self.children(id).map(|(id, _)| id)
+            })
+            .unwrap_or_else(|| unreachable!("reorder found a path"));
+            return Err(DagError::HasCycle(from, to, edge_data, path));
+        }
+        dag.edges
+            .get_mut(&from)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .insert(to.clone(), edge_data);
+        dag.back_edges
+            .get_mut(&to)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .insert(from);
+        if !replaced {
+            dag.edges_len += 1;
+        }
+        Ok(dag)
+    }
+
+    /// Remove an edge
+    /// # Returns
+    /// Return `Ok(dag)` with the new version, which is the same when there is no such edge
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `PersistentDag`
+    pub fn remove_edge(&self, from: NodeId, to: NodeId) -> Result<Self, NodeNotFound<NodeId>> {
+        if !self.nodes.contains_key(&from) {
+            return Err(NodeNotFound(from));
+        }
+        if !self.nodes.contains_key(&to) {
+            return Err(NodeNotFound(to));
+        }
+        let mut dag = self.clone();
+        if dag
+            .edges
+            .get_mut(&from)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .remove(&to)
+            .is_some()
+        {
+            dag.edges_len -= 1;
+        }
+        dag.back_edges
+            .get_mut(&to)
+            .unwrap_or_else(|| unreachable!("proved by contains_key"))
+            .remove(&from);
+        Ok(dag)
+    }
+
+    /// Remove a node and all edges related
+    /// # Returns
+    /// Return the new version, which is the same when `node_id` is NOT found
+    pub fn remove_node(&self, node_id: NodeId) -> Self {
+        let mut dag = self.clone();
+        if dag.nodes.remove(&node_id).is_none() {
+            return dag;
+        }
+        dag.order.remove(&node_id);
+        let children = dag
+            .edges
+            .remove(&node_id)
+            .unwrap_or_else(|| unreachable!("PersistentDag ensures this node exists in edges"));
+        for child_id in children.keys() {
+            if let Some(parents) = dag.back_edges.get_mut(child_id) {
+                parents.remove(&node_id);
+            }
+        }
+        let parents = dag.back_edges.remove(&node_id).unwrap_or_else(|| {
+            unreachable!("PersistentDag ensures this node exists in back_edges")
+        });
+        for parent_id in parents.iter() {
+            if let Some(children) = dag.edges.get_mut(parent_id) {
+                children.remove(&node_id);
+            }
+        }
+        dag.edges_len -= children.len() + parents.len();
+        dag
+    }
+
+    /// Get an iterator of all the children of given `node_id`
+    pub fn children<Q>(&self, node_id: &Q) -> impl Iterator<Item = (NodeId, &'_ EdgeData)>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.edges
+            .get(node_id)
+            .into_iter()
+            .flat_map(|map| map.iter().map(|(id, data)| (id.clone(), data)))
+    }
+
+    /// Get an iterator of all the parents of given `node_id`
+    pub fn parents<Q>(&self, node_id: &Q) -> impl Iterator<Item = NodeId> + '_
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.back_edges
+            .get(node_id)
+            .into_iter()
+            .flat_map(|set| set.iter().cloned())
+    }
+
+    /// Get the count of nodes
+    pub fn nodes_len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get the count of edges
+    pub fn edges_len(&self) -> usize {
+        self.edges_len
+    }
+
+    /// Check if `PersistentDag` has no node
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get all the nodes in `PersistentDag`
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.nodes.iter().map(|(id, data)| (id.clone(), data))
+    }
+
+    /// Get all the edges in `PersistentDag`
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &'_ EdgeData)> {
+        self.edges.iter().flat_map(|(from, children)| {
+            children
+                .iter()
+                .map(move |(to, data)| (from.clone(), to.clone(), data))
+        })
+    }
+
+    /// Get all the leaves in `PersistentDag`
+    pub fn leaves(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.nodes()
+            .filter(|(id, _)| self.children(id).next().is_none())
+    }
+
+    /// Get all the roots in `PersistentDag`
+    pub fn roots(&self) -> impl Iterator<Item = (NodeId, &'_ NodeData)> {
+        self.nodes().filter(|(id, _)| self.is_root(id))
+    }
+
+    /// Get data from node
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `PersistentDag`
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<&NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.nodes.get(node_id)
+    }
+
+    /// Get data from edge
+    /// # Returns
+    /// Return `Ok(Some(data))` if success
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `PersistentDag`
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Result<Option<&EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+    {
+        if !self.nodes.contains_key(from) {
+            return Err(NodeNotFound(from.to_owned()));
+        }
+        if !self.nodes.contains_key(to) {
+            return Err(NodeNotFound(to.to_owned()));
+        }
+        Ok(self.edges.get(from).and_then(|children| children.get(to)))
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> From<PersistentDag<NodeId, NodeData, EdgeData>>
+    for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    fn from(dag: PersistentDag<NodeId, NodeData, EdgeData>) -> Self {
+        Dag {
+            nodes: dag.nodes.into_iter().collect(),
+            edges: dag
+                .edges
+                .into_iter()
+                .map(|(id, children)| (id, children.into_iter().collect()))
+                .collect(),
+            back_edges: dag
+                .back_edges
+                .into_iter()
+                .map(|(id, parents)| (id, parents.into_iter().collect()))
+                .collect(),
+            order: Order::from_positions(dag.order.positions.into_iter().collect(), dag.order.next),
+            edges_len: dag.edges_len,
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> From<Dag<NodeId, NodeData, EdgeData>>
+    for PersistentDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    fn from(dag: Dag<NodeId, NodeData, EdgeData>) -> Self {
+        PersistentDag {
+            nodes: dag.nodes.into_iter().collect(),
+            edges: dag
+                .edges
+                .into_iter()
+                .map(|(id, children)| (id, OrdMap::from_iter(children)))
+                .collect(),
+            back_edges: dag
+                .back_edges
+                .into_iter()
+                .map(|(id, parents)| (id, OrdSet::from_iter(parents)))
+                .collect(),
+            order: Order::from_positions(dag.order.positions.into_iter().collect(), dag.order.next),
+            edges_len: dag.edges_len,
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::borrow::Borrow;
+
+use crate::Dag;
+
+/// Cached results of [`Dag::propagate`], which are recomputed incrementally
+/// # Remarks
+/// * [`Propagation::recompute`] evaluates only the dirty nodes, the nodes without results
+///   and their descendants, other results are kept
+/// * Mark a node dirty after changing its data or its parents,
+///   a new node is always evaluated and results of removed nodes are dropped
+#[derive(Debug, Clone)]
+pub struct Propagation<NodeId, R> {
+    results: BTreeMap<NodeId, R>,
+    dirty: BTreeSet<NodeId>,
+}
+
+impl<NodeId, R> Default for Propagation<NodeId, R> {
+    fn default() -> Self {
+        Propagation {
+            results: BTreeMap::new(),
+            dirty: BTreeSet::new(),
+        }
+    }
+}
+
+impl<NodeId, R> Propagation<NodeId, R>
+where
+    NodeId: Clone + Ord,
+{
+    /// Create an empty cache, all the nodes are evaluated by the first recomputation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a node to be evaluated again with its descendants
+    pub fn mark_dirty(&mut self, node_id: NodeId) {
+        self.dirty.insert(node_id);
+    }
+
+    /// Check if a node is marked dirty
+    pub fn is_dirty<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dirty.contains(node_id)
+    }
+
+    /// Get the cached result of a node
+    /// # Returns
+    /// Return `None` if the node is never evaluated
+    pub fn get<Q>(&self, node_id: &Q) -> Option<&R>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.results.get(node_id)
+    }
+
+    /// Get all the cached results
+    pub fn results(&self) -> &BTreeMap<NodeId, R> {
+        &self.results
+    }
+
+    /// Get the cached results back
+    pub fn into_results(self) -> BTreeMap<NodeId, R> {
+        self.results
+    }
+
+    /// Evaluate the dirty nodes and their descendants again by `f`, see [`Dag::propagate`]
+    /// # Returns
+    /// Return the count of evaluated nodes
+    pub fn recompute<NodeData, EdgeData, F>(
+        &mut self,
+        dag: &Dag<NodeId, NodeData, EdgeData>,
+        mut f: F,
+    ) -> usize
+    where
+        F: FnMut(&NodeId, &NodeData, &[(NodeId, &EdgeData, &R)]) -> R,
+    {
+        self.results.retain(|id, _| dag.nodes.contains_key(id));
+        let mut stack = dag
+            .nodes
+            .keys()
+            .filter(|id| self.dirty.contains(*id) || !self.results.contains_key(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.dirty.clear();
+        let mut affected = BTreeSet::new();
+        while let Some(top) = stack.pop() {
+            if !affected.contains(&top) {
+                stack.extend(dag.children(&top).map(|(id, _)| id));
+                affected.insert(top);
+            }
+        }
+        let mut affected = affected.into_iter().collect::<Vec<_>>();
+        affected.sort_by_key(|id| dag.order_of(id));
+        for node_id in &affected {
+            let result = dag.evaluate(node_id, &self.results, &mut f);
+            self.results.insert(node_id.clone(), result);
+        }
+        affected.len()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Evaluate every node in a topological order by `f(id,data,parent_results)`
+    /// # Returns
+    /// Return the results of all the nodes
+    /// # Remarks
+    /// * `parent_results` are `(parent_id, edge_data, result)` of all the parents
+    ///   in the order of `NodeId`, they are always evaluated before the node
+    /// * Roots get empty `parent_results`
+    pub fn propagate<R, F>(&self, mut f: F) -> BTreeMap<NodeId, R>
+    where
+        F: FnMut(&NodeId, &NodeData, &[(NodeId, &EdgeData, &R)]) -> R,
+    {
+        let mut results = BTreeMap::new();
+        for node_id in self.topological_order() {
+            let result = self.evaluate(&node_id, &results, &mut f);
+            results.insert(node_id, result);
+        }
+        results
+    }
+
+    /// Evaluate a node whose parents are all evaluated in `results`
+    pub(crate) fn evaluate<R, F>(
+        &self,
+        node_id: &NodeId,
+        results: &BTreeMap<NodeId, R>,
+        f: &mut F,
+    ) -> R
+    where
+        F: FnMut(&NodeId, &NodeData, &[(NodeId, &EdgeData, &R)]) -> R,
+    {
+        let node_data = self
+            .nodes
+            .get(node_id)
+            .unwrap_or_else(|| unreachable!("ids are from Dag"));
+        let parent_results = self
+            .parent_edges(node_id)
+            .map(|(parent_id, edge_data)| {
+                let result = results
+                    .get(&parent_id)
+                    .unwrap_or_else(|| unreachable!("parents are evaluated first"));
+                (parent_id, edge_data, result)
+            })
+            .collect::<Vec<_>>();
+        f(node_id, node_data, &parent_results)
+    }
+}
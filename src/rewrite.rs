@@ -0,0 +1,196 @@
+use alloc::vec::Vec;
+
+use crate::{Dag, DagError};
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Insert an edge which can't make a cycle, merging data with the existing one
+    fn rewire_edge<FE>(&mut self, from: NodeId, to: NodeId, theirs: EdgeData, edge_merge: &mut FE)
+    where
+        FE: FnMut(NodeId, NodeId, EdgeData, EdgeData) -> EdgeData,
+    {
+        let data = match self.remove_edge(from.clone(), to.clone()).ok().flatten() {
+            Some(ours) => edge_merge(from.clone(), to.clone(), ours, theirs),
+            None => theirs,
+        };
+        if self.insert_edge(from, to, data).is_err() {
+            unreachable!("rewired edges were checked for cycles")
+        }
+    }
+
+    /// Contract the edge `from -> to`, merging `to` into `from`
+    /// # Remarks
+    /// * Edges of `to` are moved to `from`
+    /// * `node_merge(ours, theirs)` combines data of `from` and `to`
+    /// * `edge_merge(from, to, ours, theirs)` resolves an edge of `to` which is
+    ///   already an edge of `from` after moving
+    /// # Returns
+    /// * Return `Ok(Some(data))` of the contracted edge when success
+    /// * Return `Ok(None)` when there is no such edge, `Dag` is NOT modified
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
+    /// * `Err(HasCycle(from,to,(),path))` when there is another path `from -> ... -> to`,
+    ///   given as `path`, which makes a cycle after contracting. `Dag` is NOT modified,
+    ///   the data of `from -> to` is kept in `Dag`
+    pub fn contract_edge<FN, FE>(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        node_merge: FN,
+        mut edge_merge: FE,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, ()>>
+    where
+        FN: FnOnce(NodeData, NodeData) -> NodeData,
+        FE: FnMut(NodeId, NodeId, EdgeData, EdgeData) -> EdgeData,
+    {
+        if self.get_edge(&from, &to)?.is_none() {
+            return Ok(None);
+        }
+        let data = self
+            .remove_edge(from.clone(), to.clone())
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| unreachable!("proved by get_edge"));
+        if let Some(path) = self.find_path(from.clone(), to.clone()) {
+            // put the edge back, so a rejected contraction keeps `Dag`
+            if self.insert_edge(from.clone(), to.clone(), data).is_err() {
+                unreachable!("the edge was in Dag before")
+            }
+            return Err(DagError::HasCycle(from, to, (), path));
+        }
+
+        let (theirs, edges) = self.remove_node(to.clone());
+        let theirs = theirs.unwrap_or_else(|| unreachable!("proved by remove_edge"));
+        for (edge_from, edge_to, edge_data) in edges {
+            let (edge_from, edge_to) = if edge_from == to {
+                (from.clone(), edge_to)
+            } else {
+                (edge_from, from.clone())
+            };
+            self.rewire_edge(edge_from, edge_to, edge_data, &mut edge_merge);
+        }
+        let ours = self
+            .nodes
+            .remove(&from)
+            .unwrap_or_else(|| unreachable!("proved by remove_edge"));
+        self.nodes.insert(from, node_merge(ours, theirs));
+        Ok(Some(data))
+    }
+
+    /// Split `node_id` into itself and a new node `new_id`, connected by `node_id -> new_id`
+    /// # Remarks
+    /// * `partition(from, to, data)` is called for every edge of `node_id`,
+    ///   return `true` to move the edge to `new_id`
+    /// * Splitting can't make a cycle: moved edges still go from ancestors to descendants
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `node_id` is NOT found in `Dag`
+    /// * `Err(DuplicateNode(id))` when `new_id` is already in `Dag`
+    pub fn split_node<F>(
+        &mut self,
+        node_id: NodeId,
+        new_id: NodeId,
+        new_data: NodeData,
+        edge_data: EdgeData,
+        mut partition: F,
+    ) -> Result<(), DagError<NodeId, EdgeData>>
+    where
+        F: FnMut(NodeId, NodeId, &EdgeData) -> bool,
+    {
+        if !self.nodes.contains_key(&node_id) {
+            return Err(DagError::NodeNotFound(node_id));
+        }
+        if self.nodes.contains_key(&new_id) {
+            return Err(DagError::DuplicateNode(new_id));
+        }
+        let moved = self
+            .children(&node_id)
+            .map(|(to, data)| (node_id.clone(), to, data))
+            .chain(
+                self.parent_edges(&node_id)
+                    .map(|(from, data)| (from, node_id.clone(), data)),
+            )
+            .filter(|(from, to, data)| partition(from.clone(), to.clone(), data))
+            .map(|(from, to, _)| (from, to))
+            .collect::<Vec<_>>();
+
+        self.insert_node(new_id.clone(), new_data);
+        for (from, to) in moved {
+            let data = self
+                .remove_edge(from.clone(), to.clone())
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| unreachable!("the edge was found above"));
+            let (from, to) = if from == node_id {
+                (new_id.clone(), to)
+            } else {
+                (from, new_id.clone())
+            };
+            if self.insert_edge(from, to, data).is_err() {
+                unreachable!("moved edges can't make a cycle")
+            }
+        }
+        if self.insert_edge(node_id, new_id, edge_data).is_err() {
+            unreachable!("new_id has no path to node_id")
+        }
+        Ok(())
+    }
+
+    /// Change the id of a node from `old_id` to `new_id`, keeping its data and edges
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `old_id` is NOT found in `Dag`
+    /// * `Err(DuplicateNode(id))` when `new_id` is already in `Dag`
+    pub fn rename_node(
+        &mut self,
+        old_id: NodeId,
+        new_id: NodeId,
+    ) -> Result<(), DagError<NodeId, EdgeData>> {
+        if !self.nodes.contains_key(&old_id) {
+            return Err(DagError::NodeNotFound(old_id));
+        }
+        if old_id == new_id {
+            return Ok(());
+        }
+        if self.nodes.contains_key(&new_id) {
+            return Err(DagError::DuplicateNode(new_id));
+        }
+        let data = self
+            .nodes
+            .remove(&old_id)
+            .unwrap_or_else(|| unreachable!("proved by contains_node"));
+        self.nodes.insert(new_id.clone(), data);
+        self.order.rename(&old_id, new_id.clone());
+
+        let children = self
+            .edges
+            .remove(&old_id)
+            .unwrap_or_else(|| unreachable!("every node has an entry in edges"));
+        for child_id in children.keys() {
+            let parents = self
+                .back_edges
+                .get_mut(child_id)
+                .unwrap_or_else(|| unreachable!("edges is consistent with back_edges"));
+            parents.remove(&old_id);
+            parents.insert(new_id.clone());
+        }
+        self.edges.insert(new_id.clone(), children);
+
+        let parents = self
+            .back_edges
+            .remove(&old_id)
+            .unwrap_or_else(|| unreachable!("every node has an entry in back_edges"));
+        for parent_id in parents.iter() {
+            let children = self
+                .edges
+                .get_mut(parent_id)
+                .unwrap_or_else(|| unreachable!("back_edges is consistent with edges"));
+            let data = children
+                .remove(&old_id)
+                .unwrap_or_else(|| unreachable!("back_edges is consistent with edges"));
+            children.insert(new_id.clone(), data);
+        }
+        self.back_edges.insert(new_id, parents);
+        Ok(())
+    }
+}
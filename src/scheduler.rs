@@ -0,0 +1,92 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::borrow::Borrow;
+
+use crate::Dag;
+
+/// Tracks which nodes of a `Dag` are ready to run, created by [`Dag::scheduler`]
+/// # Remarks
+/// * A node is ready when all of its parents are completed
+/// * Call [`Scheduler::complete`] when a node is done to unlock its children
+/// * Ready nodes are returned in the order of `NodeId`
+pub struct Scheduler<'a, NodeId, NodeData, EdgeData> {
+    dag: &'a Dag<NodeId, NodeData, EdgeData>,
+    // count of parents NOT completed of nodes NOT ready yet
+    pending: BTreeMap<NodeId, usize>,
+    ready: BTreeSet<NodeId>,
+    completed: usize,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Create a [`Scheduler`] in which all the roots are ready
+    pub fn scheduler(&self) -> Scheduler<'_, NodeId, NodeData, EdgeData> {
+        let mut pending = BTreeMap::new();
+        let mut ready = BTreeSet::new();
+        for (node_id, _) in self.nodes() {
+            match self.parents(&node_id).len() {
+                0 => ready.insert(node_id),
+                count => pending.insert(node_id, count).is_none(),
+            };
+        }
+        Scheduler {
+            dag: self,
+            pending,
+            ready,
+            completed: 0,
+        }
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Scheduler<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get all the nodes which are ready but NOT completed
+    pub fn ready(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.ready.iter().cloned()
+    }
+
+    /// Check if a node is ready but NOT completed
+    pub fn is_ready<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.ready.contains(node_id)
+    }
+
+    /// Mark a ready node as completed, its children become ready when all their parents are completed
+    /// # Returns
+    /// * Return `false` when `node_id` is NOT ready: it is not found, already completed,
+    ///   or some of its parents are not completed
+    pub fn complete(&mut self, node_id: NodeId) -> bool {
+        if !self.ready.remove(&node_id) {
+            return false;
+        }
+        self.completed += 1;
+        for (child_id, _) in self.dag.children(&node_id) {
+            let count = self
+                .pending
+                .get_mut(&child_id)
+                .unwrap_or_else(|| unreachable!("a child is pending until all parents complete"));
+            *count -= 1;
+            if *count == 0 {
+                self.pending.remove(&child_id);
+                self.ready.insert(child_id);
+            }
+        }
+        true
+    }
+
+    /// Get the count of completed nodes
+    pub fn completed_len(&self) -> usize {
+        self.completed
+    }
+
+    /// Check if all the nodes are completed
+    pub fn is_finished(&self) -> bool {
+        self.ready.is_empty() && self.pending.is_empty()
+    }
+}
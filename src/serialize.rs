@@ -0,0 +1,80 @@
+use alloc::vec::Vec;
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Dag;
+
+/// The serialized form of `Dag`
+/// # Remarks
+/// * `back_edges` are NOT stored, they are rebuilt from `edges`
+#[derive(Serialize)]
+struct DagRef<'a, NodeId, NodeData, EdgeData> {
+    nodes: Vec<(NodeId, &'a NodeData)>,
+    edges: Vec<(NodeId, NodeId, &'a EdgeData)>,
+}
+
+#[derive(Deserialize)]
+struct DagOwned<NodeId, NodeData, EdgeData> {
+    nodes: Vec<(NodeId, NodeData)>,
+    edges: Vec<(NodeId, NodeId, EdgeData)>,
+}
+
+impl<NodeId, NodeData, EdgeData> Serialize for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize,
+    NodeData: Serialize,
+    EdgeData: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        DagRef {
+            nodes: self.nodes().collect(),
+            edges: self.edges().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, NodeId, NodeData, EdgeData> Deserialize<'de> for Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Deserialize<'de>,
+    NodeData: Deserialize<'de>,
+    EdgeData: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let owned: DagOwned<NodeId, NodeData, EdgeData> = DagOwned::deserialize(deserializer)?;
+        let mut dag = Dag::new();
+        for (node_id, data) in owned.nodes {
+            if dag.insert_node(node_id, data).is_some() {
+                return Err(D::Error::custom("duplicate node in Dag"));
+            }
+        }
+        for (from, to, data) in owned.edges {
+            let children = dag
+                .edges
+                .get_mut(&from)
+                .ok_or_else(|| D::Error::custom("edge refers to a node not found in Dag"))?;
+            if !dag.nodes.contains_key(&to) {
+                return Err(D::Error::custom("edge refers to a node not found in Dag"));
+            }
+            if children.insert(to.clone(), data).is_some() {
+                return Err(D::Error::custom("duplicate edge in Dag"));
+            }
+            dag.back_edges
+                .get_mut(&to)
+                .unwrap_or_else(|| unreachable!("proved by contains_key"))
+                .insert(from);
+            dag.edges_len += 1;
+        }
+        // check all the edges at once
+        if !dag.rebuild_order() {
+            return Err(D::Error::custom("Dag has a cycle"));
+        }
+        Ok(dag)
+    }
+}
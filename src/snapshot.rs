@@ -0,0 +1,90 @@
+use alloc::sync::Arc;
+use core::ops::{Deref, DerefMut};
+
+use crate::Dag;
+
+/// A copy-on-write `Dag` which can hand out cheap read-only snapshots
+/// # Remarks
+/// * Created by [`Dag::into_shared`], reads go to `Dag` by `Deref`
+/// * [`SharedDag::snapshot`] is O(1), the snapshot shares the graph with `SharedDag`
+/// * The first mutation after a snapshot clones the graph once if the snapshot is still alive,
+///   see [`SharedDag::snapshot`], so mutating needs `NodeData: Clone` and `EdgeData: Clone`
+#[derive(Debug, Clone)]
+pub struct SharedDag<NodeId, NodeData, EdgeData> {
+    dag: Arc<Dag<NodeId, NodeData, EdgeData>>,
+}
+
+/// A read-only snapshot of [`SharedDag`]
+/// # Remarks
+/// * Reads go to `Dag` by `Deref`
+/// * Cloning it is O(1), it can be sent to other threads when the data is `Send + Sync`
+#[derive(Debug, Clone)]
+pub struct DagSnapshot<NodeId, NodeData, EdgeData> {
+    pub(crate) dag: Arc<Dag<NodeId, NodeData, EdgeData>>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Turn `Dag` into a [`SharedDag`] to take snapshots of it
+    pub fn into_shared(self) -> SharedDag<NodeId, NodeData, EdgeData> {
+        SharedDag {
+            dag: Arc::new(self),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> SharedDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Take a read-only snapshot of the current graph
+    /// # Remarks
+    /// * Taking it is O(1), but the first mutation of `SharedDag` while it is alive
+    ///   deep-clones the whole graph, which is O(V+E) with all the data,
+    ///   later mutations are in place until the next snapshot
+    /// * So taking a snapshot after every mutation makes every mutation O(V+E),
+    ///   `PersistentDag` of feature `persistent` shares the structure instead
+    pub fn snapshot(&self) -> DagSnapshot<NodeId, NodeData, EdgeData> {
+        DagSnapshot {
+            dag: self.dag.clone(),
+        }
+    }
+
+    /// Get the `Dag` back, it is cloned only if some snapshots are still alive
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData>
+    where
+        NodeData: Clone,
+        EdgeData: Clone,
+    {
+        Arc::unwrap_or_clone(self.dag)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for SharedDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> DerefMut for SharedDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Arc::make_mut(&mut self.dag)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for DagSnapshot<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
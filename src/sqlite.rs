@@ -0,0 +1,180 @@
+//! SQLite persistence, enabled by feature `sqlite`
+//! # Schema
+//! ```sql
+//! CREATE TABLE nodes (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+//! CREATE TABLE edges (
+//!     from_id TEXT NOT NULL,
+//!     to_id TEXT NOT NULL,
+//!     data TEXT NOT NULL,
+//!     PRIMARY KEY (from_id, to_id)
+//! );
+//! ```
+//! # Remarks
+//! * Ids and data are stored as JSON text, so they can be queried by the JSON functions of SQLite
+//! * Tables are created when they don't exist
+use std::{error::Error, fmt::Display};
+
+use rusqlite::{params, Connection, Row};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Dag, DagError};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS nodes (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS edges (
+        from_id TEXT NOT NULL,
+        to_id TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (from_id, to_id)
+    );
+";
+
+/// Errors when saving or loading `Dag` in SQLite
+#[derive(Debug)]
+pub enum SqliteError<NodeId, EdgeData> {
+    /// SQLite failed
+    Sqlite(rusqlite::Error),
+    /// An id or data can't be written as JSON, or stored JSON doesn't match the types
+    Json(serde_json::Error),
+    /// Stored nodes and edges are NOT a DAG
+    Dag(DagError<NodeId, EdgeData>),
+}
+
+impl<NodeId, EdgeData> Display for SqliteError<NodeId, EdgeData>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SqliteError::Sqlite(error) => writeln!(f, "SQLite error: {}", error),
+            SqliteError::Json(error) => writeln!(f, "Invalid JSON: {}", error),
+            SqliteError::Dag(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl<NodeId, EdgeData> Error for SqliteError<NodeId, EdgeData>
+where
+    NodeId: core::fmt::Debug + Display,
+    EdgeData: core::fmt::Debug,
+{
+}
+
+impl<NodeId, EdgeData> From<rusqlite::Error> for SqliteError<NodeId, EdgeData> {
+    fn from(error: rusqlite::Error) -> Self {
+        SqliteError::Sqlite(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<serde_json::Error> for SqliteError<NodeId, EdgeData> {
+    fn from(error: serde_json::Error) -> Self {
+        SqliteError::Json(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<DagError<NodeId, EdgeData>> for SqliteError<NodeId, EdgeData> {
+    fn from(error: DagError<NodeId, EdgeData>) -> Self {
+        SqliteError::Dag(error)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize,
+    NodeData: Serialize,
+    EdgeData: Serialize,
+{
+    /// Save `Dag` to SQLite, replacing all the rows in `nodes` and `edges`
+    /// # Errors
+    /// * `Err(Json(_))` when an id or data can't be written as JSON
+    /// * `Err(Sqlite(_))` when SQLite fails
+    /// # Remarks
+    /// * It is done in one transaction, so the tables are left unchanged on error
+    pub fn save_sqlite(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<(), SqliteError<NodeId, EdgeData>> {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(SCHEMA)?;
+        transaction.execute("DELETE FROM edges", [])?;
+        transaction.execute("DELETE FROM nodes", [])?;
+        {
+            let mut insert = transaction.prepare("INSERT INTO nodes (id, data) VALUES (?1, ?2)")?;
+            for (id, data) in &self.nodes {
+                insert.execute(params![
+                    serde_json::to_string(id)?,
+                    serde_json::to_string(data)?
+                ])?;
+            }
+            let mut insert = transaction
+                .prepare("INSERT INTO edges (from_id, to_id, data) VALUES (?1, ?2, ?3)")?;
+            for (from, children) in &self.edges {
+                let from = serde_json::to_string(from)?;
+                for (to, data) in children {
+                    insert.execute(params![
+                        from,
+                        serde_json::to_string(to)?,
+                        serde_json::to_string(data)?
+                    ])?;
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + DeserializeOwned,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    /// Load `Dag` from SQLite
+    /// # Errors
+    /// * `Err(Json(_))` when stored JSON doesn't match the types
+    /// * `Err(Dag(DuplicateNode(id)))` when different JSON texts are read as the same id
+    /// * `Err(Dag(NodeNotFound(id)))` when an edge refers to a node NOT found
+    /// * `Err(Dag(HasCycle(from,to,data,path)))` when edges make a cycle
+    /// * `Err(Sqlite(_))` when SQLite fails, e.g. the tables don't exist
+    /// # Remarks
+    /// * Both tables are read in one transaction, so a concurrent save is never seen half done
+    pub fn load_sqlite(connection: &Connection) -> Result<Self, SqliteError<NodeId, EdgeData>> {
+        let transaction = connection.unchecked_transaction()?;
+        let mut dag = Dag::new();
+        {
+            let mut select = transaction.prepare("SELECT id, data FROM nodes")?;
+            let mut rows = select.query([])?;
+            while let Some(row) = rows.next()? {
+                let id: NodeId = column(row, 0)?;
+                let data = column(row, 1)?;
+                if dag.contains_node(&id) {
+                    return Err(DagError::DuplicateNode(id).into());
+                }
+                dag.insert_node(id, data);
+            }
+            let mut select = transaction.prepare("SELECT from_id, to_id, data FROM edges")?;
+            let mut rows = select.query([])?;
+            while let Some(row) = rows.next()? {
+                let from = column(row, 0)?;
+                let to = column(row, 1)?;
+                let data = column(row, 2)?;
+                dag.insert_edge(from, to, data)?;
+            }
+        }
+        transaction.finish()?;
+        Ok(dag)
+    }
+}
+
+/// Read the JSON text at `index` of `row`
+fn column<T, NodeId, EdgeData>(row: &Row, index: usize) -> Result<T, SqliteError<NodeId, EdgeData>>
+where
+    T: DeserializeOwned,
+{
+    let text = row
+        .get_ref(index)?
+        .as_str()
+        .map_err(rusqlite::Error::from)?;
+    Ok(serde_json::from_str(text)?)
+}
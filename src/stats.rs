@@ -0,0 +1,52 @@
+use crate::Dag;
+
+/// Statistics of `Dag`, see [`Dag::stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DagStats {
+    /// Count of nodes
+    pub nodes_len: usize,
+    /// Count of edges
+    pub edges_len: usize,
+    /// Count of nodes without parents
+    pub roots_len: usize,
+    /// Count of nodes without children
+    pub leaves_len: usize,
+    /// The most parents of a node
+    pub max_in_degree: usize,
+    /// The most children of a node
+    pub max_out_degree: usize,
+    /// Count of edges in the longest path
+    pub longest_path_len: usize,
+    /// Count of weakly connected components
+    pub components_len: usize,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the statistics of `Dag`
+    /// # Remarks
+    /// * All the fields are 0 for an empty `Dag`
+    pub fn stats(&self) -> DagStats {
+        let mut stats = DagStats {
+            nodes_len: self.nodes_len(),
+            edges_len: self.edges_len(),
+            ..DagStats::default()
+        };
+        // every node has an entry in both edges and back_edges
+        for (children, parents) in self.edges.values().zip(self.back_edges.values()) {
+            if parents.is_empty() {
+                stats.roots_len += 1;
+            }
+            if children.is_empty() {
+                stats.leaves_len += 1;
+            }
+            stats.max_in_degree = stats.max_in_degree.max(parents.len());
+            stats.max_out_degree = stats.max_out_degree.max(children.len());
+        }
+        stats.longest_path_len = self.depth_map().into_values().max().unwrap_or(0);
+        stats.components_len = self.weakly_connected_components().len();
+        stats
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use crate::{Dag, NodeNotFound};
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    /// Clone the induced sub-DAG of `node_ids` into a new `Dag`
+    /// # Remarks
+    /// * The new `Dag` contains all the given nodes and all the edges between them
+    /// * No cycle checking is needed since a sub-DAG can't have a cycle
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when any of `node_ids` is NOT found in `Dag`
+    pub fn subgraph<I>(&self, node_ids: I) -> Result<Self, NodeNotFound<NodeId>>
+    where
+        I: IntoIterator<Item = NodeId>,
+    {
+        let ids = node_ids.into_iter().collect::<BTreeSet<_>>();
+        let mut dag = Dag::new();
+        for node_id in ids.iter() {
+            let data = self
+                .nodes
+                .get(node_id)
+                .ok_or_else(|| NodeNotFound(node_id.clone()))?;
+            dag.insert_node(node_id.clone(), data.clone());
+        }
+        for from in ids.iter() {
+            for (to, data) in self.children(from).filter(|(id, _)| ids.contains(id)) {
+                dag.edges
+                    .get_mut(from)
+                    .unwrap_or_else(|| unreachable!("inserted above"))
+                    .insert(to.clone(), data.clone());
+                dag.back_edges
+                    .get_mut(&to)
+                    .unwrap_or_else(|| unreachable!("inserted above"))
+                    .insert(from.clone());
+                dag.edges_len += 1;
+            }
+        }
+        dag.rebuild_order();
+        Ok(dag)
+    }
+
+    /// Clone the sub-DAG reachable from `node_ids` into a new `Dag`
+    /// # Remarks
+    /// * The new `Dag` contains the given nodes, all their descendants and all the edges between them
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when any of `node_ids` is NOT found in `Dag`
+    pub fn reachable_from<I>(&self, node_ids: I) -> Result<Self, NodeNotFound<NodeId>>
+    where
+        I: IntoIterator<Item = NodeId>,
+    {
+        let mut reachable = BTreeSet::new();
+        let mut stack = Vec::new();
+        for node_id in node_ids {
+            if !self.nodes.contains_key(&node_id) {
+                return Err(NodeNotFound(node_id));
+            }
+            stack.push(node_id);
+        }
+        while let Some(top) = stack.pop() {
+            if !reachable.contains(&top) {
+                stack.extend(self.children(&top).map(|(id, _)| id));
+                reachable.insert(top);
+            }
+        }
+        self.subgraph(reachable)
+    }
+
+    /// Clone the nodes and edges in both `Dag` and `other` into a new `Dag`
+    /// # Remarks
+    /// * Nodes and edges are matched by `NodeId`, data is cloned from `Dag`
+    /// * No cycle checking is needed since the edges are a subset of `Dag`
+    pub fn intersection(&self, other: &Self) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(id, _)| other.nodes.contains_key(*id))
+            .map(|(id, data)| (id.clone(), data.clone()));
+        let edges = self
+            .edges()
+            .filter(|(from, to, _)| other.contains_edge(from, to));
+        self.clone_parts(nodes, edges)
+    }
+
+    /// Clone the nodes and edges in `Dag` but NOT in `other` into a new `Dag`
+    /// # Remarks
+    /// * Nodes and edges are matched by `NodeId`
+    /// * Both ends of the kept edges are kept, even if they are in `other`
+    /// * No cycle checking is needed since the edges are a subset of `Dag`
+    pub fn difference(&self, other: &Self) -> Self {
+        let edges = self
+            .edges()
+            .filter(|(from, to, _)| !other.contains_edge(from, to))
+            .collect::<Vec<_>>();
+        let ends = edges
+            .iter()
+            .flat_map(|(from, to, _)| [from, to])
+            .collect::<BTreeSet<_>>();
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(id, _)| !other.nodes.contains_key(*id) || ends.contains(id))
+            .map(|(id, data)| (id.clone(), data.clone()));
+        self.clone_parts(
+            nodes,
+            edges
+                .iter()
+                .map(|(from, to, data)| (from.clone(), to.clone(), *data)),
+        )
+    }
+
+    /// Clone the differing parts of `Dag` and `other`
+    /// # Returns
+    /// Return `(ours, theirs)`, which are `self.difference(other)` and `other.difference(self)`
+    /// # Remarks
+    /// * They are NOT merged into one `Dag` since their edges may make a cycle together
+    pub fn symmetric_difference(&self, other: &Self) -> (Self, Self) {
+        (self.difference(other), other.difference(self))
+    }
+
+    /// Clone `nodes` and `edges` into a new `Dag`, `edges` must be acyclic and between `nodes`
+    fn clone_parts<'a, N, E>(&self, nodes: N, edges: E) -> Self
+    where
+        N: Iterator<Item = (NodeId, NodeData)>,
+        E: Iterator<Item = (NodeId, NodeId, &'a EdgeData)>,
+        EdgeData: 'a,
+    {
+        let mut dag = nodes.collect::<Self>();
+        for (from, to, data) in edges {
+            dag.edges
+                .get_mut(&from)
+                .unwrap_or_else(|| unreachable!("ends of edges are in nodes"))
+                .insert(to.clone(), data.clone());
+            dag.back_edges
+                .get_mut(&to)
+                .unwrap_or_else(|| unreachable!("ends of edges are in nodes"))
+                .insert(from);
+            dag.edges_len += 1;
+        }
+        dag.rebuild_order();
+        dag
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    borrow::Borrow,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{Dag, DagError, NodeNotFound};
+
+/// A `Dag` which can be shared by threads, guarded by a `RwLock`
+/// # Remarks
+/// * All the methods take `&self`, queries take a read lock and run concurrently
+/// * Queries return owned values, use [`SyncDag::read`] to borrow from the graph
+/// * Use [`SyncDag::write`] to do many mutations under one lock
+/// * A panic while holding the lock does NOT poison `SyncDag`
+#[derive(Debug)]
+pub struct SyncDag<NodeId, NodeData, EdgeData> {
+    dag: RwLock<Dag<NodeId, NodeData, EdgeData>>,
+}
+
+impl<NodeId, NodeData, EdgeData> Default for SyncDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> From<Dag<NodeId, NodeData, EdgeData>>
+    for SyncDag<NodeId, NodeData, EdgeData>
+{
+    fn from(dag: Dag<NodeId, NodeData, EdgeData>) -> Self {
+        SyncDag {
+            dag: RwLock::new(dag),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> SyncDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Create an empty DAG
+    pub fn new() -> Self {
+        Dag::new().into()
+    }
+
+    /// Get the `Dag` back
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock `SyncDag` for reading
+    pub fn read(&self) -> RwLockReadGuard<'_, Dag<NodeId, NodeData, EdgeData>> {
+        self.dag.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock `SyncDag` for writing
+    pub fn write(&self) -> RwLockWriteGuard<'_, Dag<NodeId, NodeData, EdgeData>> {
+        self.dag.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Check if a `node_id` is contained in `SyncDag`
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read().contains_node(node_id)
+    }
+
+    /// Check if an `edge` is contained in `SyncDag`
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read().contains_edge(from, to)
+    }
+
+    /// Check a node is root
+    pub fn is_root<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read().is_root(node_id)
+    }
+
+    /// Get the count of nodes
+    pub fn nodes_len(&self) -> usize {
+        self.read().nodes_len()
+    }
+
+    /// Get the count of edges
+    pub fn edges_len(&self) -> usize {
+        self.read().edges_len()
+    }
+
+    /// Insert a node with data, see [`Dag::insert_node`]
+    pub fn insert_node(&self, node_id: NodeId, node_data: NodeData) -> Option<NodeData> {
+        self.write().insert_node(node_id, node_data)
+    }
+
+    /// Insert an edge with data, see [`Dag::insert_edge`]
+    pub fn insert_edge(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        self.write().insert_edge(from, to, edge_data)
+    }
+
+    /// Remove an edge, see [`Dag::remove_edge`]
+    pub fn remove_edge(
+        &self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<Option<EdgeData>, DagError<NodeId, EdgeData>> {
+        self.write().remove_edge(from, to)
+    }
+
+    /// Remove a node and all edges related, see [`Dag::remove_node`]
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &self,
+        node_id: NodeId,
+    ) -> (Option<NodeData>, Vec<(NodeId, NodeId, EdgeData)>) {
+        self.write().remove_node(node_id)
+    }
+
+    /// Get ids of all the children of given `node_id`
+    pub fn children<Q>(&self, node_id: &Q) -> Vec<NodeId>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read().children(node_id).map(|(id, _)| id).collect()
+    }
+
+    /// Get ids of all the parents of given `node_id`
+    pub fn parents<Q>(&self, node_id: &Q) -> Vec<NodeId>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read().parents(node_id).collect()
+    }
+
+    /// Get ids of all the roots in `SyncDag`
+    pub fn roots(&self) -> Vec<NodeId> {
+        self.read().roots().map(|(id, _)| id).collect()
+    }
+
+    /// Get ids of all the leaves in `SyncDag`
+    pub fn leaves(&self) -> Vec<NodeId> {
+        self.read().leaves().map(|(id, _)| id).collect()
+    }
+
+    /// Get a clone of data from node
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `SyncDag`
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+        NodeData: Clone,
+    {
+        self.read().get_node(node_id).cloned()
+    }
+
+    /// Get a clone of data from edge
+    /// # Returns
+    /// Return `Ok(Some(data))` if success
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `SyncDag`
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Result<Option<EdgeData>, NodeNotFound<NodeId>>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = NodeId> + ?Sized,
+        EdgeData: Clone,
+    {
+        Ok(self.read().get_edge(from, to)?.cloned())
+    }
+
+    /// Update data of a node in place
+    /// # Returns
+    /// Return `None` if `node_id` is not found in `SyncDag`
+    pub fn update_node<Q, R>(&self, node_id: &Q, f: impl FnOnce(&mut NodeData) -> R) -> Option<R>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.write().get_node_mut(node_id).map(f)
+    }
+}
@@ -1,5 +1,5 @@
 use super::Dag;
-use crate::DagError;
+use crate::{DagError, NodeNotFound};
 
 #[test]
 fn basic() {
@@ -33,10 +33,11 @@ fn cycle_test() {
     dag.insert_edge(3, 4, ()).unwrap();
     let result = dag.insert_edge(4, 2, ());
     assert!(result.is_err());
-    if let DagError::HasCycle(from, to, data) = result.err().unwrap() {
+    if let DagError::HasCycle(from, to, data, path) = result.err().unwrap() {
         assert_eq!(from, 4);
         assert_eq!(to, 2);
         assert_eq!(data, ());
+        assert_eq!(path, vec![2, 3, 4]);
     } else {
         unreachable!();
     }
@@ -61,10 +62,11 @@ fn insert_and_remove() {
     let result = dag.insert_edge(3, 4, 'f');
     // it must fail and dag will not be destoryed
     assert!(result.is_err());
-    if let DagError::HasCycle(from, to, data) = result.unwrap_err() {
+    if let DagError::HasCycle(from, to, data, path) = result.unwrap_err() {
         assert_eq!(from, 3);
         assert_eq!(to, 4);
         assert_eq!(data, 'f');
+        assert_eq!(path, vec![4, 2, 3]);
     } else {
         unreachable!()
     }
@@ -78,40 +80,40 @@ fn insert_and_remove() {
         let leaves = dag.leaves().map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&leaves, &[5]);
         // all nodes
-        let children = dag.children(1).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&1).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[2]);
 
-        let children = dag.children(2).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&2).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[3]);
 
-        let children = dag.children(3).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&3).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[5]);
 
-        let children = dag.children(4).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&4).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[2, 5]);
 
-        let children = dag.children(5).map(|(id, _)| id).collect::<Vec<_>>();
-        assert_eq!(&children, &[]);
+        let children = dag.children(&5).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(children.is_empty());
 
         // parents
-        let parents = dag.parents(1).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&1).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(2).collect::<Vec<_>>();
+        let parents = dag.parents(&2).collect::<Vec<_>>();
         assert_eq!(&parents, &[1, 4]);
 
-        let parents = dag.parents(3).collect::<Vec<_>>();
+        let parents = dag.parents(&3).collect::<Vec<_>>();
         assert_eq!(&parents, &[2]);
 
-        let parents = dag.parents(4).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&4).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(5).collect::<Vec<_>>();
+        let parents = dag.parents(&5).collect::<Vec<_>>();
         assert_eq!(&parents, &[3, 4]);
 
         // data stored in node
         let node_data = (1..=5)
-            .map(|node| dag.get_node(node).unwrap())
+            .map(|node| dag.get_node(&node).unwrap())
             .copied()
             .collect::<Vec<_>>();
         assert_eq!(&node_data, &['A', 'B', 'C', 'D', 'E']);
@@ -139,40 +141,40 @@ fn insert_and_remove() {
         let leaves = dag.leaves().map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&leaves, &[3, 5]);
         // all nodes
-        let children = dag.children(1).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&1).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[2]);
 
-        let children = dag.children(2).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&2).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[3]);
 
-        let children = dag.children(3).map(|(id, _)| id).collect::<Vec<_>>();
-        assert_eq!(&children, &[]);
+        let children = dag.children(&3).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(children.is_empty());
 
-        let children = dag.children(4).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&4).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[2, 5]);
 
-        let children = dag.children(5).map(|(id, _)| id).collect::<Vec<_>>();
-        assert_eq!(&children, &[]);
+        let children = dag.children(&5).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(children.is_empty());
 
         // parents
-        let parents = dag.parents(1).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&1).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(2).collect::<Vec<_>>();
+        let parents = dag.parents(&2).collect::<Vec<_>>();
         assert_eq!(&parents, &[1, 4]);
 
-        let parents = dag.parents(3).collect::<Vec<_>>();
+        let parents = dag.parents(&3).collect::<Vec<_>>();
         assert_eq!(&parents, &[2]);
 
-        let parents = dag.parents(4).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&4).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(5).collect::<Vec<_>>();
+        let parents = dag.parents(&5).collect::<Vec<_>>();
         assert_eq!(&parents, &[4]);
 
         // data stored in node
         let node_data = (1..=5)
-            .map(|node| dag.get_node(node).unwrap())
+            .map(|node| dag.get_node(&node).unwrap())
             .copied()
             .collect::<Vec<_>>();
         assert_eq!(&node_data, &['A', 'B', 'C', 'D', 'E']);
@@ -186,11 +188,10 @@ fn insert_and_remove() {
         assert_eq!(&edges_data, &['a', 'b', 'c', 'd']);
     }
 
-    let (node_data, mut edges_data) = dag.remove_node(2);
+    let (node_data, edges) = dag.remove_node(2);
     assert!(node_data.is_some());
     assert_eq!(node_data.unwrap(), 'B');
-    edges_data.sort();
-    assert_eq!(&edges_data, &['a', 'b', 'c']);
+    assert_eq!(&edges, &[(2, 3, 'b'), (1, 2, 'a'), (4, 2, 'c')]);
     {
         // root
         let roots = dag.roots().map(|(id, _)| id).collect::<Vec<_>>();
@@ -199,43 +200,41 @@ fn insert_and_remove() {
         let leaves = dag.leaves().map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&leaves, &[1, 3, 5]);
         // all nodes
-        let children = dag.children(1).map(|(id, _)| id).collect::<Vec<_>>();
-        assert_eq!(&children, &[]);
+        let children = dag.children(&1).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(children.is_empty());
 
-        let children = dag.children(2).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&2).map(|(id, _)| id).collect::<Vec<_>>();
         // when id desn't exist in dag, it yield None
-        assert_eq!(&children, &[]);
+        assert!(children.is_empty());
 
-        let children = dag.children(3).map(|(id, _)| id).collect::<Vec<_>>();
-        assert_eq!(&children, &[]);
+        let children = dag.children(&3).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(children.is_empty());
 
-        let children = dag.children(4).map(|(id, _)| id).collect::<Vec<_>>();
+        let children = dag.children(&4).map(|(id, _)| id).collect::<Vec<_>>();
         assert_eq!(&children, &[5]);
 
-        let children = dag.children(5).map(|(id, _)| id).collect::<Vec<_>>();
-        assert_eq!(&children, &[]);
+        let children = dag.children(&5).map(|(id, _)| id).collect::<Vec<_>>();
+        assert!(children.is_empty());
 
         // parents
-        let parents = dag.parents(1).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&1).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(2).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&2).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(3).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&3).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(4).collect::<Vec<_>>();
-        assert_eq!(&parents, &[]);
+        let parents = dag.parents(&4).collect::<Vec<_>>();
+        assert!(parents.is_empty());
 
-        let parents = dag.parents(5).collect::<Vec<_>>();
+        let parents = dag.parents(&5).collect::<Vec<_>>();
         assert_eq!(&parents, &[4]);
 
         // data stored in node
         let node_data = (1..=5)
-            .map(|node| dag.get_node(node))
-            .filter(|node| node.is_some())
-            .map(|node| node.unwrap())
+            .filter_map(|node| dag.get_node(&node))
             .copied()
             .collect::<Vec<_>>();
         assert_eq!(&node_data, &['A', 'C', 'D', 'E']);
@@ -249,3 +248,3713 @@ fn insert_and_remove() {
         assert_eq!(&edges_data, &['d']);
     }
 }
+
+#[test]
+fn dominators() {
+    let mut dag = Dag::new();
+
+    //     1
+    //    / \
+    //   2   3
+    //    \ / \
+    //     4   5
+    //      \ /
+    //       6
+    for id in 1..=7 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(2, 4, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    dag.insert_edge(3, 5, ()).unwrap();
+    dag.insert_edge(4, 6, ()).unwrap();
+    dag.insert_edge(5, 6, ()).unwrap();
+    // 7 is not reachable from 1
+    dag.insert_edge(7, 5, ()).unwrap();
+
+    let doms = dag.dominators(1).unwrap();
+    assert_eq!(doms.root(), 1);
+    assert_eq!(doms.immediate_dominator(&1), None);
+    assert_eq!(doms.immediate_dominator(&2), Some(1));
+    assert_eq!(doms.immediate_dominator(&3), Some(1));
+    assert_eq!(doms.immediate_dominator(&4), Some(1));
+    assert_eq!(doms.immediate_dominator(&5), Some(3));
+    assert_eq!(doms.immediate_dominator(&6), Some(1));
+    assert_eq!(doms.immediate_dominator(&7), None);
+
+    let chain = doms.dominators(&5).unwrap().collect::<Vec<_>>();
+    assert_eq!(&chain, &[5, 3, 1]);
+    assert!(doms.dominators(&7).is_none());
+    assert!(doms.dominates(&3, &5));
+    assert!(doms.dominates(&5, &5));
+    assert!(!doms.dominates(&2, &4));
+    assert_eq!(doms.immediate_dominators().count(), 5);
+
+    let doms = dag.dominators(3).unwrap();
+    assert_eq!(doms.immediate_dominator(&6), Some(3));
+    assert_eq!(doms.immediate_dominator(&4), Some(3));
+
+    assert!(matches!(dag.dominators(8), Err(NodeNotFound(8))));
+}
+
+#[test]
+fn subgraph() {
+    let mut dag = Dag::new();
+
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_node(4, 'D');
+
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(1, 3, 'c').unwrap();
+    dag.insert_edge(3, 4, 'd').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+
+    let sub = dag.subgraph([1, 3, 4]).unwrap();
+    assert_eq!(sub.nodes_len(), 3);
+    assert!(!sub.contains_node(&2));
+    assert_eq!(sub.get_node(&3), Some(&'C'));
+    let mut edges_data = sub
+        .edges()
+        .map(|(_, _, data)| data)
+        .copied()
+        .collect::<Vec<_>>();
+    edges_data.sort();
+    assert_eq!(&edges_data, &['c', 'd']);
+    let parents = sub.parents(&3).collect::<Vec<_>>();
+    assert_eq!(&parents, &[1]);
+    // the original dag is untouched
+    assert_eq!(dag.nodes_len(), 4);
+
+    assert!(matches!(dag.subgraph([1, 5]), Err(NodeNotFound(5))));
+}
+
+#[test]
+fn filtered_view() {
+    let mut dag = Dag::new();
+
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_node(4, 'D');
+
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(3, 4, 'c').unwrap();
+
+    // hide node 2
+    let view = dag.filtered(|_, data| *data != 'B');
+    assert_eq!(view.nodes_len(), 3);
+    assert!(!view.contains_node(&2));
+    assert!(view.contains_node(&3));
+    assert!(!view.contains_edge(&1, &2));
+    assert!(view.contains_edge(&3, &4));
+    assert_eq!(view.get_edge(&3, &4), Some(&'c'));
+    assert_eq!(view.get_edge(&2, &3), None);
+    assert_eq!(view.children(&1).count(), 0);
+    assert_eq!(view.children(&2).count(), 0);
+    assert_eq!(view.parents(&3).count(), 0);
+
+    let mut roots = view.roots().map(|(id, _)| id).collect::<Vec<_>>();
+    roots.sort();
+    assert_eq!(&roots, &[1, 3]);
+    let mut leaves = view.leaves().map(|(id, _)| id).collect::<Vec<_>>();
+    leaves.sort();
+    assert_eq!(&leaves, &[1, 4]);
+    assert_eq!(view.edges().count(), 1);
+}
+
+#[test]
+fn transposed_view() {
+    let mut dag = Dag::new();
+
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(1, 3, 'b').unwrap();
+
+    let rev = dag.transposed();
+    assert!(rev.contains_edge(&2, &1));
+    assert!(!rev.contains_edge(&1, &2));
+    assert_eq!(rev.get_edge(&3, &1), Some(&'b'));
+    let children = rev.children(&2).collect::<Vec<_>>();
+    assert_eq!(&children, &[(1, &'a')]);
+    let mut parents = rev.parents(&1).collect::<Vec<_>>();
+    parents.sort();
+    assert_eq!(&parents, &[2, 3]);
+    let mut roots = rev.roots().map(|(id, _)| id).collect::<Vec<_>>();
+    roots.sort();
+    assert_eq!(&roots, &[2, 3]);
+    let leaves = rev.leaves().map(|(id, _)| id).collect::<Vec<_>>();
+    assert_eq!(&leaves, &[1]);
+    assert!(rev.is_root(&3));
+    assert!(!rev.is_root(&1));
+    assert!(rev
+        .edges()
+        .all(|(from, to, _)| dag.contains_edge(&to, &from)));
+}
+
+#[test]
+fn merge() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, 1);
+    dag.insert_node(2, 2);
+    dag.insert_edge(1, 2, 10).unwrap();
+
+    let mut other = Dag::new();
+    other.insert_node(1, 100);
+    other.insert_node(2, 200);
+    other.insert_node(3, 300);
+    other.insert_edge(1, 2, 1000).unwrap();
+    other.insert_edge(2, 3, 2000).unwrap();
+
+    dag.merge(other, |_, a, b| a + b, |_, _, a, b| a.max(b))
+        .unwrap();
+    assert_eq!(dag.nodes_len(), 3);
+    assert_eq!(dag.get_node(&1), Some(&101));
+    assert_eq!(dag.get_node(&3), Some(&300));
+    assert_eq!(dag.get_edge(&1, &2).unwrap(), Some(&1000));
+    assert_eq!(dag.get_edge(&2, &3).unwrap(), Some(&2000));
+    let parents = dag.parents(&3).collect::<Vec<_>>();
+    assert_eq!(&parents, &[2]);
+
+    // 3 -> 1 makes a cycle
+    let mut other = Dag::new();
+    other.insert_node(3, 0);
+    other.insert_node(4, 0);
+    other.insert_node(1, 0);
+    other.insert_edge(3, 4, 0).unwrap();
+    other.insert_edge(4, 1, 7).unwrap();
+    let before = dag.clone();
+    let result = dag.merge(other, |_, a, _| a, |_, _, a, _| a);
+    assert!(matches!(result, Err(DagError::HasCycle(4, 1, 7, path)) if path == [1, 2, 3, 4]));
+    // dag is untouched
+    assert_eq!(dag, before);
+    assert!(!dag.contains_node(&4));
+    assert_eq!(dag.edges().count(), 2);
+    assert!(dag.insert_edge(3, 1, 0).is_err());
+}
+
+#[test]
+fn diff_and_patch() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+
+    let mut other = dag.clone();
+    other.remove_node(3);
+    other.insert_node(4, 'D');
+    *other.get_node_mut(&1).unwrap() = 'Z';
+    *other.get_edge_mut(&1, &2).unwrap().unwrap() = 'z';
+    other.insert_edge(2, 4, 'c').unwrap();
+
+    let patch = dag.diff(&other);
+    assert_eq!(&patch.added_nodes, &[(4, 'D')]);
+    assert_eq!(&patch.removed_nodes, &[3]);
+    assert_eq!(&patch.changed_nodes, &[(1, 'Z')]);
+    assert_eq!(&patch.added_edges, &[(2, 4, 'c')]);
+    // edge 2 -> 3 is removed together with node 3
+    assert!(patch.removed_edges.is_empty());
+    assert_eq!(&patch.changed_edges, &[(1, 2, 'z')]);
+
+    dag.apply_patch(patch).unwrap();
+    assert!(dag.diff(&other).is_empty());
+    assert!(other.diff(&dag).is_empty());
+    assert_eq!(dag, other);
+    let parents = dag.parents(&4).collect::<Vec<_>>();
+    assert_eq!(&parents, &[2]);
+}
+
+#[test]
+fn rejected_patch_keeps_dag() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    let before = dag.clone();
+
+    // every patch below also removes an edge, changes a node and adds a node before the rejected part
+    let patch = || {
+        let mut patch = before.diff(&before);
+        patch.removed_edges.push((1, 2));
+        patch.changed_nodes.push((2, 'Z'));
+        patch.added_nodes.push((4, 'D'));
+        patch
+    };
+
+    let mut p = patch();
+    p.changed_edges.push((1, 3, 'x'));
+    assert!(matches!(
+        dag.apply_patch(p),
+        Err(DagError::EdgeNotFound(1, 3))
+    ));
+    assert_eq!(dag, before);
+    let mut p = patch();
+    p.changed_edges.push((1, 2, 'x'));
+    assert!(matches!(
+        dag.apply_patch(p),
+        Err(DagError::EdgeNotFound(1, 2))
+    ));
+    assert_eq!(dag, before);
+    let mut p = patch();
+    p.removed_nodes.push(5);
+    assert!(matches!(dag.apply_patch(p), Err(DagError::NodeNotFound(5))));
+    assert_eq!(dag, before);
+    let mut p = patch();
+    p.added_nodes.push((3, 'X'));
+    assert!(matches!(
+        dag.apply_patch(p),
+        Err(DagError::DuplicateNode(3))
+    ));
+    assert_eq!(dag, before);
+    let mut p = patch();
+    p.added_edges.push((2, 3, 'x'));
+    assert!(matches!(
+        dag.apply_patch(p),
+        Err(DagError::DuplicateEdge(2, 3))
+    ));
+    assert_eq!(dag, before);
+    let mut p = patch();
+    p.removed_nodes.push(3);
+    p.added_edges.push((4, 3, 'x'));
+    assert!(matches!(dag.apply_patch(p), Err(DagError::NodeNotFound(3))));
+    assert_eq!(dag, before);
+
+    // a cycle is found only while patching, so everything is rolled back
+    let mut p = patch();
+    p.removed_nodes.push(1);
+    p.added_edges.push((3, 4, 'c'));
+    p.added_edges.push((4, 2, 'd'));
+    assert!(matches!(
+        dag.apply_patch(p),
+        Err(DagError::HasCycle(4, 2, 'd', _))
+    ));
+    assert_eq!(dag, before);
+
+    let mut p = patch();
+    p.added_edges.push((3, 4, 'c'));
+    p.changed_edges.push((3, 4, 'd'));
+    dag.apply_patch(p).unwrap();
+    assert_eq!(dag.get_node(&2), Some(&'Z'));
+    assert_eq!(dag.get_edge(&3, &4).unwrap(), Some(&'d'));
+    assert!(!dag.contains_edge(&1, &2));
+}
+
+#[test]
+fn equality() {
+    let mut a = Dag::new();
+    a.insert_node(1, 'A');
+    a.insert_node(2, 'B');
+    a.insert_edge(1, 2, 'a').unwrap();
+
+    let mut b = Dag::new();
+    b.insert_node(2, 'B');
+    b.insert_node(1, 'A');
+    b.insert_edge(1, 2, 'a').unwrap();
+    assert_eq!(a, b);
+
+    *b.get_edge_mut(&1, &2).unwrap().unwrap() = 'b';
+    assert_ne!(a, b);
+    *b.get_edge_mut(&1, &2).unwrap().unwrap() = 'a';
+    assert_eq!(a, b);
+
+    b.remove_edge(1, 2).unwrap();
+    assert_ne!(a, b);
+    b.insert_edge(1, 2, 'a').unwrap();
+    *b.get_node_mut(&1).unwrap() = 'Z';
+    assert_ne!(a, b);
+    assert_ne!(a, Dag::new());
+}
+
+#[test]
+fn fingerprint() {
+    let mut a = Dag::new();
+    a.insert_node(1, 'A');
+    a.insert_node(2, 'B');
+    a.insert_node(3, 'C');
+    a.insert_edge(1, 2, 'a').unwrap();
+    a.insert_edge(1, 3, 'b').unwrap();
+
+    let mut b = Dag::new();
+    b.insert_node(3, 'C');
+    b.insert_node(2, 'B');
+    b.insert_node(1, 'Z');
+    b.insert_edge(1, 3, 'b').unwrap();
+    b.insert_edge(1, 2, 'a').unwrap();
+
+    // only data is different
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_ne!(a.fingerprint_with_data(), b.fingerprint_with_data());
+
+    let fps_a = a.node_fingerprints_with_data();
+    let fps_b = b.node_fingerprints_with_data();
+    assert_eq!(fps_a[&2], fps_b[&2]);
+    assert_eq!(fps_a[&3], fps_b[&3]);
+    assert_ne!(fps_a[&1], fps_b[&1]);
+
+    // a change in a child changes the fingerprints of its ancestors
+    b.insert_node(4, 'D');
+    b.insert_edge(3, 4, 'c').unwrap();
+    let fps_b = b.node_fingerprints();
+    let fps_a = a.node_fingerprints();
+    assert_eq!(fps_a[&2], fps_b[&2]);
+    assert_ne!(fps_a[&3], fps_b[&3]);
+    assert_ne!(fps_a[&1], fps_b[&1]);
+    assert_ne!(a.fingerprint(), b.fingerprint());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+
+    let json = serde_json::to_string(&dag).unwrap();
+    let de: Dag<i32, char, char> = serde_json::from_str(&json).unwrap();
+    assert_eq!(de, dag);
+    let parents = de.parents(&3).collect::<Vec<_>>();
+    assert_eq!(&parents, &[2]);
+
+    let dangling = r#"{"nodes":[[1,"A"]],"edges":[[1,2,"a"]]}"#;
+    assert!(serde_json::from_str::<Dag<i32, char, char>>(dangling).is_err());
+    let cycle = r#"{"nodes":[[1,"A"],[2,"B"]],"edges":[[1,2,"a"],[2,1,"b"]]}"#;
+    assert!(serde_json::from_str::<Dag<i32, char, char>>(cycle).is_err());
+}
+
+#[test]
+fn dot_import() {
+    let input = r#"
+        // a diamond
+        digraph G {
+            rankdir = LR;
+            node [shape = box];
+            a [label = "A \"node\""];
+            a -> b -> d [weight = 1];
+            a -> c;
+            c -> d;
+            e:port -> { f g } [color = red]
+            /* comment */
+            "h i" -> a
+        }
+    "#;
+    let dag = crate::dot::parse(input).unwrap();
+    assert_eq!(dag.nodes_len(), 8);
+    assert_eq!(dag.edges().count(), 7);
+    let a = dag.get_node("a").unwrap();
+    assert_eq!(a["label"], "A \"node\"");
+    assert_eq!(a["shape"], "box");
+    assert_eq!(dag.get_edge("b", "d").unwrap().unwrap()["weight"], "1");
+    assert!(dag.get_edge("a", "c").unwrap().unwrap().is_empty());
+    assert_eq!(dag.get_edge("e", "g").unwrap().unwrap()["color"], "red");
+    assert!(dag.contains_edge("h i", "a"));
+    let mut parents = dag.parents("d").collect::<Vec<_>>();
+    parents.sort();
+    assert_eq!(&parents, &["b", "c"]);
+
+    let result = crate::dot::parse("digraph {\n a -> b\n b -> c\n c -> a\n}");
+    match result {
+        Err(crate::dot::DotError::Dag {
+            line,
+            error: DagError::HasCycle(from, to, _, _),
+        }) => {
+            assert_eq!(line, 4);
+            assert_eq!((from.as_str(), to.as_str()), ("c", "a"));
+        }
+        _ => unreachable!(),
+    }
+
+    assert!(matches!(
+        crate::dot::parse("graph { a -- b }"),
+        Err(crate::dot::DotError::Syntax { line: 1, .. })
+    ));
+    assert!(matches!(
+        crate::dot::parse("digraph {\n a -> \n}"),
+        Err(crate::dot::DotError::Syntax { line: 3, .. })
+    ));
+
+    // ids are owned and unescaped like attributes
+    let dag = crate::dot::parse(&String::from(r#"digraph { "a\"b" -> c }"#)).unwrap();
+    assert!(dag.contains_edge("a\"b", "c"));
+    #[cfg(feature = "std")]
+    {
+        let read = |input: &str| -> Result<usize, Box<dyn std::error::Error>> {
+            Ok(crate::dot::parse(input)?.nodes_len())
+        };
+        assert!(read("digraph { a -> }").is_err());
+    }
+}
+
+#[test]
+fn diamond_is_not_cycle() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(2, 4, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    dag.insert_edge(1, 2, ()).unwrap();
+    // 1 reaches 4 by 2 paths
+    dag.insert_edge(1, 3, ()).unwrap();
+    assert!(dag.insert_edge(4, 1, ()).is_err());
+}
+
+#[cfg(feature = "graphml")]
+#[test]
+fn graphml() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Task {
+        name: String,
+        cost: u32,
+        done: Option<bool>,
+    }
+
+    let mut dag = Dag::new();
+    dag.insert_node(
+        1,
+        Task {
+            name: "build <all> & test".to_string(),
+            cost: 3,
+            done: Some(true),
+        },
+    );
+    dag.insert_node(
+        2,
+        Task {
+            name: "deploy".to_string(),
+            cost: 1,
+            done: None,
+        },
+    );
+    dag.insert_edge(1, 2, 0.5).unwrap();
+
+    let mut buf = Vec::new();
+    dag.write_graphml(&mut buf).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    assert!(text.contains(r#"attr.name="cost" attr.type="long""#));
+    let de = Dag::<u32, Task, f64>::read_graphml(text.as_bytes()).unwrap();
+    assert_eq!(de, dag);
+
+    // written by other tools
+    let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+          <key id="w" for="edge" attr.name="weight" attr.type="double">
+            <default>1.0</default>
+          </key>
+          <graph id="G" edgedefault="directed">
+            <node id="1"/>
+            <node id="2"/>
+            <node id="3"/>
+            <edge source="1" target="2"><data key="w">2.5</data></edge>
+            <edge source="2" target="3"/>
+          </graph>
+        </graphml>"#;
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Weight {
+        weight: f64,
+    }
+    let de = Dag::<u32, (), Weight>::read_graphml(input.as_bytes()).unwrap();
+    assert_eq!(de.get_edge(&1, &2).unwrap().unwrap().weight, 2.5);
+    assert_eq!(de.get_edge(&2, &3).unwrap().unwrap().weight, 1.0);
+
+    let cycle = r#"<graphml><graph edgedefault="directed">
+        <node id="1"/><node id="2"/>
+        <edge source="1" target="2"/><edge source="2" target="1"/>
+        </graph></graphml>"#;
+    assert!(matches!(
+        Dag::<u32, (), ()>::read_graphml(cycle.as_bytes()),
+        Err(crate::graphml::GraphMlError::Dag(DagError::HasCycle(
+            _,
+            _,
+            _,
+            _
+        )))
+    ));
+    let dangling =
+        r#"<graphml><graph><node id="1"/><edge source="1" target="2"/></graph></graphml>"#;
+    assert!(matches!(
+        Dag::<u32, (), ()>::read_graphml(dangling.as_bytes()),
+        Err(crate::graphml::GraphMlError::Dag(DagError::NodeNotFound(2)))
+    ));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_adjacency() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, "A".to_string());
+    dag.insert_node(2, "B".to_string());
+    dag.insert_edge(1, 2, ()).unwrap();
+
+    let json = dag.to_json_adjacency().unwrap();
+    let de = Dag::<u32, String, ()>::from_json_adjacency(&json).unwrap();
+    assert_eq!(de, dag);
+
+    let input = r#"{
+        "nodes": [{ "id": 1, "data": "A" }, { "id": 2, "data": "B" }, { "id": 3, "data": "C" }],
+        "edges": [{ "from": 1, "to": 2 }, { "from": 2, "to": 3 }, { "from": 3, "to": 1 }]
+    }"#;
+    let result = Dag::<u32, String, ()>::from_json_adjacency(input);
+    assert!(matches!(
+        result,
+        Err(crate::json::JsonError::Dag(DagError::HasCycle(3, 1, (), _)))
+    ));
+    let input = r#"{ "nodes": [{ "id": 1 }], "edges": [{ "from": 1, "to": 2 }] }"#;
+    let result = Dag::<u32, (), ()>::from_json_adjacency(input);
+    assert!(matches!(
+        result,
+        Err(crate::json::JsonError::Dag(DagError::NodeNotFound(2)))
+    ));
+    let input = r#"{ "nodes": [{ "id": 1 }, { "id": 1 }], "edges": [] }"#;
+    let result = Dag::<u32, (), ()>::from_json_adjacency(input);
+    assert!(matches!(result, Err(crate::json::JsonError::Json(_))));
+}
+
+#[cfg(feature = "binary")]
+#[test]
+fn binary() {
+    let mut dag = Dag::new();
+    dag.insert_node(1u32, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 10u8).unwrap();
+    dag.insert_edge(1, 3, 20).unwrap();
+
+    let bytes = dag.to_bytes().unwrap();
+    let de = Dag::<u32, char, u8>::from_bytes(&bytes).unwrap();
+    assert_eq!(de, dag);
+    let parents = de.parents(&3).collect::<Vec<_>>();
+    assert_eq!(&parents, &[1]);
+
+    assert!(Dag::<u32, char, u8>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn edge_entry() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, ());
+    dag.insert_node(2, ());
+
+    for _ in 0..3 {
+        *dag.edge_entry(1, 2).unwrap().or_insert(0).unwrap() += 1;
+    }
+    assert_eq!(dag.get_edge(&1, &2).unwrap(), Some(&3));
+    let parents = dag.parents(&2).collect::<Vec<_>>();
+    assert_eq!(&parents, &[1]);
+
+    let entry = dag
+        .edge_entry(1, 2)
+        .unwrap()
+        .and_modify(|count| *count *= 10);
+    assert_eq!((entry.from(), entry.to()), (1, 2));
+    match entry {
+        crate::EdgeEntry::Occupied(mut entry) => {
+            assert_eq!(*entry.get(), 30);
+            assert_eq!(entry.insert(7), 30);
+            assert_eq!(entry.remove(), 7);
+        }
+        crate::EdgeEntry::Vacant(_) => unreachable!(),
+    }
+    assert!(!dag.contains_edge(&1, &2));
+    assert_eq!(dag.parents(&2).count(), 0);
+
+    dag.edge_entry(2, 1).unwrap().or_default().unwrap();
+    let result = dag.edge_entry(1, 2).unwrap().or_insert(5);
+    assert!(matches!(result, Err(DagError::HasCycle(1, 2, 5, _))));
+    assert!(matches!(
+        dag.edge_entry(1, 3),
+        Err(DagError::NodeNotFound(3))
+    ));
+}
+
+#[test]
+fn collect_and_extend() {
+    let mut dag = (1..=3).map(|id| (id, id * 10)).collect::<Dag<_, _, ()>>();
+    assert_eq!(dag.nodes_len(), 3);
+    assert_eq!(dag.get_node(&2), Some(&20));
+
+    dag.extend([(3, 0), (4, 40)]);
+    assert_eq!(dag.nodes_len(), 4);
+    assert_eq!(dag.get_node(&3), Some(&0));
+
+    dag.extend_edges([(1, 2, ()), (2, 3, ()), (3, 4, ())])
+        .unwrap();
+    assert_eq!(dag.edges().count(), 3);
+
+    let result = dag.extend_edges([(1, 4, ()), (4, 1, ()), (2, 4, ())]);
+    assert!(matches!(result, Err(DagError::HasCycle(4, 1, (), _))));
+    // edges before the error are kept
+    assert!(dag.contains_edge(&1, &4));
+    assert!(!dag.contains_edge(&2, &4));
+    assert!(matches!(
+        dag.extend_edges([(1, 5, ())]),
+        Err(DagError::NodeNotFound(5))
+    ));
+}
+
+#[test]
+fn consuming_iterators() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, "A".to_string());
+    dag.insert_node(2, "B".to_string());
+    dag.insert_node(3, "C".to_string());
+    dag.insert_edge(1, 2, "a".to_string()).unwrap();
+    dag.insert_edge(1, 3, "b".to_string()).unwrap();
+    dag.insert_edge(2, 3, "c".to_string()).unwrap();
+
+    let nodes = dag.clone().into_nodes().collect::<Vec<_>>();
+    assert_eq!(
+        &nodes,
+        &[
+            (1, "A".to_string()),
+            (2, "B".to_string()),
+            (3, "C".to_string())
+        ]
+    );
+    let edges = dag.clone().into_edges().collect::<Vec<_>>();
+    assert_eq!(
+        &edges,
+        &[
+            (1, 2, "a".to_string()),
+            (1, 3, "b".to_string()),
+            (2, 3, "c".to_string())
+        ]
+    );
+    let (nodes, edges) = dag.clone().into_parts();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(edges.count(), 3);
+    let data = dag.into_iter().map(|(_, data)| data).collect::<String>();
+    assert_eq!(data, "ABC");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn hash_dag() {
+    use crate::HashDag;
+
+    let mut dag = HashDag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(1, 3, 'b').unwrap();
+    dag.insert_edge(2, 3, 'c').unwrap();
+    assert!(matches!(
+        dag.insert_edge(3, 1, 'd'),
+        Err(DagError::HasCycle(3, 1, 'd', _))
+    ));
+    assert_eq!(dag.children(&1).count(), 2);
+    assert!(dag.is_root(&1));
+    let leaves = dag.leaves().map(|(id, _)| id).collect::<Vec<_>>();
+    assert_eq!(&leaves, &[3]);
+    assert_eq!(dag.get_edge(&2, &3).unwrap(), Some(&'c'));
+
+    let sorted: Dag<_, _, _> = dag.clone().into();
+    let edges = sorted.edges().map(|(_, _, data)| *data).collect::<String>();
+    assert_eq!(edges, "abc");
+    assert_eq!(HashDag::from(sorted), dag);
+
+    let (node_data, mut edges) = dag.remove_node(3);
+    assert_eq!(node_data, Some('C'));
+    edges.sort();
+    assert_eq!(&edges, &[(1, 3, 'b'), (2, 3, 'c')]);
+    assert_eq!(dag.edges().count(), 1);
+    assert_eq!(dag.children(&2).count(), 0);
+}
+
+#[test]
+fn freeze() {
+    use alloc::string::String;
+
+    let mut dag = Dag::new();
+    for (id, data) in [(1, 'A'), (2, 'B'), (3, 'C'), (4, 'D')] {
+        dag.insert_node(id, data);
+    }
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(1, 3, 'b').unwrap();
+    dag.insert_edge(2, 4, 'c').unwrap();
+    dag.insert_edge(3, 4, 'd').unwrap();
+    let frozen = dag.clone().freeze();
+
+    assert_eq!(frozen.nodes_len(), 4);
+    assert_eq!(frozen.edges_len(), 4);
+    assert_eq!(frozen.node_ids(), &[1, 2, 3, 4]);
+    let one = frozen.index_of(&1).unwrap();
+    let four = frozen.index_of(&4).unwrap();
+    assert_eq!(frozen.children(one), &[1, 2]);
+    assert_eq!(frozen.children_data(one), &['a', 'b']);
+    assert_eq!(frozen.parents(four), &[1, 2]);
+    assert!(frozen.parents(one).is_empty());
+    assert_eq!(frozen.roots().collect::<Vec<_>>(), vec![one]);
+    assert_eq!(frozen.leaves().collect::<Vec<_>>(), vec![four]);
+    assert_eq!(frozen.get_node(&3), Some(&'C'));
+    assert_eq!(frozen.get_edge(&3, &4).unwrap(), Some(&'d'));
+    assert_eq!(frozen.get_edge(&4, &3).unwrap(), None);
+    assert!(matches!(frozen.get_edge(&5, &3), Err(NodeNotFound(5))));
+    assert!(frozen.contains_edge(&1, &3));
+    assert!(!frozen.contains_edge(&1, &5));
+    assert!(frozen
+        .edges()
+        .map(|(from, to, data)| (from, to, *data))
+        .eq(dag.edges().map(|(from, to, data)| (from, to, *data))));
+
+    let mut dag = Dag::new();
+    dag.insert_node(String::from("a"), ());
+    dag.insert_node(String::from("b"), ());
+    dag.insert_edge(String::from("a"), String::from("b"), ())
+        .unwrap();
+    let frozen = dag.freeze();
+    assert_eq!(frozen.index_of("b"), Some(1));
+    assert!(frozen.contains_node("a"));
+    assert!(frozen.contains_edge("a", "b"));
+    assert!(matches!(frozen.get_edge("a", "c"), Err(NodeNotFound(id)) if id == "c"));
+}
+
+#[test]
+fn snapshot() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    let mut shared = dag.into_shared();
+    let snapshot = shared.snapshot();
+
+    let reader = {
+        let snapshot = snapshot.clone();
+        std::thread::spawn(move || snapshot.edges().count())
+    };
+    shared.insert_node(3, 'C');
+    shared.insert_edge(2, 3, 'b').unwrap();
+    assert_eq!(reader.join().unwrap(), 1);
+
+    assert_eq!(snapshot.nodes_len(), 2);
+    assert!(!snapshot.contains_edge(&2, &3));
+    assert_eq!(shared.nodes_len(), 3);
+    assert!(shared.contains_edge(&2, &3));
+    assert_eq!(shared.snapshot().edges().count(), 2);
+    assert_eq!(shared.into_inner().nodes_len(), 3);
+}
+
+#[test]
+fn snapshot_reader_keeps_old_state() {
+    use std::sync::mpsc;
+
+    let mut dag = Dag::new();
+    for (id, data) in [(1, 'A'), (2, 'B'), (3, 'C')] {
+        dag.insert_node(id, data);
+    }
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    let mut shared = dag.into_shared();
+
+    let (written, wait_written) = mpsc::channel();
+    let reader = {
+        let snapshot = shared.snapshot();
+        std::thread::spawn(move || {
+            // read only after the writer has mutated
+            wait_written.recv().unwrap();
+            (
+                snapshot.get_node(&2).copied(),
+                snapshot
+                    .edges()
+                    .map(|(from, to, _)| (from, to))
+                    .collect::<Vec<_>>(),
+                snapshot.nodes_len(),
+            )
+        })
+    };
+    *shared.get_node_mut(&2).unwrap() = 'X';
+    shared.remove_node(3);
+    shared.insert_node(4, 'D');
+    shared.insert_edge(1, 4, 'c').unwrap();
+    written.send(()).unwrap();
+
+    let (data, edges, nodes_len) = reader.join().unwrap();
+    assert_eq!(data, Some('B'));
+    assert_eq!(edges, [(1, 2), (2, 3)]);
+    assert_eq!(nodes_len, 3);
+    assert_eq!(shared.get_node(&2), Some(&'X'));
+    assert_eq!(
+        shared
+            .edges()
+            .map(|(from, to, _)| (from, to))
+            .collect::<Vec<_>>(),
+        [(1, 2), (1, 4)]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sync_dag() {
+    use crate::SyncDag;
+    use std::{sync::Arc, thread};
+
+    let dag = Arc::new(SyncDag::new());
+    let workers = (0..4)
+        .map(|worker| {
+            let dag = dag.clone();
+            thread::spawn(move || {
+                for i in 0..10 {
+                    dag.insert_node(worker * 10 + i, 0);
+                }
+                for i in 1..10 {
+                    dag.insert_edge(worker * 10 + i - 1, worker * 10 + i, ())
+                        .unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    assert_eq!(dag.nodes_len(), 40);
+    assert_eq!(dag.roots(), vec![0, 10, 20, 30]);
+    assert_eq!(dag.children(&5), vec![6]);
+    assert_eq!(dag.parents(&5), vec![4]);
+    assert!(matches!(
+        dag.insert_edge(9, 0, ()),
+        Err(DagError::HasCycle(9, 0, (), _))
+    ));
+    assert_eq!(dag.update_node(&3, |data| *data += 1), Some(()));
+    assert_eq!(dag.get_node(&3), Some(1));
+    assert_eq!(dag.read().edges().count(), 36);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_iterators() {
+    use rayon::prelude::*;
+
+    let mut dag = Dag::new();
+    for id in 0..100 {
+        dag.insert_node(id, id);
+    }
+    for id in 1..100 {
+        dag.insert_edge(id - 1, id, id).unwrap();
+    }
+    dag.par_nodes_mut().for_each(|(id, data)| *data += id);
+    assert_eq!(dag.par_nodes().map(|(_, data)| *data).sum::<i32>(), 9900);
+    assert!(dag.par_nodes().all(|(id, data)| *data == id * 2));
+    let mut edges = dag
+        .par_edges()
+        .map(|(from, to, _)| (from, to))
+        .collect::<Vec<_>>();
+    edges.sort();
+    assert_eq!(edges.len(), 99);
+    assert_eq!(edges[0], (0, 1));
+}
+
+#[test]
+fn scheduler() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    dag.insert_edge(3, 5, ()).unwrap();
+    let mut scheduler = dag.scheduler();
+
+    assert_eq!(scheduler.ready().collect::<Vec<_>>(), vec![1, 2]);
+    assert!(!scheduler.complete(3));
+    assert!(scheduler.complete(1));
+    assert!(!scheduler.complete(1));
+    assert!(!scheduler.is_ready(&3));
+    assert!(scheduler.complete(2));
+    assert_eq!(scheduler.ready().collect::<Vec<_>>(), vec![3]);
+    assert!(scheduler.complete(3));
+    assert_eq!(scheduler.ready().collect::<Vec<_>>(), vec![4, 5]);
+    assert!(!scheduler.is_finished());
+    assert!(scheduler.complete(5));
+    assert!(scheduler.complete(4));
+    assert!(scheduler.is_finished());
+    assert_eq!(scheduler.completed_len(), 5);
+    assert!(!scheduler.complete(6));
+}
+
+#[test]
+fn incremental_order() {
+    fn reaches(dag: &Dag<u32, (), ()>, from: u32, to: u32) -> bool {
+        let mut stack = vec![from];
+        let mut visited = std::collections::BTreeSet::new();
+        while let Some(top) = stack.pop() {
+            if top == to {
+                return true;
+            }
+            if visited.insert(top) {
+                stack.extend(dag.children(&top).map(|(id, _)| id));
+            }
+        }
+        false
+    }
+
+    let mut dag = Dag::new();
+    for id in 0..40 {
+        dag.insert_node(id, ());
+    }
+    let mut seed = 7u32;
+    let mut next = || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (seed >> 16) % 40
+    };
+    for _ in 0..400 {
+        let (from, to) = (next(), next());
+        let expect_cycle = reaches(&dag, to, from);
+        assert_eq!(dag.insert_edge(from, to, ()).is_err(), expect_cycle);
+        if from % 13 == 0 {
+            dag.remove_node(to);
+            dag.insert_node(to, ());
+        }
+    }
+    for (from, to, _) in dag.edges() {
+        assert!(dag.order_of(&from) < dag.order_of(&to));
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn hash_dag_cycle_check() {
+    use crate::HashDag;
+
+    let mut dag = HashDag::new();
+    for id in 0..20 {
+        dag.insert_node(id, ());
+    }
+    // a wide layer between 0 and 19
+    for id in 1..19 {
+        dag.insert_edge(0, id, ()).unwrap();
+        dag.insert_edge(id, 19, ()).unwrap();
+    }
+    assert!(matches!(
+        dag.insert_edge(19, 0, ()),
+        Err(DagError::HasCycle(19, 0, (), _))
+    ));
+    assert!(matches!(
+        dag.insert_edge(5, 5, ()),
+        Err(DagError::HasCycle(5, 5, (), _))
+    ));
+    assert!(dag.insert_edge(0, 19, ()).unwrap().is_none());
+    assert!(dag.insert_edge(1, 2, ()).is_ok());
+    assert!(dag.insert_edge(2, 1, ()).is_err());
+    assert_eq!(dag.edges_len(), 38);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn hash_dag_keeps_order_like_dag() {
+    use crate::HashDag;
+
+    let mut dag = HashDag::new();
+    let mut expected = Dag::new();
+    for id in 0..10 {
+        dag.insert_node(id, ());
+        expected.insert_node(id, ());
+    }
+    // every edge goes against the insertion order, so each one reorders
+    for id in 0..9 {
+        dag.insert_edge(id + 1, id, ()).unwrap();
+        expected.insert_edge(id + 1, id, ()).unwrap();
+    }
+    assert!(dag.insert_edge(0, 9, ()).is_err());
+    assert_eq!(dag.edges_len(), 9);
+    let sorted: Dag<_, _, _> = dag.clone().into();
+    assert_eq!(sorted, expected);
+    assert_eq!(sorted.edges_len(), 9);
+    for id in 0..9 {
+        assert!(sorted.order_of(&(id + 1)) < sorted.order_of(&id));
+    }
+
+    dag.remove_node(5);
+    assert_eq!(dag.edges_len(), 7);
+    assert!(dag.insert_edge(0, 9, ()).is_ok());
+    assert!(dag.insert_edge(9, 0, ()).is_err());
+}
+
+#[test]
+fn insert_edges_atomically() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, 'a').unwrap();
+    let before = dag.clone();
+
+    assert!(matches!(
+        dag.insert_edges([
+            (2, 3, 'b'),
+            (1, 2, 'x'),
+            (3, 4, 'c'),
+            (4, 1, 'd'),
+            (1, 4, 'e')
+        ]),
+        Err(DagError::HasCycle(4, 1, 'd', _))
+    ));
+    assert_eq!(dag, before);
+    assert!(matches!(
+        dag.insert_edges([(2, 3, 'b'), (3, 5, 'c')]),
+        Err(DagError::NodeNotFound(5))
+    ));
+    assert_eq!(dag, before);
+
+    dag.insert_edges([(2, 3, 'b'), (1, 2, 'x'), (3, 4, 'c')])
+        .unwrap();
+    assert_eq!(dag.get_edge(&1, &2).unwrap(), Some(&'x'));
+    assert_eq!(dag.edges().count(), 3);
+    assert!(dag.insert_edge(4, 1, 'd').is_err());
+    assert!(dag.insert_edge(1, 4, 'e').is_ok());
+}
+
+#[test]
+fn transaction() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    let before = dag.clone();
+
+    let result = dag.transaction(|txn| {
+        txn.insert_node(4, 'D');
+        txn.insert_node(1, 'X');
+        txn.insert_edge(3, 4, 'c')?;
+        txn.insert_edge(1, 2, 'x')?;
+        assert!(txn.remove_node(2));
+        assert_eq!(txn.nodes_len(), 3);
+        txn.insert_edge(4, 1, 'd')?;
+        txn.insert_edge(1, 3, 'e')
+    });
+    assert!(matches!(result, Err(DagError::HasCycle(1, 3, 'e', _))));
+    assert_eq!(dag, before);
+    assert!(dag.insert_edge(3, 1, 'f').is_err());
+
+    let result = dag.transaction(|txn| {
+        txn.insert_node(4, 'D');
+        txn.insert_edge(3, 4, 'c')?;
+        txn.remove_edge(1, 2)
+    });
+    assert!(result.unwrap());
+    assert_eq!(dag.edges().count(), 2);
+    assert!(!dag.contains_edge(&1, &2));
+    assert_eq!(dag.get_edge(&3, &4).unwrap(), Some(&'c'));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn transaction_rolls_back_on_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    let before = dag.clone();
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        dag.transaction(|txn| {
+            txn.insert_node(3, 'C');
+            txn.insert_edge(2, 3, 'b')?;
+            txn.remove_edge(1, 2)?;
+            assert!(txn.remove_node(1));
+            if txn.nodes_len() == 2 {
+                panic!("failed in the middle of a transaction");
+            }
+            Ok::<_, DagError<_, _>>(())
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(dag, before);
+    assert!(dag.insert_edge(2, 1, 'x').is_err());
+}
+
+#[test]
+fn edges_len() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    assert!(dag.insert_edge(3, 1, ()).is_err());
+    assert_eq!(dag.edges_len(), 2);
+    dag.insert_edges([(3, 4, ()), (1, 4, ()), (1, 2, ())])
+        .unwrap();
+    assert_eq!(dag.edges_len(), 4);
+    assert!(dag.insert_edges([(2, 4, ()), (4, 1, ())]).is_err());
+    assert_eq!(dag.edges_len(), 4);
+    dag.remove_edge(1, 4).unwrap();
+    dag.remove_edge(1, 4).unwrap();
+    assert_eq!(dag.edges_len(), 3);
+    dag.remove_node(2);
+    assert_eq!(dag.edges_len(), 1);
+    assert_eq!(dag.edges_len(), dag.edges().count());
+    assert_eq!(dag.subgraph([3, 4]).unwrap().edges_len(), 1);
+}
+
+#[test]
+fn degrees() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    assert_eq!(dag.in_degree(&3), Some(2));
+    assert_eq!(dag.out_degree(&1), Some(2));
+    assert_eq!(dag.in_degree(&1), Some(0));
+    assert_eq!(dag.out_degree(&4), Some(0));
+    assert_eq!(dag.in_degree(&5), None);
+    dag.remove_node(2);
+    assert_eq!(dag.in_degree(&3), Some(1));
+    assert_eq!(dag.out_degree(&2), None);
+}
+
+#[test]
+fn parent_edges() {
+    let mut dag = Dag::new();
+    for id in 1..=3 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 3, 10).unwrap();
+    dag.insert_edge(2, 3, 20).unwrap();
+    dag.insert_edge(1, 2, 30).unwrap();
+    let parents = dag
+        .parent_edges(&3)
+        .map(|(id, data)| (id, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(parents, vec![(1, 10), (2, 20)]);
+    assert_eq!(dag.parent_edges(&3).len(), 2);
+    assert_eq!(dag.parent_edges(&1).count(), 0);
+    assert_eq!(dag.parent_edges(&4).count(), 0);
+}
+
+#[test]
+fn parent_edges_mut() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 5, 10).unwrap();
+    dag.insert_edge(2, 3, 20).unwrap();
+    dag.insert_edge(4, 5, 30).unwrap();
+    dag.insert_edge(3, 5, 40).unwrap();
+    for (parent_id, data) in dag.parent_edges_mut(&5) {
+        *data += parent_id;
+    }
+    let parents = dag
+        .parent_edges(&5)
+        .map(|(id, data)| (id, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(parents, vec![(1, 11), (3, 43), (4, 34)]);
+    assert_eq!(dag.get_edge(&2, &3).unwrap(), Some(&20));
+    assert_eq!(dag.parent_edges_mut(&5).len(), 3);
+    assert_eq!(dag.parent_edges_mut(&1).count(), 0);
+    assert_eq!(dag.parent_edges_mut(&6).count(), 0);
+}
+
+#[test]
+fn retain_nodes() {
+    let mut dag = Dag::new();
+    for id in 1..=6 {
+        dag.insert_node(id, id * 10);
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    dag.insert_edge(1, 4, ()).unwrap();
+    dag.insert_edge(4, 5, ()).unwrap();
+    dag.insert_edge(2, 6, ()).unwrap();
+    dag.retain_nodes(|id, data| id != 2 && *data != 30);
+
+    let nodes = dag.nodes().map(|(id, _)| id).collect::<Vec<_>>();
+    assert_eq!(nodes, vec![1, 4, 5, 6]);
+    let edges = dag
+        .edges()
+        .map(|(from, to, _)| (from, to))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(1, 4), (4, 5)]);
+    assert_eq!(dag.edges_len(), 2);
+    assert_eq!(dag.parents(&4).collect::<Vec<_>>(), vec![1]);
+    assert!(dag.is_root(&6));
+    assert_eq!(dag, dag.subgraph([1, 4, 5, 6]).unwrap());
+}
+
+#[test]
+fn retain_edges() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, 5).unwrap();
+    dag.insert_edge(1, 3, 1).unwrap();
+    dag.insert_edge(2, 4, 2).unwrap();
+    dag.insert_edge(3, 4, 7).unwrap();
+    dag.retain_edges(|from, _, weight| from == 1 || *weight > 3);
+
+    let edges = dag
+        .edges()
+        .map(|(from, to, _)| (from, to))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(1, 2), (1, 3), (3, 4)]);
+    assert_eq!(dag.edges_len(), 3);
+    assert_eq!(dag.parents(&4).collect::<Vec<_>>(), vec![3]);
+    assert_eq!(dag.nodes_len(), 4);
+}
+
+#[test]
+fn clear() {
+    let mut dag = Dag::new();
+    assert!(dag.is_empty());
+    dag.insert_node(1, ());
+    dag.insert_node(2, ());
+    dag.insert_edge(1, 2, ()).unwrap();
+    assert!(!dag.is_empty());
+    dag.clear();
+    assert!(dag.is_empty());
+    assert_eq!(dag.edges_len(), 0);
+    assert_eq!(dag, Dag::new());
+    dag.insert_node(2, ());
+    dag.insert_node(1, ());
+    dag.insert_edge(2, 1, ()).unwrap();
+    assert!(dag.insert_edge(1, 2, ()).is_err());
+}
+
+#[test]
+fn remove_descendants() {
+    let mut dag = Dag::new();
+    for id in 1..=7 {
+        dag.insert_node(id, id);
+    }
+    // 1 -> 2 -> 3 -> 4, 2 -> 5, 6 -> 5, 3 -> 7, 4 -> 7
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(3, 4, 'c').unwrap();
+    dag.insert_edge(2, 5, 'd').unwrap();
+    dag.insert_edge(6, 5, 'e').unwrap();
+    dag.insert_edge(3, 7, 'f').unwrap();
+    dag.insert_edge(4, 7, 'g').unwrap();
+    let mut other = dag.clone();
+
+    let (nodes, mut edges) = dag.remove_descendants(2).unwrap();
+    let mut ids = nodes.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, vec![3, 4, 7]);
+    edges.sort();
+    assert_eq!(
+        edges,
+        vec![(2, 3, 'b'), (3, 4, 'c'), (3, 7, 'f'), (4, 7, 'g')]
+    );
+    assert_eq!(
+        dag.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![1, 2, 5, 6]
+    );
+    assert_eq!(dag.edges_len(), 3);
+
+    let (nodes, edges) = other.remove_with_descendants(2).unwrap();
+    assert_eq!(nodes.len(), 4);
+    assert_eq!(nodes[0], (2, 2));
+    assert_eq!(edges.len(), 6);
+    assert_eq!(
+        other.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![1, 5, 6]
+    );
+    assert_eq!(other.parents(&5).collect::<Vec<_>>(), vec![6]);
+    assert!(matches!(
+        other.remove_descendants(2),
+        Err(DagError::NodeNotFound(2))
+    ));
+}
+
+#[test]
+fn contract_edge() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, id.to_string());
+    }
+    // 1 -> 2 -> 3 -> 4, 1 -> 4, 5 -> 3, 2 -> 4
+    dag.insert_edge(1, 2, 1).unwrap();
+    dag.insert_edge(2, 3, 2).unwrap();
+    dag.insert_edge(3, 4, 3).unwrap();
+    dag.insert_edge(1, 4, 4).unwrap();
+    dag.insert_edge(5, 3, 5).unwrap();
+    dag.insert_edge(2, 4, 6).unwrap();
+
+    // 2 -> 3 -> 4 and 2 -> 4
+    let before = dag.clone();
+    assert!(matches!(
+        dag.contract_edge(2, 4, |a, _| a, |_, _, a, _| a),
+        Err(DagError::HasCycle(2, 4, (), _))
+    ));
+    assert_eq!(dag, before);
+    assert_eq!(
+        dag.contract_edge(4, 2, |a, _| a, |_, _, a, _| a).unwrap(),
+        None
+    );
+
+    let result = dag.contract_edge(2, 3, |a, b| a + &b, |_, _, a, b| a + b);
+    assert_eq!(result.unwrap(), Some(2));
+    assert!(!dag.contains_node(&3));
+    assert_eq!(dag.get_node(&2).map(String::as_str), Some("23"));
+    let edges = dag
+        .edges()
+        .map(|(from, to, data)| (from, to, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(1, 2, 1), (1, 4, 4), (2, 4, 9), (5, 2, 5)]);
+    assert_eq!(dag.edges_len(), 4);
+    assert!(dag.insert_edge(4, 5, 0).is_err());
+}
+
+#[test]
+fn split_node() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 3, 2 -> 3, 3 -> 4, 3 -> 5
+    dag.insert_edge(1, 3, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(3, 4, 'c').unwrap();
+    dag.insert_edge(3, 5, 'd').unwrap();
+    assert!(matches!(
+        dag.split_node(3, 4, (), 'x', |_, _, _| true),
+        Err(DagError::DuplicateNode(4))
+    ));
+    assert!(matches!(
+        dag.split_node(6, 7, (), 'x', |_, _, _| true),
+        Err(DagError::NodeNotFound(6))
+    ));
+
+    dag.split_node(3, 6, (), 'x', |from, to, data| {
+        (to == 3 && from == 2) || *data == 'd'
+    })
+    .unwrap();
+    let edges = dag
+        .edges()
+        .map(|(from, to, data)| (from, to, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        vec![
+            (1, 3, 'a'),
+            (2, 6, 'b'),
+            (3, 4, 'c'),
+            (3, 6, 'x'),
+            (6, 5, 'd')
+        ]
+    );
+    assert_eq!(dag.edges_len(), 5);
+    assert!(dag.insert_edge(5, 1, 'y').is_err());
+}
+
+#[test]
+fn rename_node() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, id * 10);
+    }
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(4, 2, 'c').unwrap();
+    assert!(matches!(
+        dag.rename_node(2, 3),
+        Err(DagError::DuplicateNode(3))
+    ));
+    assert!(matches!(
+        dag.rename_node(5, 6),
+        Err(DagError::NodeNotFound(5))
+    ));
+    dag.rename_node(2, 2).unwrap();
+
+    dag.rename_node(2, 9).unwrap();
+    assert!(!dag.contains_node(&2));
+    assert_eq!(dag.get_node(&9), Some(&20));
+    let edges = dag
+        .edges()
+        .map(|(from, to, data)| (from, to, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(1, 9, 'a'), (4, 9, 'c'), (9, 3, 'b')]);
+    assert_eq!(dag.parents(&9).collect::<Vec<_>>(), vec![1, 4]);
+    assert_eq!(dag.parents(&3).collect::<Vec<_>>(), vec![9]);
+    assert!(dag.insert_edge(3, 1, 'd').is_err());
+}
+
+#[test]
+fn map() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, "1");
+    dag.insert_node(2, "20");
+    dag.insert_node(3, "300");
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    let mapped = dag.map(
+        |_, data| data.parse::<u32>().unwrap(),
+        |from, to, data| format!("{}{}{}", from, data, to),
+    );
+    let nodes = mapped.nodes().map(|(_, data)| *data).collect::<Vec<_>>();
+    assert_eq!(nodes, vec![1, 20, 300]);
+    assert_eq!(
+        mapped.get_edge(&2, &3).unwrap().map(String::as_str),
+        Some("2b3")
+    );
+    assert_eq!(mapped.parents(&3).collect::<Vec<_>>(), vec![2]);
+    assert_eq!(mapped.edges_len(), 2);
+    let mut mapped = mapped;
+    assert!(mapped.insert_edge(3, 1, String::new()).is_err());
+}
+
+#[test]
+fn filter_map() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, id);
+    }
+    dag.insert_edge(1, 2, 1).unwrap();
+    dag.insert_edge(2, 3, 2).unwrap();
+    dag.insert_edge(1, 3, 3).unwrap();
+    dag.insert_edge(3, 4, 4).unwrap();
+    let filtered = dag.filter_map(
+        |id, data| (id != 2).then(|| data * 10),
+        |_, _, weight| (weight != 4).then_some(weight as f32),
+    );
+    let nodes = filtered
+        .nodes()
+        .map(|(id, data)| (id, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(nodes, vec![(1, 10), (3, 30), (4, 40)]);
+    let edges = filtered
+        .edges()
+        .map(|(from, to, data)| (from, to, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(1, 3, 3.0)]);
+    assert_eq!(filtered.edges_len(), 1);
+    assert!(filtered.is_root(&4));
+    let mut filtered = filtered;
+    assert!(filtered.insert_edge(3, 1, 0.0).is_err());
+    assert!(filtered.insert_edge(4, 1, 0.0).is_ok());
+}
+
+#[test]
+fn reachable_from() {
+    let mut dag = Dag::new();
+    for id in 1..=6 {
+        dag.insert_node(id, id);
+    }
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(4, 3, 'c').unwrap();
+    dag.insert_edge(4, 5, 'd').unwrap();
+    dag.insert_edge(6, 1, 'e').unwrap();
+    let cone = dag.reachable_from([2, 5]).unwrap();
+    assert_eq!(
+        cone.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![2, 3, 5]
+    );
+    let edges = cone
+        .edges()
+        .map(|(from, to, data)| (from, to, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(2, 3, 'b')]);
+    assert_eq!(dag.reachable_from([6]).unwrap().nodes_len(), 4);
+    assert!(matches!(dag.reachable_from([1, 7]), Err(NodeNotFound(7))));
+}
+
+#[test]
+fn from_edges() {
+    let dag: Dag<_, (), _> = Dag::from_edges([(1, 2, 'a'), (2, 3, 'b'), (1, 3, 'c')]).unwrap();
+    assert_eq!(dag.nodes_len(), 3);
+    assert_eq!(dag.edges_len(), 3);
+    assert_eq!(dag.get_node(&2), Some(&()));
+
+    let dag = Dag::from_edges_with([(3, 1, ()), (1, 2, ())], |id| id * 10).unwrap();
+    let nodes = dag
+        .nodes()
+        .map(|(id, data)| (id, *data))
+        .collect::<Vec<_>>();
+    assert_eq!(nodes, vec![(1, 10), (2, 20), (3, 30)]);
+
+    let result = Dag::<_, (), _>::from_edges([(1, 2, 'a'), (2, 3, 'b'), (3, 1, 'c'), (4, 4, 'd')]);
+    assert!(matches!(result, Err(DagError::HasCycle(3, 1, 'c', _))));
+}
+
+#[test]
+fn cycle_path() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    dag.insert_edge(2, 5, ()).unwrap();
+    let error = dag.insert_edge(4, 1, ()).unwrap_err();
+    assert!(matches!(&error, DagError::HasCycle(4, 1, (), path) if path == &[1, 2, 3, 4]));
+    assert_eq!(
+        error.to_string(),
+        "DAG was destoryed since detected a cycle when insert edge '4' -> '1': '4' -> '1' -> '2' -> '3' -> '4'\n"
+    );
+    assert!(matches!(
+        dag.insert_edge(5, 5, ()),
+        Err(DagError::HasCycle(5, 5, (), path)) if path == [5]
+    ));
+
+    let mut other = Dag::new();
+    other.insert_node(5, ());
+    other.insert_node(1, ());
+    other.insert_edge(5, 1, ()).unwrap();
+    assert!(matches!(
+        dag.clone().merge(other, |_, a, _| a, |_, _, a, _| a),
+        Err(DagError::HasCycle(5, 1, (), path)) if path == [1, 2, 5]
+    ));
+
+    assert!(matches!(
+        dag.contract_edge(1, 2, |a, _| a, |_, _, a, _| a),
+        Ok(Some(()))
+    ));
+    dag.insert_edge(1, 4, ()).unwrap();
+    assert!(matches!(
+        dag.contract_edge(1, 4, |a, _| a, |_, _, a, _| a),
+        Err(DagError::HasCycle(1, 4, (), path)) if path == [1, 3, 4]
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn hash_dag_cycle_path() {
+    use crate::HashDag;
+
+    let mut dag = HashDag::new();
+    for id in 1..=3 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    assert!(matches!(
+        dag.insert_edge(3, 1, ()),
+        Err(DagError::HasCycle(3, 1, (), path)) if path == [1, 2, 3]
+    ));
+}
+
+#[test]
+fn would_create_cycle() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    assert!(matches!(dag.would_create_cycle(&3, &1), Ok(true)));
+    assert!(matches!(dag.would_create_cycle(&2, &2), Ok(true)));
+    assert!(matches!(dag.would_create_cycle(&1, &3), Ok(false)));
+    assert!(matches!(dag.would_create_cycle(&1, &2), Ok(false)));
+    assert!(matches!(dag.would_create_cycle(&4, &1), Ok(false)));
+    assert!(matches!(dag.would_create_cycle(&3, &4), Ok(false)));
+    assert_eq!(dag.would_create_cycle(&5, &1), Err(NodeNotFound(5)));
+    assert_eq!(dag.edges_len(), 2);
+}
+
+#[test]
+fn lookup_errors() {
+    let mut dag = Dag::<u32, (), char>::new();
+    dag.insert_node(1, ());
+    dag.insert_node(2, ());
+    // read APIs don't need `EdgeData` to be named
+    assert_eq!(dag.get_edge(&1, &3), Err(NodeNotFound(3)));
+    assert_eq!(
+        NodeNotFound(3).to_string(),
+        "Cannot found node in Dag where node_id='3'.\n"
+    );
+
+    let error: DagError<u32, char> = NodeNotFound(3).into();
+    assert!(matches!(error, DagError::NodeNotFound(3)));
+    assert_eq!(
+        DagError::<u32, char>::EdgeNotFound(1, 2).to_string(),
+        "Cannot found edge in Dag where edge='1' -> '2'.\n"
+    );
+
+    let mut patch = dag.diff(&dag);
+    patch.removed_edges.push((1, 2));
+    assert!(matches!(
+        dag.apply_patch(patch),
+        Err(DagError::EdgeNotFound(1, 2))
+    ));
+}
+
+#[test]
+fn clone_node_ids() {
+    use alloc::string::{String, ToString};
+
+    let mut dag = Dag::<String, (), ()>::new();
+    for id in ["a", "b", "c"] {
+        dag.insert_node(id.to_string(), ());
+    }
+    dag.insert_edge("a".to_string(), "b".to_string(), ())
+        .unwrap();
+    dag.insert_edge("b".to_string(), "c".to_string(), ())
+        .unwrap();
+    assert!(matches!(
+        dag.insert_edge("c".to_string(), "a".to_string(), ()),
+        Err(DagError::HasCycle(_, _, (), path)) if path == ["a", "b", "c"]
+    ));
+    let children = dag
+        .children(&"a".to_string())
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    assert_eq!(children, ["b"]);
+    let edges = dag
+        .edges()
+        .map(|(from, to, _)| (from, to))
+        .collect::<Vec<_>>();
+    assert_eq!(edges.len(), 2);
+
+    let (data, edges) = dag.remove_node("b".to_string());
+    assert_eq!(data, Some(()));
+    assert_eq!(edges.len(), 2);
+    assert_eq!(dag.roots().count(), 2);
+}
+
+#[test]
+fn borrowed_lookups() {
+    use alloc::string::{String, ToString};
+
+    let mut dag = Dag::<String, u32, u32>::new();
+    dag.insert_node("a".to_string(), 1);
+    dag.insert_node("b".to_string(), 2);
+    dag.insert_edge("a".to_string(), "b".to_string(), 3)
+        .unwrap();
+    assert!(dag.contains_node("a"));
+    assert!(!dag.contains_node("c"));
+    assert!(dag.contains_edge("a", "b"));
+    assert!(dag.is_root("a"));
+    assert_eq!(dag.get_node("b"), Some(&2));
+    assert_eq!(dag.get_edge("a", "b"), Ok(Some(&3)));
+    assert_eq!(dag.get_edge("a", "c"), Err(NodeNotFound("c".to_string())));
+    *dag.get_node_mut("a").unwrap() += 10;
+    assert_eq!(dag.get_node("a"), Some(&11));
+    assert_eq!(
+        dag.children("a").map(|(id, _)| id).collect::<Vec<_>>(),
+        ["b"]
+    );
+    assert_eq!(dag.parents("b").collect::<Vec<_>>(), ["a"]);
+    assert_eq!(dag.in_degree("b"), Some(1));
+    assert_eq!(dag.out_degree("b"), Some(0));
+}
+
+#[test]
+fn index_dag() {
+    use crate::IndexDag;
+
+    let mut dag = IndexDag::new();
+    let a = dag.insert_node("a");
+    let b = dag.insert_node("b");
+    let c = dag.insert_node("c");
+    assert_eq!([a.index(), b.index(), c.index()], [0, 1, 2]);
+    dag.insert_edge(a, b, ()).unwrap();
+    dag.insert_edge(b, c, ()).unwrap();
+    assert!(matches!(
+        dag.insert_edge(c, a, ()),
+        Err(DagError::HasCycle(..))
+    ));
+    assert_eq!(dag.get_node(&b), Some(&"b"));
+    assert_eq!(dag.children(&a).map(|(id, _)| id).collect::<Vec<_>>(), [b]);
+
+    let (data, edges) = dag.remove_node(b);
+    assert_eq!(data, Some("b"));
+    assert_eq!(edges.len(), 2);
+    assert_eq!(dag.remove_node(b).0, None);
+    // the index of b is recycled
+    let d = dag.insert_node("d");
+    assert_eq!(d, b);
+    assert_eq!(dag.insert_node("e").index(), 3);
+    *dag.get_node_mut(d).unwrap() = "D";
+    assert_eq!(dag.get_node(&d), Some(&"D"));
+    assert_eq!(dag.nodes_len(), 4);
+
+    dag.clear();
+    assert_eq!(dag.insert_node("f").index(), 0);
+}
+
+#[test]
+fn dag_builder() {
+    let dag = Dag::<u32, &str, ()>::builder()
+        .node(1, "a")
+        .node(2, "b")
+        .node(3, "c")
+        .node(4, "d")
+        .chain([1, 2, 3])
+        .edge(1, 4, ())
+        .build()
+        .unwrap();
+    assert_eq!(dag.nodes_len(), 4);
+    assert_eq!(dag.edges_len(), 3);
+    assert!(dag.contains_edge(&2, &3));
+    assert_eq!(dag.get_node(&4), Some(&"d"));
+
+    let errors = Dag::<u32, (), ()>::builder()
+        .node(1, ())
+        .node(2, ())
+        .node(1, ())
+        .chain([1, 2, 1])
+        .edge(2, 5, ())
+        .edge(6, 7, ())
+        .build()
+        .unwrap_err();
+    assert_eq!(errors.len(), 5);
+    assert!(matches!(errors[0], DagError::DuplicateNode(1)));
+    assert!(matches!(&errors[1], DagError::HasCycle(2, 1, (), path) if path == &[1, 2]));
+    assert!(matches!(errors[2], DagError::NodeNotFound(5)));
+    assert!(matches!(errors[3], DagError::NodeNotFound(6)));
+    assert!(matches!(errors[4], DagError::NodeNotFound(7)));
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn arbitrary_dags() {
+    use proptest::{arbitrary::any_with, prop_assert, prop_assert_eq, test_runner::TestRunner};
+
+    let strategy = any_with::<Dag<u8, u8, u8>>(((1..16).into(), (0..32).into()));
+    TestRunner::default()
+        .run(&strategy, |dag| {
+            prop_assert!((1..16).contains(&dag.nodes_len()));
+            prop_assert!(dag.edges_len() < 32);
+            prop_assert_eq!(dag.edges().count(), dag.edges_len());
+            let mut copy = Dag::new();
+            for (node_id, node_data) in dag.nodes() {
+                copy.insert_node(node_id, *node_data);
+            }
+            for (from, to, edge_data) in dag.edges() {
+                prop_assert!(dag.parents(&to).any(|id| id == from));
+                prop_assert!(copy.insert_edge(from, to, *edge_data).is_ok());
+            }
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn depth_and_height() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 2 -> 3 -> 4, 1 -> 4, 5 -> 3, 6
+    for (from, to) in [(1, 2), (2, 3), (3, 4), (1, 4), (5, 3)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    let depths = (1..=6)
+        .map(|id| dag.depth(&id).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(depths, [0, 1, 2, 3, 0, 0]);
+    let heights = (1..=6)
+        .map(|id| dag.height(&id).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(heights, [3, 2, 1, 0, 2, 0]);
+    assert_eq!(dag.depth(&7), None);
+    assert_eq!(dag.height(&7), None);
+}
+
+#[test]
+#[allow(deprecated)]
+fn layers() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    for (from, to) in [(1, 2), (2, 3), (3, 4), (1, 4), (5, 3)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    let layers = dag.layers();
+    assert_eq!(
+        layers.into_iter().collect::<Vec<_>>(),
+        [(1, 0), (2, 1), (3, 2), (4, 3), (5, 0), (6, 0)]
+    );
+    for id in 1..=6 {
+        assert_eq!(dag.layers()[&id], dag.depth(&id).unwrap());
+    }
+    assert!(Dag::<u32, (), ()>::new().layers().is_empty());
+    assert_eq!(dag.layers(), dag.depth_map());
+}
+
+#[cfg(feature = "layout")]
+#[test]
+fn layout() {
+    use crate::layout::LayoutOptions;
+
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 4 and 2 -> 3 cross when layer 1 is in the order of ids,
+    // 1 -> 5 goes through a dummy between 4 and 3
+    for (from, to) in [(1, 4), (2, 3), (3, 5), (1, 5)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    let options = LayoutOptions {
+        layer_spacing: 2.0,
+        ..LayoutOptions::default()
+    };
+    let positions = dag.layout(&options);
+    assert_eq!(
+        positions,
+        [
+            (1, (-0.5, 0.0)),
+            (2, (0.5, 0.0)),
+            (3, (1.0, 2.0)),
+            (4, (-1.0, 2.0)),
+            (5, (0.0, 4.0)),
+        ]
+    );
+    assert!(Dag::<u32, (), ()>::new().layout(&options).is_empty());
+}
+
+#[test]
+fn mermaid() {
+    use alloc::format;
+
+    let mut dag = Dag::new();
+    dag.insert_node(1, "A");
+    dag.insert_node(2, "say \"B\"");
+    dag.insert_node(3, "C");
+    dag.insert_edge(1, 2, 5).unwrap();
+    dag.insert_edge(1, 3, 0).unwrap();
+    assert_eq!(
+        dag.to_mermaid(),
+        "graph TD\n    n0[\"1\"]\n    n1[\"2\"]\n    n2[\"3\"]\n    n0 --> n1\n    n0 --> n2\n"
+    );
+    let output = dag.to_mermaid_with(
+        |_, data| data.to_string(),
+        |_, _, weight| (*weight > 0).then(|| format!("w={}", weight)),
+    );
+    assert_eq!(
+        output,
+        "graph TD\n    n0[\"A\"]\n    n1[\"say #quot;B#quot;\"]\n    n2[\"C\"]\n    n0 -->|\"w=5\"| n1\n    n0 --> n2\n"
+    );
+}
+
+#[test]
+fn render_ascii() {
+    let mut dag = Dag::<u32, &str, ()>::new();
+    for (id, data) in [(1, "a"), (2, "b"), (3, "c"), (4, "d"), (5, "e"), (6, "f")] {
+        dag.insert_node(id, data);
+    }
+    for (from, to) in [(1, 2), (1, 3), (2, 4), (3, 4), (4, 5), (3, 5)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(
+        dag.render_ascii(),
+        "1\n├── 2\n│   └── 4\n│       └── 5\n└── 3\n    ├── 4 (*)\n    └── 5\n6\n"
+    );
+    assert_eq!(
+        dag.render_ascii_with(|id, data| alloc::format!("{} {}", id, data)),
+        "1 a\n├── 2 b\n│   └── 4 d\n│       └── 5 e\n└── 3 c\n    ├── 4 d (*)\n    └── 5 e\n6 f\n"
+    );
+    assert_eq!(Dag::<u32, (), ()>::new().render_ascii(), "");
+}
+
+#[test]
+fn display() {
+    use alloc::format;
+
+    let mut dag = Dag::new();
+    dag.insert_node(1, 'A');
+    dag.insert_node(2, 'B');
+    dag.insert_node(3, 'C');
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(1, 3, 'b').unwrap();
+    dag.insert_edge(2, 3, 'c').unwrap();
+    assert_eq!(
+        format!("{}", dag),
+        "{1: A -> [2: a, 3: b], 2: B -> [3: c], 3: C}"
+    );
+    assert_eq!(
+        format!("{:#}", dag),
+        "1: A\n    -> 2: a\n    -> 3: b\n2: B\n    -> 3: c\n3: C"
+    );
+    let empty = Dag::<u32, char, char>::new();
+    assert_eq!(format!("{}", empty), "{}");
+    assert_eq!(format!("{:#}", empty), "");
+}
+
+#[test]
+fn stats() {
+    use crate::DagStats;
+
+    let mut dag = Dag::<u32, (), ()>::new();
+    assert_eq!(dag.stats(), DagStats::default());
+    for id in 1..=7 {
+        dag.insert_node(id, ());
+    }
+    for (from, to) in [(1, 2), (1, 3), (1, 4), (2, 5), (3, 5), (4, 5), (6, 7)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(
+        dag.stats(),
+        DagStats {
+            nodes_len: 7,
+            edges_len: 7,
+            roots_len: 2,
+            leaves_len: 2,
+            max_in_degree: 3,
+            max_out_degree: 3,
+            longest_path_len: 2,
+            components_len: 2,
+        }
+    );
+}
+
+#[test]
+fn shape_predicates() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    assert!(dag.is_forest());
+    assert!(!dag.is_tree());
+    assert!(!dag.is_chain());
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    // 4 roots
+    assert!(dag.is_forest() && !dag.is_tree() && !dag.is_chain());
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    assert!(dag.is_forest() && dag.is_tree() && dag.is_chain());
+    dag.remove_edge(3, 4).unwrap();
+    dag.insert_edge(2, 4, ()).unwrap();
+    assert!(dag.is_forest() && dag.is_tree() && !dag.is_chain());
+    dag.insert_edge(3, 4, ()).unwrap();
+    assert!(!dag.is_forest() && !dag.is_tree() && !dag.is_chain());
+}
+
+#[test]
+fn weakly_connected_components() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    assert!(dag.weakly_connected_components().is_empty());
+    for id in 1..=7 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 3 <- 6, 2 -> 5, 4, 7 -> 2
+    for (from, to) in [(1, 3), (6, 3), (2, 5), (7, 2)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(
+        dag.weakly_connected_components(),
+        [vec![1, 3, 6], vec![2, 5, 7], vec![4]]
+    );
+}
+
+#[test]
+fn roots_and_leaves_mut() {
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, "waiting");
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    for (_, data) in dag.leaves_mut() {
+        *data = "ready";
+    }
+    assert_eq!(
+        dag.nodes().map(|(_, data)| *data).collect::<Vec<_>>(),
+        ["waiting", "waiting", "ready", "ready"]
+    );
+    let roots = dag
+        .roots_mut()
+        .map(|(id, data)| {
+            *data = "root";
+            id
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(roots, [1, 4]);
+    assert_eq!(dag.get_node(&1), Some(&"root"));
+    assert_eq!(dag.get_node(&3), Some(&"ready"));
+}
+
+#[test]
+fn siblings() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> {3, 4}, 2 -> {4, 5}
+    for (from, to) in [(1, 3), (1, 4), (2, 4), (2, 5)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(dag.siblings(&4).collect::<Vec<_>>(), [3, 5]);
+    assert_eq!(dag.siblings(&3).collect::<Vec<_>>(), [4]);
+    assert_eq!(dag.siblings(&1).count(), 0);
+    assert_eq!(dag.siblings(&6).count(), 0);
+    assert_eq!(dag.siblings(&7).count(), 0);
+}
+
+#[test]
+fn neighbors() {
+    use crate::Direction;
+
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(2, 4, 'c').unwrap();
+    assert_eq!(
+        dag.neighbors(&2).collect::<Vec<_>>(),
+        [
+            (Direction::Outgoing, 3, &'b'),
+            (Direction::Outgoing, 4, &'c'),
+            (Direction::Incoming, 1, &'a'),
+        ]
+    );
+    assert_eq!(dag.neighbors(&5).count(), 0);
+}
+
+#[test]
+fn descendants_within() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 2 -> 3 -> 4 -> 5, 1 -> 4, 6
+    for (from, to) in [(1, 2), (2, 3), (3, 4), (4, 5), (1, 4)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(
+        dag.descendants_within(&1, 2).collect::<Vec<_>>(),
+        [(1, 2), (1, 4), (2, 3), (2, 5)]
+    );
+    assert_eq!(
+        dag.descendants_within(&1, 1).collect::<Vec<_>>(),
+        [(1, 2), (1, 4)]
+    );
+    assert_eq!(dag.descendants_within(&1, 0).count(), 0);
+    assert_eq!(
+        dag.descendants_within(&3, 10).collect::<Vec<_>>(),
+        [(1, 4), (2, 5)]
+    );
+    assert_eq!(dag.descendants_within(&6, 3).count(), 0);
+    assert_eq!(dag.descendants_within(&7, 3).count(), 0);
+}
+
+#[test]
+fn k_shortest_paths() {
+    let mut dag = Dag::<u32, (), u32>::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    for (from, to, weight) in [
+        (1, 2, 1),
+        (1, 3, 2),
+        (2, 4, 5),
+        (3, 4, 1),
+        (2, 3, 0),
+        (4, 5, 1),
+    ] {
+        dag.insert_edge(from, to, weight).unwrap();
+    }
+    let paths = dag
+        .k_shortest_paths(&1, &5, 3, |_, _, weight| *weight)
+        .unwrap();
+    assert_eq!(
+        paths,
+        [
+            (3, vec![1, 2, 3, 4, 5]),
+            (4, vec![1, 3, 4, 5]),
+            (7, vec![1, 2, 4, 5]),
+        ]
+    );
+    let paths = dag.k_shortest_paths(&1, &5, 10, |_, _, _| 1.0).unwrap();
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[0], (3.0, vec![1, 2, 4, 5]));
+    assert_eq!(
+        dag.k_shortest_paths(&1, &1, 2, |_, _, w| *w).unwrap(),
+        [(0, vec![1])]
+    );
+    assert!(dag
+        .k_shortest_paths(&5, &1, 2, |_, _, w| *w)
+        .unwrap()
+        .is_empty());
+    assert!(dag
+        .k_shortest_paths(&1, &6, 2, |_, _, w| *w)
+        .unwrap()
+        .is_empty());
+    assert!(dag
+        .k_shortest_paths(&1, &5, 0, |_, _, w| *w)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        dag.k_shortest_paths(&1, &7, 2, |_, _, w| *w),
+        Err(NodeNotFound(7))
+    );
+}
+
+#[test]
+fn topological_sort_by() {
+    use core::cmp::{Ordering, Reverse};
+
+    let mut dag = Dag::new();
+    for (id, priority) in [(1, 0), (2, 5), (3, 1), (4, 9), (5, 3)] {
+        dag.insert_node(id, priority);
+    }
+    // 1 -> {2, 3}, 3 -> 4, 5
+    for (from, to) in [(1, 2), (1, 3), (3, 4)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(dag.topological_sort_by(|a, b| a.cmp(b)), [1, 2, 3, 4, 5]);
+    assert_eq!(dag.topological_sort_by(|a, b| b.cmp(a)), [5, 1, 3, 4, 2]);
+    // high priority first
+    let order = dag.topological_sort_by_key(|id| Reverse(dag.get_node(id).copied()));
+    assert_eq!(order, [5, 1, 2, 3, 4]);
+    // ties keep the order of becoming ready
+    assert_eq!(
+        dag.topological_sort_by(|_, _| Ordering::Equal),
+        [1, 5, 2, 3, 4]
+    );
+
+    // a wide graph, children of the root become ready in the order of ids
+    let mut dag = Dag::new();
+    dag.insert_node(0, ());
+    for id in 1..=1000 {
+        dag.insert_node(id, ());
+        dag.insert_edge(0, id, ()).unwrap();
+    }
+    let order = dag.topological_sort_by_key(|id| (id * 7919) % 10);
+    let mut expected = (1..=1000).collect::<Vec<_>>();
+    expected.sort_by_key(|id| (id * 7919) % 10);
+    assert_eq!(order[0], 0);
+    assert_eq!(order[1..], expected);
+}
+
+#[test]
+fn all_topological_orders() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    assert_eq!(
+        dag.all_topological_orders().collect::<Vec<_>>(),
+        [Vec::<u32>::new()]
+    );
+    assert_eq!(dag.count_topological_orders(), 1);
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 2, 1 -> 3, 4
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(1, 3, ()).unwrap();
+    let orders = dag.all_topological_orders().collect::<Vec<_>>();
+    assert_eq!(orders.len(), 8);
+    assert_eq!(orders[0], [1, 2, 3, 4]);
+    assert_eq!(orders[7], [4, 1, 3, 2]);
+    assert_eq!(dag.count_topological_orders(), 8);
+    for order in &orders {
+        for (from, to, _) in dag.edges() {
+            let position = |id| order.iter().position(|other| *other == id);
+            assert!(position(from) < position(to));
+        }
+    }
+    let mut unique = orders.clone();
+    unique.dedup();
+    assert_eq!(unique, orders);
+
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    assert_eq!(
+        dag.all_topological_orders().collect::<Vec<_>>(),
+        [vec![1, 2, 3, 4]]
+    );
+    assert_eq!(dag.count_topological_orders(), 1);
+}
+
+#[test]
+fn width_and_maximum_antichain() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    assert_eq!(dag.width(), 0);
+    assert!(dag.maximum_antichain().is_empty());
+    for id in 1..=7 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> {2, 3, 4} -> 5 -> 6, 7
+    for (from, to) in [(1, 2), (1, 3), (1, 4), (2, 5), (3, 5), (4, 5), (5, 6)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    assert_eq!(dag.width(), 4);
+    let antichain = dag.maximum_antichain();
+    assert_eq!(antichain.len(), 4);
+    assert!(antichain.contains(&7));
+    for a in &antichain {
+        for b in &antichain {
+            assert!(
+                a == b
+                    || dag
+                        .descendants_within(a, usize::MAX)
+                        .all(|(_, id)| id != *b)
+            );
+        }
+    }
+    // a chain has width 1
+    let chain = Dag::<u32, (), ()>::builder()
+        .node(1, ())
+        .node(2, ())
+        .node(3, ())
+        .chain([1, 2, 3])
+        .build()
+        .unwrap();
+    assert_eq!(chain.width(), 1);
+    assert_eq!(chain.maximum_antichain().len(), 1);
+}
+
+#[test]
+fn minimum_path_cover() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    assert!(dag.minimum_path_cover().is_empty());
+    for id in 1..=7 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> {2, 3, 4} -> 5 -> 6, 7
+    for (from, to) in [(1, 2), (1, 3), (1, 4), (2, 5), (3, 5), (4, 5), (5, 6)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    let paths = dag.minimum_path_cover();
+    assert_eq!(paths.len(), 4);
+    let mut covered = paths.iter().flatten().copied().collect::<Vec<_>>();
+    covered.sort();
+    assert_eq!(covered, [1, 2, 3, 4, 5, 6, 7]);
+    for path in &paths {
+        for pair in path.windows(2) {
+            assert!(dag.contains_edge(&pair[0], &pair[1]));
+        }
+    }
+    assert!(paths.contains(&vec![7]));
+}
+
+#[test]
+fn reachability_index() {
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 0..100 {
+        dag.insert_node(id, ());
+    }
+    // two chains 0 -> 2 -> 4 ... and 1 -> 3 -> 5 ..., joined by 10 -> 71
+    for id in 0..98 {
+        dag.insert_edge(id, id + 2, ()).unwrap();
+    }
+    dag.insert_edge(10, 71, ()).unwrap();
+    let index = dag.reachability_index();
+    assert_eq!(index.nodes_len(), 100);
+    assert!(index.reaches(&0, &98));
+    assert!(index.reaches(&0, &99));
+    assert!(!index.reaches(&0, &69));
+    assert!(index.reaches(&1, &99));
+    assert!(!index.reaches(&1, &98));
+    assert!(!index.reaches(&98, &0));
+    assert!(index.reaches(&64, &64));
+    assert!(!index.reaches(&0, &100));
+}
+
+#[test]
+fn dominance_frontiers() {
+    use alloc::collections::{BTreeMap, BTreeSet};
+
+    let mut dag = Dag::<u32, (), ()>::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> {2, 3} -> 4 -> 5, 2 -> 5, 6 -> 4 is unreachable from 1
+    for (from, to) in [(1, 2), (1, 3), (2, 4), (3, 4), (4, 5), (2, 5), (6, 4)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    let frontiers = dag.dominance_frontiers(1).unwrap();
+    let expected = [
+        (1, vec![]),
+        (2, vec![4, 5]),
+        (3, vec![4]),
+        (4, vec![5]),
+        (5, vec![]),
+    ]
+    .into_iter()
+    .map(|(id, frontier)| (id, frontier.into_iter().collect::<BTreeSet<_>>()))
+    .collect::<BTreeMap<_, _>>();
+    assert_eq!(frontiers, expected);
+    assert!(matches!(dag.dominance_frontiers(7), Err(NodeNotFound(7))));
+}
+
+#[test]
+fn node_handles() {
+    use crate::IndexDag;
+
+    let mut dag = IndexDag::new();
+    let a = dag.insert_node_with_handle("a");
+    let b = dag.insert_node_with_handle("b");
+    let a_index = dag.resolve(a).unwrap();
+    assert_eq!(dag.handle(a_index), Some(a));
+    dag.insert_edge(a_index, b.index(), ()).unwrap();
+
+    dag.remove_node(a_index);
+    assert_eq!(dag.resolve(a), None);
+    assert_eq!(dag.handle(a_index), None);
+    // the index is reused by a new node, but the old handle doesn't resolve to it
+    let c = dag.insert_node_with_handle("c");
+    assert_eq!(c.index(), a.index());
+    assert_ne!(c, a);
+    assert_eq!(dag.resolve(a), None);
+    assert_eq!(dag.resolve(c), Some(c.index()));
+    assert_eq!(dag.get_node(&dag.resolve(c).unwrap()), Some(&"c"));
+
+    dag.clear();
+    let d = dag.insert_node_with_handle("d");
+    assert_eq!(d.index(), c.index());
+    assert_eq!(dag.resolve(c), None);
+    assert_eq!(dag.resolve(b), None);
+    assert_eq!(dag.resolve(d), Some(d.index()));
+}
+
+#[test]
+fn fold_descendants_and_ancestors() {
+    let mut dag = Dag::new();
+    for (id, size) in [(1, 1), (2, 10), (3, 100), (4, 1000), (5, 10000)] {
+        dag.insert_node(id, size);
+    }
+    // 1 -> {2, 3} -> 4, 5
+    for (from, to) in [(1, 2), (1, 3), (2, 4), (3, 4)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+    let sum = |acc, _, size: &u32| acc + *size;
+    assert_eq!(dag.fold_descendants(&1, 0, sum), Ok(1110));
+    assert_eq!(dag.fold_descendants(&4, 0, sum), Ok(0));
+    assert_eq!(dag.fold_ancestors(&4, 0, sum), Ok(111));
+    assert_eq!(dag.fold_ancestors(&5, 0, sum), Ok(0));
+    let mut ids = dag
+        .fold_descendants(&1, Vec::new(), |mut ids, id, _| {
+            ids.push(id);
+            ids
+        })
+        .unwrap();
+    ids.sort();
+    assert_eq!(ids, [2, 3, 4]);
+    assert_eq!(dag.fold_descendants(&6, 0, sum), Err(NodeNotFound(6)));
+}
+
+#[test]
+fn propagate() {
+    // a tiny spreadsheet, every cell adds its value to the weighted sum of its inputs
+    let mut dag = Dag::new();
+    for (id, value) in [("a", 1), ("b", 2), ("c", 0), ("d", 10)] {
+        dag.insert_node(id, value);
+    }
+    dag.insert_edge("a", "c", 1).unwrap();
+    dag.insert_edge("b", "c", 10).unwrap();
+    dag.insert_edge("c", "d", 2).unwrap();
+    let mut evaluated = Vec::new();
+    let results = dag.propagate(|id, value, inputs| {
+        evaluated.push(*id);
+        value
+            + inputs
+                .iter()
+                .map(|(_, weight, result)| *weight * **result)
+                .sum::<i32>()
+    });
+    assert_eq!(
+        results.into_iter().collect::<Vec<_>>(),
+        [("a", 1), ("b", 2), ("c", 21), ("d", 52)]
+    );
+    assert_eq!(evaluated.len(), 4);
+    assert_eq!(evaluated.last(), Some(&"d"));
+    assert!(Dag::<u32, (), ()>::new().propagate(|_, _, _| ()).is_empty());
+}
+
+#[test]
+fn incremental_propagation() {
+    use crate::Propagation;
+
+    let mut dag = Dag::new();
+    for (id, value) in [(1, 1), (2, 2), (3, 3), (4, 4)] {
+        dag.insert_node(id, value);
+    }
+    // 1 -> 3 -> 4, 2
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+    let sum = |_: &u32, value: &u32, inputs: &[(u32, &(), &u32)]| {
+        value + inputs.iter().map(|(_, _, result)| **result).sum::<u32>()
+    };
+    let mut propagation = Propagation::new();
+    assert_eq!(propagation.recompute(&dag, sum), 4);
+    assert_eq!(propagation.results(), &dag.propagate(sum));
+    assert_eq!(propagation.recompute(&dag, sum), 0);
+
+    *dag.get_node_mut(&3).unwrap() = 30;
+    propagation.mark_dirty(3);
+    assert!(propagation.is_dirty(&3));
+    assert_eq!(propagation.recompute(&dag, sum), 2);
+    assert!(!propagation.is_dirty(&3));
+    assert_eq!(propagation.get(&4), Some(&35));
+    assert_eq!(propagation.get(&2), Some(&2));
+
+    // new nodes are evaluated, results of removed nodes are dropped
+    dag.remove_node(2);
+    dag.insert_node(5, 5);
+    dag.insert_edge(4, 5, ()).unwrap();
+    assert_eq!(propagation.recompute(&dag, sum), 1);
+    assert_eq!(propagation.get(&2), None);
+    assert_eq!(propagation.get(&5), Some(&40));
+    assert_eq!(propagation.into_results(), dag.propagate(sum));
+}
+
+#[test]
+fn cached_queries() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, ());
+    dag.insert_node(2, ());
+    dag.insert_node(3, ());
+    dag.insert_edge(1, 2, 'a').unwrap();
+    let mut dag = dag.into_cached();
+    assert_eq!(dag.mutations(), 0);
+    assert_eq!(dag.root_ids(), &[1, 3]);
+    assert_eq!(dag.leaf_ids(), &[2, 3]);
+    assert!(dag.reachability_index().reaches(&1, &2));
+    assert!(!dag.reachability_index().reaches(&2, &3));
+
+    // data mutations keep the cache
+    *dag.get_edge_mut(&1, &2).unwrap().unwrap() = 'b';
+    assert_eq!(dag.mutations(), 0);
+
+    dag.insert_edge(2, 3, 'c').unwrap();
+    assert_eq!(dag.mutations(), 1);
+    assert_eq!(dag.topological_order(), &[1, 2, 3]);
+    assert_eq!(dag.root_ids(), &[1]);
+    assert_eq!(dag.leaf_ids(), &[3]);
+    assert!(dag.reachability_index().reaches(&1, &3));
+
+    assert!(matches!(
+        dag.insert_edge(3, 1, 'd'),
+        Err(DagError::HasCycle(3, 1, 'd', _))
+    ));
+    assert_eq!(dag.mutations(), 1);
+
+    dag.remove_node(2);
+    dag.dag_mut().insert_node(4, ());
+    assert_eq!(dag.mutations(), 3);
+    assert_eq!(dag.root_ids(), &[1, 3, 4]);
+    assert!(!dag.reachability_index().reaches(&1, &3));
+    assert_eq!(dag.into_inner().nodes_len(), 3);
+}
+
+#[test]
+fn observed_mutations() {
+    use crate::DagEvent;
+    use alloc::{rc::Rc, vec::Vec};
+    use core::cell::RefCell;
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut dag = Dag::new().into_observed();
+    let observer = dag.observe({
+        let events = events.clone();
+        move |dag: &Dag<u32, &str, u32>, event: &DagEvent<u32>| {
+            events.borrow_mut().push((event.clone(), dag.nodes_len()));
+        }
+    });
+    dag.insert_node(1, "a");
+    dag.insert_node(2, "b");
+    dag.insert_node(1, "c");
+    dag.insert_edge(1, 2, 0).unwrap();
+    assert!(dag.insert_edge(2, 1, 0).is_err());
+    assert_eq!(dag.update_edge(&1, &2, |data| *data += 1), Some(()));
+    assert_eq!(dag.update_node(&3, |_| ()), None);
+    assert_eq!(dag.update_node(&2, |data| *data), Some("b"));
+    dag.remove_node(1);
+    dag.clear();
+    assert_eq!(
+        events.borrow().as_slice(),
+        &[
+            (DagEvent::NodeInserted(1), 1),
+            (DagEvent::NodeInserted(2), 2),
+            (DagEvent::NodeUpdated(1), 2),
+            (DagEvent::EdgeInserted(1, 2), 2),
+            (DagEvent::EdgeUpdated(1, 2), 2),
+            (DagEvent::NodeUpdated(2), 2),
+            (DagEvent::EdgeRemoved(1, 2), 1),
+            (DagEvent::NodeRemoved(1), 1),
+            (DagEvent::Cleared, 0),
+        ]
+    );
+
+    assert!(dag.unobserve(observer));
+    assert!(!dag.unobserve(observer));
+    dag.insert_node(3, "d");
+    assert_eq!(events.borrow().len(), 9);
+    assert_eq!(dag.into_inner().nodes_len(), 1);
+}
+
+#[test]
+fn undo_redo() {
+    let mut dag = Dag::new();
+    dag.insert_node(1, "a");
+    dag.insert_node(2, "b");
+    dag.insert_node(3, "c");
+    dag.insert_edge(1, 2, 12).unwrap();
+    dag.insert_edge(2, 3, 23).unwrap();
+    let original = dag.clone();
+    let mut dag = dag.into_history();
+    assert!(!dag.can_undo());
+    assert!(!dag.undo());
+
+    assert!(dag.remove_node(2));
+    assert!(!dag.remove_node(2));
+    assert!(dag.insert_node(1, "x"));
+    assert!(!dag.insert_edge(1, 3, 13).unwrap());
+    let edited = (*dag).clone();
+
+    assert!(dag.undo());
+    assert!(dag.undo());
+    assert_eq!(dag.get_node(&1), Some(&"a"));
+    assert!(dag.undo());
+    assert_eq!(*dag, original);
+    assert!(!dag.can_undo());
+
+    assert!(dag.redo());
+    assert!(dag.redo());
+    assert!(dag.redo());
+    assert!(!dag.redo());
+    assert_eq!(*dag, edited);
+
+    dag.clear();
+    assert!(dag.is_empty());
+    assert!(dag.undo());
+    assert_eq!(*dag, edited);
+
+    // a new mutation drops the steps to redo
+    assert!(dag.undo());
+    assert!(dag.can_redo());
+    assert!(!dag.remove_edge(1, 3).unwrap());
+    assert!(dag.can_redo());
+    assert!(!dag.insert_edge(1, 3, 31).unwrap());
+    assert!(!dag.can_redo());
+    dag.clear_history();
+    assert!(!dag.can_undo());
+    assert_eq!(dag.into_inner().get_edge(&1, &3), Ok(Some(&31)));
+}
+
+#[test]
+#[cfg(feature = "persistent")]
+fn persistent_versions() {
+    use crate::PersistentDag;
+
+    let empty = PersistentDag::new();
+    let v1 = empty
+        .insert_node(1, "a")
+        .insert_node(2, "b")
+        .insert_node(3, "c");
+    let v2 = v1.insert_edge(1, 2, 12).unwrap();
+    let v3 = v2.insert_edge(2, 3, 23).unwrap();
+    assert!(matches!(
+        v3.insert_edge(3, 1, 31),
+        Err(DagError::HasCycle(3, 1, 31, path)) if path == [1, 2, 3]
+    ));
+    assert!(matches!(
+        v3.insert_edge(1, 4, 0),
+        Err(DagError::NodeNotFound(4))
+    ));
+    let v4 = v3.remove_node(2);
+
+    // old versions are unchanged
+    assert!(empty.is_empty());
+    assert_eq!(v1.edges_len(), 0);
+    assert_eq!(v2.edges().collect::<Vec<_>>(), [(1, 2, &12)]);
+    assert_eq!(v3.edges_len(), 2);
+    assert_eq!(v3.children(&2).collect::<Vec<_>>(), [(3, &23)]);
+    assert_eq!(v3.parents(&3).collect::<Vec<_>>(), [2]);
+    assert_eq!(v4.nodes().collect::<Vec<_>>(), [(1, &"a"), (3, &"c")]);
+    assert_eq!(v4.edges_len(), 0);
+    assert!(v4.is_root(&3));
+    assert_eq!(v4.get_edge(&1, &2), Err(NodeNotFound(2)));
+
+    let v5 = v3.remove_edge(1, 2).unwrap();
+    assert_eq!(v5.remove_edge(1, 2).unwrap(), v5);
+    assert_eq!(v5.roots().map(|(id, _)| id).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(v5.leaves().map(|(id, _)| id).collect::<Vec<_>>(), [1, 3]);
+    assert_eq!(v5.get_node(&2), Some(&"b"));
+
+    let dag = Dag::from(v3.clone());
+    assert_eq!(dag.edges_len(), 2);
+    assert_eq!(PersistentDag::from(dag), v3);
+
+    // 3 -> 1 goes backward in the order of insertion, only the new version is reordered
+    let v6 = v1.insert_edge(3, 1, 31).unwrap();
+    assert!(matches!(
+        v6.insert_edge(1, 3, 13),
+        Err(DagError::HasCycle(1, 3, 13, path)) if path == [3, 1]
+    ));
+    assert!(v1.insert_edge(1, 3, 13).is_ok());
+    let mut dag = Dag::from(v6);
+    assert!(dag.insert_edge(1, 3, 13).is_err());
+    assert!(dag.insert_edge(2, 3, 23).is_ok());
+}
+
+#[test]
+fn retain_reachable_from() {
+    let mut dag = Dag::new();
+    for id in 1..=6 {
+        dag.insert_node(id, id * 10);
+    }
+    // 1 -> 2 -> 3, 4 -> 3, 5 -> 6
+    dag.insert_edge(1, 2, 'a').unwrap();
+    dag.insert_edge(2, 3, 'b').unwrap();
+    dag.insert_edge(4, 3, 'c').unwrap();
+    dag.insert_edge(5, 6, 'd').unwrap();
+
+    assert!(matches!(
+        dag.retain_reachable_from([1, 7]),
+        Err(DagError::NodeNotFound(7))
+    ));
+    assert_eq!(dag.nodes_len(), 6);
+
+    let (nodes, edges) = dag.retain_reachable_from([1, 6]).unwrap();
+    assert_eq!(nodes, [(4, 40), (5, 50)]);
+    assert_eq!(edges.len(), 2);
+    assert!(edges.contains(&(4, 3, 'c')));
+    assert!(edges.contains(&(5, 6, 'd')));
+    assert_eq!(
+        dag.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        [1, 2, 3, 6]
+    );
+    assert_eq!(dag.edges_len(), 2);
+
+    let (nodes, edges) = dag.retain_reachable_from([]).unwrap();
+    assert_eq!(nodes.len(), 4);
+    assert_eq!(edges.len(), 2);
+    assert!(dag.is_empty());
+}
+
+#[test]
+fn spanning_forest() {
+    use crate::{MultipleParents, Tree};
+
+    let leaf = |id| Tree {
+        id,
+        children: Vec::new(),
+    };
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 2 -> 4, 1 -> 3 -> 4, 5
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(2, 4, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+
+    assert_eq!(dag.to_tree(), Err(MultipleParents(4, Vec::from([2, 3]))));
+    let forest = dag.spanning_forest(|id, parents| {
+        assert_eq!((id, parents), (&4, &[2, 3][..]));
+        parents.len() - 1
+    });
+    assert_eq!(
+        forest,
+        [
+            Tree {
+                id: 1,
+                children: Vec::from([
+                    leaf(2),
+                    Tree {
+                        id: 3,
+                        children: Vec::from([leaf(4)]),
+                    }
+                ]),
+            },
+            leaf(5),
+        ]
+    );
+
+    dag.remove_edge(3, 4).unwrap();
+    let forest = dag.to_tree().unwrap();
+    assert_eq!(forest, dag.spanning_forest(|_, _| unreachable!()));
+    assert_eq!(forest[0].children[0].children, [leaf(4)]);
+    assert_eq!(Dag::<u32, (), ()>::new().to_tree(), Ok(Vec::new()));
+}
+
+#[test]
+fn centrality_metrics() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    // 1 -> 3, 2 -> 3, 3 -> 4, 3 -> 5, 1 -> 4
+    for (from, to) in [(1, 3), (2, 3), (3, 4), (3, 5), (1, 4)] {
+        dag.insert_edge(from, to, ()).unwrap();
+    }
+
+    let through = dag.path_betweenness();
+    assert_eq!(
+        through.values().copied().collect::<Vec<_>>(),
+        [0.0, 0.0, 4.0, 0.0, 0.0]
+    );
+
+    // 1 -> 4 is shorter than 1 -> 3 -> 4
+    let betweenness = dag.betweenness_centrality();
+    assert_eq!(
+        betweenness.values().copied().collect::<Vec<_>>(),
+        [0.0, 0.0, 3.0, 0.0, 0.0]
+    );
+
+    let ranks = dag.pagerank(0.5);
+    let expected = [(1, 0.1), (2, 0.1), (3, 0.175), (4, 0.16875), (5, 0.14375)];
+    for (id, rank) in expected {
+        assert!((ranks[&id] - rank).abs() < 1e-9);
+    }
+    assert!(Dag::<u32, (), ()>::new().pagerank(0.85).is_empty());
+}
+
+#[test]
+fn intersection_and_difference() {
+    let declared = Dag::from_edges([(1, 2, 'a'), (2, 3, 'b'), (1, 4, 'c')]).unwrap();
+    let mut observed = Dag::from_edges([(1, 2, 'x'), (3, 2, 'y'), (2, 5, 'z')]).unwrap();
+    observed.insert_node(6, ());
+
+    let common = declared.intersection(&observed);
+    assert_eq!(
+        common.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(common.edges().collect::<Vec<_>>(), [(1, 2, &'a')]);
+
+    let (ours, theirs) = declared.symmetric_difference(&observed);
+    assert_eq!(
+        ours.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        [1, 2, 3, 4]
+    );
+    assert_eq!(
+        ours.edges().collect::<Vec<_>>(),
+        [(1, 4, &'c'), (2, 3, &'b')]
+    );
+    assert_eq!(
+        theirs.nodes().map(|(id, _)| id).collect::<Vec<_>>(),
+        [2, 3, 5, 6]
+    );
+    assert_eq!(
+        theirs.edges().collect::<Vec<_>>(),
+        [(2, 5, &'z'), (3, 2, &'y')]
+    );
+    assert_eq!(theirs.topological_sort_by(Ord::cmp), [3, 2, 5, 6]);
+
+    assert_eq!(declared.intersection(&declared), declared);
+    assert!(declared.difference(&declared).is_empty());
+}
+
+#[test]
+#[cfg(feature = "watch")]
+fn watch_snapshots() {
+    let mut dag = Dag::from_edges([(1, 2, ())]).unwrap().into_watch();
+    let reader = dag.reader();
+    let before = reader.load();
+
+    dag.insert_node(3, ());
+    dag.insert_edge(2, 3, ()).unwrap();
+    assert_eq!(dag.edges_len(), 2);
+    // readers see nothing until publishing
+    assert_eq!(reader.load().edges_len(), 1);
+
+    dag.publish();
+    let handle = std::thread::spawn({
+        let reader = reader.clone();
+        move || reader.load().nodes_len()
+    });
+    assert_eq!(handle.join().unwrap(), 3);
+    assert_eq!(before.nodes_len(), 2);
+
+    dag.remove_node(1);
+    let dag = dag.into_inner();
+    assert_eq!(dag.nodes_len(), 2);
+    assert_eq!(reader.load().nodes_len(), 3);
+}
+
+#[test]
+#[cfg(feature = "disk")]
+fn disk_backed_data() {
+    use crate::DiskDag;
+
+    let path = std::env::temp_dir().join(format!("xdag-disk-{}.data", std::process::id()));
+    let mut dag = DiskDag::<u32, String, Vec<u8>>::create(&path).unwrap();
+    assert!(!dag.insert_node(1, &"one".to_string()).unwrap());
+    assert!(!dag.insert_node(2, &"two".to_string()).unwrap());
+    assert!(!dag.insert_node(3, &"three".to_string()).unwrap());
+    assert!(!dag.insert_edge(1, 2, vec![1, 2]).unwrap());
+    assert!(!dag.insert_edge(2, 3, vec![2, 3]).unwrap());
+    let len = dag.file_len();
+    assert!(matches!(
+        dag.insert_edge(3, 1, vec![3, 1]),
+        Err(crate::DiskError::Dag(DagError::HasCycle(3, 1, data, _))) if data == [3, 1]
+    ));
+    assert!(matches!(
+        dag.insert_edge(1, 4, Vec::new()),
+        Err(crate::DiskError::Dag(DagError::NodeNotFound(4)))
+    ));
+    assert_eq!(dag.file_len(), len);
+
+    // the structure is in memory
+    assert_eq!(dag.children(&1).map(|(id, _)| id).collect::<Vec<_>>(), [2]);
+    assert_eq!(dag.get_node(&2).unwrap().as_deref(), Some("two"));
+    assert_eq!(dag.get_node(&4).unwrap(), None);
+    assert_eq!(dag.get_edge(&2, &3).unwrap(), Some(vec![2, 3]));
+    assert_eq!(dag.get_edge(&1, &3).unwrap(), None);
+    assert!(matches!(
+        dag.get_edge(&1, &4),
+        Err(crate::DiskError::Dag(DagError::NodeNotFound(4)))
+    ));
+
+    assert!(dag.insert_node(2, &"TWO".to_string()).unwrap());
+    assert!(dag.remove_node(1).unwrap());
+    assert!(!dag.remove_node(1).unwrap());
+    assert!(matches!(
+        dag.remove_edge(1, 2),
+        Err(crate::DiskError::Dag(DagError::NodeNotFound(1)))
+    ));
+    assert!(dag.garbage_len() > 0);
+
+    // the structure is loaded again from the file
+    let reopened = DiskDag::<u32, String, Vec<u8>>::open(&path).unwrap();
+    assert_eq!(*reopened, *dag);
+    assert_eq!(reopened.file_len(), dag.file_len());
+    assert_eq!(reopened.garbage_len(), dag.garbage_len());
+    assert_eq!(reopened.get_node(&2).unwrap().as_deref(), Some("TWO"));
+    assert_eq!(reopened.get_edge(&2, &3).unwrap(), Some(vec![2, 3]));
+    drop(reopened);
+
+    dag.compact().unwrap();
+    assert_eq!(dag.garbage_len(), 0);
+    assert!(dag.file_len() < len);
+    assert_eq!(dag.get_node(&2).unwrap().as_deref(), Some("TWO"));
+    assert_eq!(dag.get_node(&3).unwrap().as_deref(), Some("three"));
+    assert_eq!(dag.get_edge(&2, &3).unwrap(), Some(vec![2, 3]));
+    let reopened = DiskDag::<u32, String, Vec<u8>>::open(&path).unwrap();
+    assert_eq!(*reopened, *dag);
+    assert_eq!(reopened.garbage_len(), 0);
+    drop(reopened);
+
+    dag.clear().unwrap();
+    assert!(dag.is_empty());
+    assert_eq!(dag.file_len(), 8);
+    assert!(DiskDag::<u32, String, Vec<u8>>::open(&path)
+        .unwrap()
+        .is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "disk")]
+fn disk_open_validates_file() {
+    use crate::{DiskDag, DiskError};
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("xdag-disk-open-{}.data", std::process::id()));
+    let mut dag = DiskDag::<u32, String, String>::create(&path).unwrap();
+    dag.insert_node(1, &"one".to_string()).unwrap();
+    dag.insert_node(2, &"two".to_string()).unwrap();
+    dag.insert_edge(1, 2, "a".to_string()).unwrap();
+    let len = dag.file_len();
+    drop(dag);
+
+    // a torn entry at the end is cut
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[9, 0, 0, 0, 1]).unwrap();
+    drop(file);
+    let dag = DiskDag::<u32, String, String>::open(&path).unwrap();
+    assert_eq!(dag.file_len(), len);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+    assert_eq!(dag.get_edge(&1, &2).unwrap().as_deref(), Some("a"));
+    drop(dag);
+
+    // an edge back from 2 to 1 can't be applied, the record is the variant,
+    // `from`, `to` and the length of the data, which is the string "b"
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[4, 0, 0, 0, 1, 2, 1, 2, 1, b'b']).unwrap();
+    drop(file);
+    assert!(matches!(
+        DiskDag::<u32, String, String>::open(&path),
+        Err(DiskError::InvalidRecord(offset)) if offset == len
+    ));
+
+    std::fs::write(&path, b"not a dag").unwrap();
+    assert!(matches!(
+        DiskDag::<u32, String, String>::open(&path),
+        Err(DiskError::InvalidRecord(0))
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "wal")]
+fn write_ahead_log() {
+    use crate::{WalDag, WalError, WalOptions};
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("xdag-wal-{}.log", std::process::id()));
+    let snapshot = std::path::PathBuf::from(format!("{}.snapshot", path.display()));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&snapshot);
+
+    let mut dag = WalDag::<u32, String, u32>::open(&path).unwrap();
+    dag.insert_node(1, "a".to_string()).unwrap();
+    dag.insert_node(2, "b".to_string()).unwrap();
+    dag.insert_node(3, "c".to_string()).unwrap();
+    dag.insert_edge(1, 2, 12).unwrap();
+    dag.insert_edge(2, 3, 23).unwrap();
+    assert!(matches!(
+        dag.insert_edge(3, 1, 31),
+        Err(WalError::Dag(DagError::HasCycle(3, 1, 31, _)))
+    ));
+    assert!(matches!(
+        dag.remove_edge(1, 4),
+        Err(WalError::Dag(DagError::NodeNotFound(4)))
+    ));
+    assert_eq!(dag.remove_edge(1, 3).unwrap(), None);
+    assert_eq!(dag.remove_node(2).unwrap().0.as_deref(), Some("b"));
+    assert_eq!(dag.records_len(), 6);
+    let expected = dag.into_inner();
+    assert_eq!(Dag::recover(&path).unwrap(), expected);
+
+    // a torn record is dropped, then cut by opening
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(&[9, 0, 0, 0, 1])
+        .unwrap();
+    assert_eq!(Dag::recover(&path).unwrap(), expected);
+    let options = WalOptions {
+        checkpoint_every: 2,
+        ..WalOptions::default()
+    };
+    let mut dag = WalDag::<u32, String, u32>::open_with(&path, options).unwrap();
+    assert_eq!(*dag, expected);
+    assert_eq!(dag.records_len(), 6);
+    // the log is full, so a checkpoint is taken before this record
+    dag.insert_node(4, "d".to_string()).unwrap();
+    assert_eq!(dag.records_len(), 1);
+    assert!(snapshot.exists());
+    dag.insert_edge(1, 4, 14).unwrap();
+    assert_eq!(dag.records_len(), 2);
+    dag.sync().unwrap();
+    let expected = dag.into_inner();
+    assert_eq!(Dag::recover(&path).unwrap(), expected);
+
+    let mut dag = WalDag::<u32, String, u32>::open(&path).unwrap();
+    dag.clear().unwrap();
+    dag.checkpoint().unwrap();
+    assert_eq!(dag.records_len(), 0);
+    assert!(Dag::<u32, String, u32>::recover(&path).unwrap().is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&snapshot).unwrap();
+}
+
+#[test]
+fn three_way_merge() {
+    use crate::MergeConflict;
+
+    let mut base = Dag::new();
+    for (id, data) in [(1, "a"), (2, "b"), (3, "c"), (4, "d")] {
+        base.insert_node(id, data);
+    }
+    base.insert_edge(1, 2, 12).unwrap();
+    base.insert_edge(2, 3, 23).unwrap();
+
+    let mut ours = base.clone();
+    ours.insert_node(1, "A");
+    ours.insert_node(2, "B");
+    ours.remove_node(4);
+    ours.insert_node(5, "e");
+    ours.insert_edge(3, 5, 35).unwrap();
+    ours.insert_edge(1, 2, 120).unwrap();
+
+    let mut theirs = base.clone();
+    theirs.insert_node(2, "β");
+    theirs.insert_node(4, "δ");
+    theirs.remove_edge(2, 3).unwrap();
+    theirs.insert_edge(3, 1, 31).unwrap();
+    theirs.insert_edge(1, 2, 121).unwrap();
+    theirs.insert_node(6, "f");
+    theirs.insert_edge(6, 4, 64).unwrap();
+
+    let (merged, conflicts) = Dag::merge3(&base, &ours, &theirs);
+    assert_eq!(
+        merged.nodes().collect::<Vec<_>>(),
+        [
+            (1, &"A"),
+            (2, &"B"),
+            (3, &"c"),
+            (4, &"δ"),
+            (5, &"e"),
+            (6, &"f")
+        ]
+    );
+    // 3 -> 1 makes no cycle since 2 -> 3 is removed by theirs
+    assert_eq!(
+        merged.edges().collect::<Vec<_>>(),
+        [(1, 2, &120), (3, 1, &31), (3, 5, &35), (6, 4, &64)]
+    );
+    assert_eq!(
+        conflicts,
+        [
+            MergeConflict::Node {
+                id: 2,
+                base: Some("b"),
+                ours: Some("B"),
+                theirs: Some("β"),
+            },
+            MergeConflict::Node {
+                id: 4,
+                base: Some("d"),
+                ours: None,
+                theirs: Some("δ"),
+            },
+            MergeConflict::Edge {
+                from: 1,
+                to: 2,
+                base: Some(12),
+                ours: Some(120),
+                theirs: Some(121),
+            },
+        ]
+    );
+
+    let mut ours = base.clone();
+    ours.insert_edge(3, 4, 34).unwrap();
+    let mut theirs = base.clone();
+    theirs.insert_edge(4, 1, 41).unwrap();
+    let (merged, conflicts) = Dag::merge3(&base, &ours, &theirs);
+    assert_eq!(merged.edges_len(), 3);
+    assert_eq!(
+        conflicts,
+        [MergeConflict::Cycle {
+            from: 4,
+            to: 1,
+            data: 41,
+            path: Vec::from([1, 2, 3, 4]),
+        }]
+    );
+
+    let mut theirs = base.clone();
+    theirs.remove_node(3);
+    let mut ours = base.clone();
+    ours.insert_edge(3, 4, 34).unwrap();
+    let (_, conflicts) = Dag::merge3(&base, &ours, &theirs);
+    assert_eq!(
+        conflicts,
+        [MergeConflict::DanglingEdge {
+            from: 3,
+            to: 4,
+            data: 34,
+        }]
+    );
+}
+
+#[cfg(feature = "crdt")]
+#[test]
+fn crdt_replicas_converge() {
+    use crate::crdt::CrdtDag;
+
+    let mut a = CrdtDag::new(1);
+    a.insert_node(1, "1");
+    a.insert_node(2, "2");
+    let mut b = CrdtDag::new(2);
+    b.merge(&a);
+    a.insert_edge(1, 2, 12).unwrap();
+    b.insert_edge(2, 1, 21).unwrap();
+    assert!(matches!(
+        a.insert_edge(2, 1, 0),
+        Err(DagError::HasCycle(..))
+    ));
+
+    let mut ab = a.clone();
+    ab.merge(&b);
+    let mut ba = b.clone();
+    ba.merge(&a);
+    ba.merge(&a);
+    // the older edge wins, the other one is hidden
+    assert_eq!(
+        ab.edges().collect::<Vec<_>>(),
+        ba.edges().collect::<Vec<_>>()
+    );
+    assert_eq!(ab.edges_len(), 1);
+    assert_eq!(ab.hidden_edges().count(), 1);
+
+    // the hidden edge shows once the cycle is broken
+    let (from, to) = ab.edges().map(|(from, to, _)| (from, to)).next().unwrap();
+    assert!(ab.remove_edge(from, to));
+    assert!(ab.contains_edge(&to, &from));
+    assert_eq!(ab.hidden_edges().count(), 0);
+
+    // a removal wins over older updates
+    assert!(ba.remove_node(1));
+    ba.merge(&a);
+    assert!(!ba.contains_node(&1));
+    assert!(!ba.remove_node(1));
+}
+
+#[test]
+fn map_ids() {
+    use crate::CollisionError;
+
+    let mut dag = Dag::new();
+    for id in ["a", "b", "c", "d"] {
+        dag.insert_node(id, id.len());
+    }
+    dag.insert_edge("a", "b", 1).unwrap();
+    dag.insert_edge("b", "c", 2).unwrap();
+    dag.insert_edge("a", "d", 3).unwrap();
+
+    let dense_id = |id: &&str| id.as_bytes()[0] - b'a';
+    let order = dag.topological_order();
+    let mut dense = dag.clone().map_ids(dense_id).unwrap();
+    assert_eq!(dense.nodes_len(), 4);
+    assert_eq!(dense.edges_len(), 3);
+    assert_eq!(dense.get_edge(&1, &2), Ok(Some(&2)));
+    assert_eq!(dense.parents(&3).collect::<Vec<_>>(), [0]);
+    assert_eq!(
+        dense.topological_order(),
+        order.iter().map(dense_id).collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        dense.insert_edge(2, 0, 0),
+        Err(DagError::HasCycle(..))
+    ));
+
+    assert_eq!(
+        dag.map_ids(|id| *id == "a" || *id == "c").unwrap_err(),
+        CollisionError("a", "c", true)
+    );
+}
+
+#[test]
+fn branded_ids() {
+    let mut dag = Dag::new();
+    for id in 1..=3 {
+        dag.insert_node(id, id * 10);
+    }
+    dag.insert_edge(1, 2, 12).unwrap();
+    dag.insert_edge(1, 3, 13).unwrap();
+
+    let sum = dag.with_brand(|dag| {
+        assert!(dag.id(&4).is_none());
+        let root = dag.id(&1).unwrap();
+        let children = dag.children(&root).collect::<Vec<_>>();
+        assert_eq!(dag.get_edge(&root, &children[0].0), Some(&12));
+        assert_eq!(dag.get_edge(&children[0].0, &root), None);
+        assert_eq!(
+            dag.parents(&children[1].0)
+                .map(|id| *id.id())
+                .collect::<Vec<_>>(),
+            [1]
+        );
+        children.iter().map(|(id, _)| dag.get_node(id)).sum::<i32>()
+    });
+    assert_eq!(sum, 50);
+}
+
+#[test]
+fn paths_to() {
+    let mut dag = Dag::new();
+    for id in 1..=5 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(1, 4, ()).unwrap();
+    dag.insert_edge(3, 4, ()).unwrap();
+
+    let mut paths = dag.paths_to(&4).collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, [vec![1, 3, 4], vec![1, 4], vec![2, 3, 4]]);
+    assert_eq!(dag.paths_to(&1).collect::<Vec<_>>(), [vec![1]]);
+    assert_eq!(dag.paths_to(&5).collect::<Vec<_>>(), [vec![5]]);
+    assert_eq!(dag.paths_to(&6).count(), 0);
+}
+
+#[test]
+fn depth_and_height_maps() {
+    let mut dag = Dag::new();
+    for id in 1..=6 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(2, 3, ()).unwrap();
+    dag.insert_edge(1, 3, ()).unwrap();
+    dag.insert_edge(4, 3, ()).unwrap();
+    dag.insert_edge(3, 5, ()).unwrap();
+
+    let depths = dag.depth_map();
+    let heights = dag.height_map();
+    assert_eq!(
+        depths.values().copied().collect::<Vec<_>>(),
+        [0, 1, 2, 0, 3, 0]
+    );
+    assert_eq!(
+        heights.values().copied().collect::<Vec<_>>(),
+        [3, 2, 1, 2, 0, 0]
+    );
+    for id in 1..=6 {
+        assert_eq!(dag.depth(&id), Some(depths[&id]));
+        assert_eq!(dag.height(&id), Some(heights[&id]));
+    }
+}
+
+#[test]
+fn dot_export() {
+    use crate::dot::{Attributes, DotConfig};
+
+    let mut dag = Dag::new();
+    dag.insert_node(1, "done");
+    dag.insert_node(2, "failed");
+    dag.insert_node(3, "say \"hi\"");
+    dag.insert_edge(1, 2, 5).unwrap();
+    dag.insert_edge(1, 3, 7).unwrap();
+
+    assert_eq!(
+        dag.to_dot(),
+        "digraph {\n    \"1\";\n    \"2\";\n    \"3\";\n    \"1\" -> \"2\";\n    \"1\" -> \"3\";\n}\n"
+    );
+
+    let output = dag.to_dot_with(
+        DotConfig::new()
+            .name("jobs")
+            .rankdir("LR")
+            .node_default("shape", "box")
+            .edge_default("color", "gray")
+            .node_attributes(|_, status: &&str| {
+                let mut attrs = Attributes::from([("label".into(), status.to_string())]);
+                if *status == "failed" {
+                    attrs.insert("color".into(), "red".into());
+                }
+                attrs
+            })
+            .edge_attributes(|_, _, weight: &i32| {
+                Attributes::from([("weight".into(), weight.to_string())])
+            }),
+    );
+    assert!(output.starts_with("digraph \"jobs\" {\n    \"rankdir\"=\"LR\";\n"));
+    assert!(output.contains("    \"2\" [\"color\"=\"red\", \"label\"=\"failed\"];\n"));
+
+    let parsed = crate::dot::parse(&output).unwrap();
+    assert_eq!(parsed.nodes_len(), 3);
+    assert_eq!(parsed.get_node("3").unwrap()["label"], "say \"hi\"");
+    assert_eq!(parsed.get_node("1").unwrap()["shape"], "box");
+    let edge = parsed.get_edge("1", "3").unwrap().unwrap();
+    assert_eq!(edge["weight"], "7");
+    assert_eq!(edge["color"], "gray");
+}
+
+#[test]
+fn dot_round_trip_escapes() {
+    use crate::dot::{Attributes, DotConfig};
+    use alloc::string::{String, ToString};
+
+    let ids = ["C:\\dir\\", "say \"hi\"", "\\\"", "plain"];
+    let mut dag = Dag::new();
+    for id in ids {
+        dag.insert_node(id.to_string(), ());
+    }
+    dag.insert_edge(ids[0].to_string(), ids[1].to_string(), ())
+        .unwrap();
+    dag.insert_edge(ids[1].to_string(), ids[2].to_string(), ())
+        .unwrap();
+
+    let output =
+        dag.to_dot_with(DotConfig::new().node_attributes(|id: &String, _: &()| {
+            Attributes::from([("label".into(), id.clone())])
+        }));
+    assert!(output.contains("\"C:\\\\dir\\\\\""));
+
+    let parsed = crate::dot::parse(&output).unwrap();
+    assert_eq!(parsed.nodes_len(), ids.len());
+    for id in ids {
+        assert_eq!(parsed.get_node(id).unwrap()["label"], id);
+    }
+    assert!(parsed.get_edge(ids[0], ids[1]).unwrap().is_some());
+    assert!(parsed.get_edge(ids[1], ids[2]).unwrap().is_some());
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn random_topological_order() {
+    use alloc::collections::BTreeSet;
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    let mut dag = Dag::new();
+    for id in 1..=4 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(1, 2, ()).unwrap();
+    dag.insert_edge(1, 3, ()).unwrap();
+
+    let all = dag.all_topological_orders().collect::<BTreeSet<_>>();
+    let mut rng = XorShiftRng::seed_from_u64(7);
+    let mut sampled = BTreeSet::new();
+    for _ in 0..200 {
+        let order = dag.random_topological_order(&mut rng);
+        assert!(all.contains(&order));
+        sampled.insert(order);
+    }
+    assert_eq!(sampled, all);
+
+    let order = dag.random_topological_order(&mut XorShiftRng::seed_from_u64(1));
+    assert_eq!(
+        order,
+        dag.random_topological_order(&mut XorShiftRng::seed_from_u64(1))
+    );
+}
+
+#[test]
+fn series_parallel_decomposition() {
+    use crate::algo::SeriesParallel::{Node, Parallel, Series};
+
+    let dag_of = |edges: &[(i32, i32)], len: i32| {
+        let mut dag = Dag::new();
+        for id in 1..=len {
+            dag.insert_node(id, ());
+        }
+        for (from, to) in edges {
+            dag.insert_edge(*from, *to, ()).unwrap();
+        }
+        dag
+    };
+
+    // 1 -> (2 | 3 -> 4) -> 5, with 6 alone and a redundant edge 1 -> 5
+    let dag = dag_of(&[(1, 2), (1, 3), (3, 4), (2, 5), (4, 5), (1, 5)], 6);
+    let expected = Parallel(vec![
+        Series(vec![
+            Node(1),
+            Parallel(vec![Node(2), Series(vec![Node(3), Node(4)])]),
+            Node(5),
+        ]),
+        Node(6),
+    ]);
+    assert_eq!(dag.series_parallel_decomposition(), Some(expected));
+
+    // the "N": 1 -> 3, 1 -> 4, 2 -> 4
+    let dag = dag_of(&[(1, 3), (1, 4), (2, 4)], 4);
+    assert_eq!(dag.series_parallel_decomposition(), None);
+
+    assert_eq!(
+        dag_of(&[], 1).series_parallel_decomposition(),
+        Some(Node(1))
+    );
+    assert_eq!(
+        dag_of(&[], 0).series_parallel_decomposition(),
+        Some(Parallel(Vec::new()))
+    );
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn csv_import() {
+    use crate::csv::CsvError;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Task {
+        name: String,
+        cost: u32,
+    }
+
+    let nodes = "id,name,cost\n1,build,3\n2,test,5\n3,deploy,1\n";
+    let edges = "from,to,kind\n1,2,hard\n2,3,soft\n1,3,hard\n";
+    let dag = Dag::<u32, Task, String>::from_csv(nodes.as_bytes(), edges.as_bytes()).unwrap();
+    assert_eq!(
+        dag.get_node(&2),
+        Some(&Task {
+            name: "test".into(),
+            cost: 5
+        })
+    );
+    assert_eq!(dag.get_edge(&2, &3), Ok(Some(&"soft".to_string())));
+    assert_eq!(dag.edges_len(), 3);
+
+    let dag = Dag::<String, (), ()>::from_csv_edges("from,to\na,b\nb,c\n".as_bytes()).unwrap();
+    assert_eq!(dag.nodes_len(), 3);
+
+    let result = Dag::<u32, Task, String>::from_csv(
+        nodes.as_bytes(),
+        "from,to,kind\n1,2,hard\n2,3,soft\n3,1,soft\n".as_bytes(),
+    );
+    match result {
+        Err(CsvError::Cycle {
+            line,
+            cycle_lines,
+            error: DagError::HasCycle(3, 1, _, _),
+        }) => {
+            assert_eq!(line, 4);
+            assert_eq!(cycle_lines, [2, 3]);
+        }
+        _ => unreachable!(),
+    }
+    assert!(matches!(
+        Dag::<u32, Task, String>::from_csv(nodes.as_bytes(), "from,to,kind\n1,4,hard\n".as_bytes()),
+        Err(CsvError::Dag {
+            line: 2,
+            error: DagError::NodeNotFound(4)
+        })
+    ));
+    assert!(matches!(
+        Dag::<u32, Task, String>::from_csv(
+            "id,name,cost\n1,a,1\n1,b,2\n".as_bytes(),
+            "from,to,kind\n".as_bytes()
+        ),
+        Err(CsvError::Dag {
+            line: 3,
+            error: DagError::DuplicateNode(1)
+        })
+    ));
+    assert!(matches!(
+        Dag::<u32, Task, String>::from_csv(
+            nodes.as_bytes(),
+            "from,to,kind\n1,2,a\n1,2,b\n".as_bytes()
+        ),
+        Err(CsvError::DuplicateEdge {
+            line: 3,
+            first_line: 2,
+            from: 1,
+            to: 2
+        })
+    ));
+    match Dag::<u32, Task, String>::from_csv("id,name,cost\n1,a,x\n".as_bytes(), "".as_bytes()) {
+        Err(CsvError::Nodes(error)) => {
+            assert_eq!(error.position().map(|position| position.line()), Some(2))
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_save_and_load() {
+    use crate::sqlite::SqliteError;
+    use rusqlite::Connection;
+
+    let mut dag = Dag::new();
+    for id in 1..=3 {
+        dag.insert_node(id, format!("node {}", id));
+    }
+    dag.insert_edge(1, 2, vec![1.5]).unwrap();
+    dag.insert_edge(2, 3, Vec::new()).unwrap();
+
+    let mut connection = Connection::open_in_memory().unwrap();
+    dag.save_sqlite(&mut connection).unwrap();
+    let loaded = Dag::<u32, String, Vec<f64>>::load_sqlite(&connection).unwrap();
+    assert_eq!(loaded, dag);
+
+    // saving again replaces all the rows
+    dag.remove_node(3);
+    dag.save_sqlite(&mut connection).unwrap();
+    let loaded = Dag::<u32, String, Vec<f64>>::load_sqlite(&connection).unwrap();
+    assert_eq!((loaded.nodes_len(), loaded.edges_len()), (2, 1));
+
+    connection
+        .execute("INSERT INTO edges VALUES ('2', '1', '[]')", [])
+        .unwrap();
+    assert!(matches!(
+        Dag::<u32, String, Vec<f64>>::load_sqlite(&connection),
+        Err(SqliteError::Dag(DagError::HasCycle(..)))
+    ));
+    connection
+        .execute("UPDATE edges SET from_id = '4' WHERE from_id = '2'", [])
+        .unwrap();
+    assert!(matches!(
+        Dag::<u32, String, Vec<f64>>::load_sqlite(&connection),
+        Err(SqliteError::Dag(DagError::NodeNotFound(4)))
+    ));
+    assert!(matches!(
+        Dag::<String, String, Vec<f64>>::load_sqlite(&connection),
+        Err(SqliteError::Json(_))
+    ));
+    assert!(matches!(
+        Dag::<u32, String, Vec<f64>>::load_sqlite(&Connection::open_in_memory().unwrap()),
+        Err(SqliteError::Sqlite(_))
+    ));
+}
+
+#[test]
+fn edges_of_sparse_dag() {
+    let mut dag = Dag::new();
+    for id in 0..200_000 {
+        dag.insert_node(id, ());
+    }
+    dag.insert_edge(199_998, 199_999, ()).unwrap();
+    assert_eq!(dag.edges().count(), 1);
+    assert_eq!(dag.edges_mut().count(), 1);
+}
+
+#[test]
+fn reorder_reuses_scratch() {
+    // every edge goes backward in the insertion order, so every insertion reorders
+    let mut dag = Dag::new();
+    for id in 0..50 {
+        dag.insert_node(id, ());
+    }
+    for id in 1..50 {
+        dag.insert_edge(id, id - 1, ()).unwrap();
+        assert!(matches!(
+            dag.insert_edge(0, id, ()),
+            Err(DagError::HasCycle(..))
+        ));
+    }
+    let order = dag.topological_order();
+    assert_eq!(order, (0..50).rev().collect::<Vec<_>>());
+    let mut cloned = dag.clone();
+    cloned.insert_edge(49, 0, ()).unwrap();
+    assert!(matches!(
+        cloned.insert_edge(0, 49, ()),
+        Err(DagError::HasCycle(..))
+    ));
+}
+
+#[cfg(feature = "crdt")]
+#[test]
+fn crdt_replacing_edge_data_converges() {
+    use crate::crdt::CrdtDag;
+
+    let mut a = CrdtDag::new(1);
+    for id in 1..=3 {
+        a.insert_node(id, ());
+    }
+    let mut b = CrdtDag::new(2);
+    b.merge(&a);
+    b.insert_edge(3, 1, "b").unwrap();
+    a.insert_edge(1, 2, "a").unwrap();
+    a.insert_edge(2, 3, "a").unwrap();
+    a.merge(&b);
+    let visible = |replica: &CrdtDag<i32, (), &str>| {
+        replica
+            .edges()
+            .map(|(from, to, _)| (from, to))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(visible(&a), [(1, 2), (3, 1)]);
+
+    // 1 -> 2 would be the newest, so 2 -> 3 wins the cycle
+    let before = visible(&a);
+    assert!(matches!(
+        a.insert_edge(1, 2, "replaced"),
+        Err(DagError::HasCycle(1, 2, "replaced", _))
+    ));
+    assert_eq!(visible(&a), before);
+    assert_eq!(a.get_edge(&1, &2), Ok(Some(&"a")));
+    let mut fresh = CrdtDag::new(3);
+    fresh.merge(&a);
+    assert_eq!(visible(&fresh), visible(&a));
+
+    // replacing an edge NOT on a cycle
+    a.insert_node(4, ());
+    a.insert_edge(1, 4, "a").unwrap();
+    a.insert_edge(1, 4, "replaced").unwrap();
+    let mut fresh = CrdtDag::new(3);
+    fresh.merge(&a);
+    assert_eq!(visible(&fresh), visible(&a));
+    assert_eq!(fresh.get_edge(&1, &4), Ok(Some(&"replaced")));
+}
+
+#[cfg(feature = "crdt")]
+#[test]
+fn crdt_updates_match_rebuild() {
+    use crate::crdt::CrdtDag;
+
+    // a removed node breaks the cycle of a hidden edge, though its edges are live
+    let mut a = CrdtDag::new(1);
+    for id in 1..=3 {
+        a.insert_node(id, ());
+    }
+    let mut b = CrdtDag::new(2);
+    b.merge(&a);
+    let mut c = CrdtDag::new(3);
+    c.merge(&a);
+    a.insert_edge(1, 2, ()).unwrap();
+    b.insert_edge(3, 1, ()).unwrap();
+    a.insert_edge(2, 3, ()).unwrap();
+    a.merge(&b);
+    assert_eq!(a.hidden_edges().collect::<Vec<_>>(), [(2, 3)]);
+    assert!(c.remove_node(1));
+    a.merge(&c);
+    assert!(a.contains_edge(&2, &3));
+
+    let mut replicas = [CrdtDag::new(0), CrdtDag::new(1), CrdtDag::new(2)];
+    let mut seed = 7u64;
+    let mut next = |bound: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+    for step in 0..4000 {
+        let index = next(3) as usize;
+        let (from, to) = (next(4), next(4));
+        let replica = &mut replicas[index];
+        match next(8) {
+            0 | 1 => replica.insert_node(from, step),
+            2..=4 => {
+                let _ = replica.insert_edge(from, to, step);
+            }
+            5 => {
+                replica.remove_edge(from, to);
+            }
+            6 => {
+                replica.remove_node(from);
+            }
+            _ => {
+                let other = replicas[next(3) as usize].clone();
+                replicas[index].merge(&other);
+            }
+        }
+        // a replica NOT updated in place derives the same `Dag` from the state
+        let replica = &replicas[index];
+        let mut rebuilt = CrdtDag::new(3);
+        rebuilt.merge(replica);
+        assert_eq!(**replica, *rebuilt, "step {}", step);
+    }
+}
+
+#[test]
+fn order_is_compacted_after_churn() {
+    let mut dag = Dag::new();
+    for id in 0..100 {
+        dag.insert_node(id, ());
+    }
+    for id in 1..100 {
+        dag.insert_edge(id - 1, id, ()).unwrap();
+    }
+    // positions are handed out on every insertion, and freed ones are NOT reused
+    for id in 100..100_000 {
+        dag.insert_node(id, ());
+        dag.remove_node(id);
+    }
+    dag.insert_node(100, ());
+    assert!(dag.order.next <= 2 * dag.nodes_len() + 64);
+    assert!(dag
+        .order
+        .positions
+        .values()
+        .all(|position| *position < dag.order.next));
+
+    // a back edge in the order visits nodes by the gap between their positions
+    dag.insert_edge(100, 0, ()).unwrap();
+    assert!(matches!(
+        dag.insert_edge(99, 100, ()),
+        Err(DagError::HasCycle(99, 100, (), _))
+    ));
+    let order = dag.topological_order();
+    assert_eq!(order[0], 100);
+    assert_eq!(order[1..], (0..100).collect::<Vec<_>>());
+}
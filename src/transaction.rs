@@ -0,0 +1,184 @@
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use crate::{Dag, DagError};
+
+/// Staged mutations of a `Dag`, created by [`Dag::transaction`]
+/// # Remarks
+/// * Reads go to `Dag` by `Deref` and see the staged mutations
+/// * Removed data is kept to roll back, so removing methods don't return it
+/// * It rolls back when dropped without being committed, so a panic in
+///   [`Dag::transaction`] rolls back too
+pub struct Transaction<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    dag: &'a mut Dag<NodeId, NodeData, EdgeData>,
+    undo: Vec<Undo<NodeId, NodeData, EdgeData>>,
+}
+
+enum Undo<NodeId, NodeData, EdgeData> {
+    InsertNode(NodeId, Option<NodeData>),
+    InsertEdge(NodeId, NodeId, Option<EdgeData>),
+    RemoveNode(NodeId, NodeData),
+    RemoveEdge(NodeId, NodeId, EdgeData),
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Run `f` on a [`Transaction`], its mutations are kept only if `f` returns `Ok`
+    /// # Remarks
+    /// * On `Err` or when `f` panics, all the mutations are rolled back in reverse order
+    ///   and `Dag` is equal to the one before
+    /// * Nothing is cloned, an undo log is recorded instead
+    pub fn transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction<'_, NodeId, NodeData, EdgeData>) -> Result<T, E>,
+    {
+        let mut txn = Transaction {
+            dag: self,
+            undo: Vec::new(),
+        };
+        let result = f(&mut txn);
+        if result.is_ok() {
+            txn.commit();
+        }
+        result
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Transaction<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Insert a node with data
+    /// # Returns
+    /// * Return `true` when `node_id` is already in `Dag` and its data is replaced
+    pub fn insert_node(&mut self, node_id: NodeId, node_data: NodeData) -> bool {
+        let old = self.dag.insert_node(node_id.clone(), node_data);
+        let replaced = old.is_some();
+        self.undo.push(Undo::InsertNode(node_id, old));
+        replaced
+    }
+
+    /// Insert an edge with data
+    /// # Returns
+    /// * Return `Ok(true)` when there is a same edge in `Dag` and its data is replaced
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
+    /// * `Err(HasCycle(from,to,data,path))` when a cycle is detected
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<bool, DagError<NodeId, EdgeData>> {
+        let old = self.dag.insert_edge(from.clone(), to.clone(), edge_data)?;
+        let replaced = old.is_some();
+        self.undo.push(Undo::InsertEdge(from, to, old));
+        Ok(replaced)
+    }
+
+    /// Remove an edge
+    /// # Returns
+    /// * Return `Ok(true)` when success
+    /// * Return `Ok(false)` when there is no such edge
+    /// # Errors
+    /// * `Err(NodeNotFound(id))` when `from` or `to` is NOT found in `Dag`
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<bool, DagError<NodeId, EdgeData>> {
+        match self.dag.remove_edge(from.clone(), to.clone())? {
+            Some(data) => {
+                self.undo.push(Undo::RemoveEdge(from, to, data));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Remove a node and all edges related
+    /// # Returns
+    /// * Return `false` when `node_id` is NOT found in `Dag`
+    pub fn remove_node(&mut self, node_id: NodeId) -> bool {
+        if !self.dag.nodes.contains_key(&node_id) {
+            return false;
+        }
+        let (data, edges) = self.dag.remove_node(node_id.clone());
+        let data = data.unwrap_or_else(|| unreachable!("proved by contains_node"));
+        // edges are restored after the node
+        for (from, to, edge_data) in edges {
+            self.undo.push(Undo::RemoveEdge(from, to, edge_data));
+        }
+        self.undo.push(Undo::RemoveNode(node_id, data));
+        true
+    }
+
+    /// Keep all the mutations, nothing is rolled back when dropped
+    fn commit(mut self) {
+        self.undo.clear();
+    }
+
+    /// Undo all the mutations in reverse order
+    fn rollback(&mut self) {
+        while let Some(undo) = self.undo.pop() {
+            match undo {
+                Undo::InsertNode(node_id, Some(old)) => {
+                    self.dag.insert_node(node_id, old);
+                }
+                Undo::InsertNode(node_id, None) => {
+                    // edges inserted later were rolled back already
+                    self.dag.remove_node(node_id);
+                }
+                Undo::InsertEdge(from, to, Some(old)) => {
+                    let data = self
+                        .dag
+                        .get_edge_mut(&from, &to)
+                        .ok()
+                        .flatten()
+                        .unwrap_or_else(|| unreachable!("the edge was inserted"));
+                    *data = old;
+                }
+                Undo::InsertEdge(from, to, None) => {
+                    self.dag
+                        .remove_edge(from, to)
+                        .unwrap_or_else(|_| unreachable!("the edge was inserted"));
+                }
+                Undo::RemoveNode(node_id, data) => {
+                    self.dag.insert_node(node_id, data);
+                }
+                Undo::RemoveEdge(from, to, data) => {
+                    // the edge was in an acyclic `Dag` before
+                    if self.dag.insert_edge(from, to, data).is_err() {
+                        unreachable!("restoring an edge can't make a cycle")
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Drop for Transaction<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    fn drop(&mut self) {
+        // NOT committed: `f` returned `Err` or panicked
+        self.rollback();
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Deref for Transaction<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        self.dag
+    }
+}
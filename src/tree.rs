@@ -0,0 +1,91 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{Dag, MultipleParents};
+
+/// A tree projected from `Dag`, created by [`Dag::spanning_forest`] or [`Dag::to_tree`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tree<NodeId> {
+    /// Id of the node
+    pub id: NodeId,
+    /// Subtrees of the children, in the order of `NodeId`
+    pub children: Vec<Tree<NodeId>>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Project `Dag` into a forest by keeping one parent of every node
+    /// # Returns
+    /// Return one tree per root in the order of `NodeId`, every node is in exactly one tree
+    /// # Remarks
+    /// * `choose_parent(id, parents)` picks the index of the parent kept for a node with
+    ///   more than one parent, `parents` are in the order of `NodeId`
+    /// # Panics
+    /// Panics if `choose_parent` returns an index out of `parents`
+    pub fn spanning_forest<F>(&self, mut choose_parent: F) -> Vec<Tree<NodeId>>
+    where
+        F: FnMut(&NodeId, &[NodeId]) -> usize,
+    {
+        self.forest(|node_id, parents| Ok::<_, ()>(choose_parent(node_id, parents)))
+            .unwrap_or_else(|_| unreachable!("choose_parent never fails"))
+    }
+
+    /// Convert `Dag` into a forest, if every node has at most one parent
+    /// # Returns
+    /// Return `Ok(trees)` with one tree per root in the order of `NodeId`
+    /// # Errors
+    /// * `Err(MultipleParents(id, parents))` when a node has more than one parent
+    pub fn to_tree(&self) -> Result<Vec<Tree<NodeId>>, MultipleParents<NodeId>> {
+        self.forest(|node_id, parents| Err(MultipleParents(node_id.clone(), parents.to_vec())))
+    }
+
+    fn forest<E, F>(&self, mut choose_parent: F) -> Result<Vec<Tree<NodeId>>, E>
+    where
+        F: FnMut(&NodeId, &[NodeId]) -> Result<usize, E>,
+    {
+        // children kept in the forest, in the order of `NodeId`
+        let mut tree_children = BTreeMap::<NodeId, Vec<NodeId>>::new();
+        for (node_id, parents) in &self.back_edges {
+            let parent_id = match parents.len() {
+                0 => continue,
+                1 => parents
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| unreachable!("proved by len")),
+                _ => {
+                    let mut parents = parents.iter().cloned().collect::<Vec<_>>();
+                    let index = choose_parent(node_id, &parents)?;
+                    parents.swap_remove(index)
+                }
+            };
+            tree_children
+                .entry(parent_id)
+                .or_default()
+                .push(node_id.clone());
+        }
+        // subtrees are built bottom-up, a kept parent is always before its children
+        let mut subtrees = BTreeMap::new();
+        for node_id in self.topological_order().into_iter().rev() {
+            let children = tree_children
+                .remove(&node_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|child_id| {
+                    subtrees
+                        .remove(&child_id)
+                        .unwrap_or_else(|| unreachable!("children are built before"))
+                })
+                .collect();
+            subtrees.insert(
+                node_id.clone(),
+                Tree {
+                    id: node_id,
+                    children,
+                },
+            );
+        }
+        // only the subtrees of roots are left
+        Ok(subtrees.into_values().collect())
+    }
+}
@@ -0,0 +1,295 @@
+use core::borrow::Borrow;
+
+use crate::Dag;
+
+/// A borrowed view of `Dag` which hides the nodes NOT matching a predicate
+/// # Remarks
+/// * Edges from or to a hidden node are hidden too
+/// * Nothing is cloned, all queries go to the underlying `Dag`
+pub struct DagView<'a, NodeId, NodeData, EdgeData, F> {
+    dag: &'a Dag<NodeId, NodeData, EdgeData>,
+    predicate: F,
+}
+
+impl<'a, NodeId, NodeData, EdgeData, F> DagView<'a, NodeId, NodeData, EdgeData, F>
+where
+    NodeId: Clone + Ord,
+    F: Fn(NodeId, &NodeData) -> bool,
+{
+    /// Create a view of `dag` which only shows the nodes matching `predicate`
+    pub fn new(dag: &'a Dag<NodeId, NodeData, EdgeData>, predicate: F) -> Self {
+        DagView { dag, predicate }
+    }
+
+    /// Get the underlying `Dag`
+    pub fn dag(&self) -> &'a Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Check if a `node_id` is visible in `DagView`
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_node(node_id).is_some()
+    }
+
+    /// Check if an `edge` is visible in `DagView`
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_edge(from, to).is_some()
+    }
+
+    /// Check a visible node is root in `DagView`
+    pub fn is_root<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.parents(node_id).next().is_none()
+    }
+
+    /// Get data from a visible node
+    /// # Returns
+    /// Return `None` if `node_id` is not found or hidden
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<&'a NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag
+            .nodes
+            .get_key_value(node_id)
+            .filter(|(id, data)| (self.predicate)((*id).clone(), data))
+            .map(|(_, data)| data)
+    }
+
+    /// Get data from a visible edge
+    /// # Returns
+    /// Return `None` if the edge is not found or hidden
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Option<&'a EdgeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if !self.contains_node(from) || !self.contains_node(to) {
+            return None;
+        }
+        self.dag
+            .edges
+            .get(from)
+            .and_then(|children| children.get(to))
+    }
+
+    /// Get an iterator of all the visible children of given `node_id`
+    pub fn children<Q>(&self, node_id: &Q) -> impl Iterator<Item = (NodeId, &'a EdgeData)> + '_
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let visible = self.contains_node(node_id);
+        self.dag
+            .children(node_id)
+            .filter(move |(id, _)| visible && self.contains_node::<NodeId>(id))
+    }
+
+    /// Get an iterator of all the visible parents of given `node_id`
+    pub fn parents<Q>(&self, node_id: &Q) -> impl Iterator<Item = NodeId> + '_
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let visible = self.contains_node(node_id);
+        self.dag
+            .parents(node_id)
+            .filter(move |id| visible && self.contains_node::<NodeId>(id))
+    }
+
+    /// Get all the visible nodes in `DagView`
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &'a NodeData)> + '_ {
+        self.dag
+            .nodes()
+            .filter(move |(id, data)| (self.predicate)(id.clone(), data))
+    }
+
+    /// Get the count of visible nodes
+    pub fn nodes_len(&self) -> usize {
+        self.nodes().count()
+    }
+
+    /// Get all the visible edges in `DagView`
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &'a EdgeData)> + '_ {
+        self.dag
+            .edges()
+            .filter(move |(from, to, _)| self.contains_node(from) && self.contains_node(to))
+    }
+
+    /// Get all the leaves in `DagView`
+    pub fn leaves(&self) -> impl Iterator<Item = (NodeId, &'a NodeData)> + '_ {
+        self.nodes()
+            .filter(move |(id, _)| self.children(id).next().is_none())
+    }
+
+    /// Get all the roots in `DagView`
+    pub fn roots(&self) -> impl Iterator<Item = (NodeId, &'a NodeData)> + '_ {
+        self.nodes()
+            .filter(move |(id, _)| self.parents(id).next().is_none())
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData, F> Clone for DagView<'a, NodeId, NodeData, EdgeData, F>
+where
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        DagView {
+            dag: self.dag,
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get a borrowed view which only shows the nodes matching `predicate`
+    pub fn filtered<F>(&self, predicate: F) -> DagView<'_, NodeId, NodeData, EdgeData, F>
+    where
+        F: Fn(NodeId, &NodeData) -> bool,
+    {
+        DagView::new(self, predicate)
+    }
+
+    /// Get a borrowed view with all the edges reversed
+    pub fn transposed(&self) -> Transposed<'_, NodeId, NodeData, EdgeData> {
+        Transposed { dag: self }
+    }
+}
+
+/// A borrowed view of `Dag` with all the edges reversed
+/// # Remarks
+/// * `children` and `parents` are swapped, nothing is cloned
+/// * An edge `from -> to` in `Dag` is `to -> from` in `Transposed`
+pub struct Transposed<'a, NodeId, NodeData, EdgeData> {
+    dag: &'a Dag<NodeId, NodeData, EdgeData>,
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Transposed<'a, NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Get the underlying `Dag`
+    pub fn dag(&self) -> &'a Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Check if a `node_id` is contained in `Transposed`
+    pub fn contains_node<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.contains_node(node_id)
+    }
+
+    /// Check if a reversed `edge` is contained in `Transposed`
+    pub fn contains_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.contains_edge(to, from)
+    }
+
+    /// Check a node is root in `Transposed`, which means it is a leaf in `Dag`
+    pub fn is_root<Q>(&self, node_id: &Q) -> bool
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.children(node_id).len() == 0
+    }
+
+    /// Get data from node
+    /// # Returns
+    /// Return `None` if `node_id` is not found
+    pub fn get_node<Q>(&self, node_id: &Q) -> Option<&'a NodeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.get_node(node_id)
+    }
+
+    /// Get data from a reversed edge
+    /// # Returns
+    /// Return `None` if the edge is not found
+    pub fn get_edge<Q>(&self, from: &Q, to: &Q) -> Option<&'a EdgeData>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag
+            .edges
+            .get(to)
+            .and_then(|children| children.get(from))
+    }
+
+    /// Get an iterator of all the children of given `node_id`,
+    /// which are the parents in `Dag`
+    pub fn children<Q>(&self, node_id: &Q) -> impl Iterator<Item = (NodeId, &'a EdgeData)>
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.parent_edges(node_id)
+    }
+
+    /// Get an iterator of all the parents of given `node_id`,
+    /// which are the children in `Dag`
+    pub fn parents<Q>(&self, node_id: &Q) -> impl Iterator<Item = NodeId> + 'a
+    where
+        NodeId: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.dag.children(node_id).map(|(id, _)| id)
+    }
+
+    /// Get all the nodes in `Transposed`
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &'a NodeData)> {
+        self.dag.nodes()
+    }
+
+    /// Get the count of nodes
+    pub fn nodes_len(&self) -> usize {
+        self.dag.nodes_len()
+    }
+
+    /// Get all the reversed edges in `Transposed`
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &'a EdgeData)> {
+        self.dag.edges().map(|(from, to, data)| (to, from, data))
+    }
+
+    /// Get all the leaves in `Transposed`, which are the roots in `Dag`
+    pub fn leaves(&self) -> impl Iterator<Item = (NodeId, &'a NodeData)> {
+        self.dag.roots()
+    }
+
+    /// Get all the roots in `Transposed`, which are the leaves in `Dag`
+    pub fn roots(&self) -> impl Iterator<Item = (NodeId, &'a NodeData)> {
+        self.dag.leaves()
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Clone for Transposed<'a, NodeId, NodeData, EdgeData> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, NodeId, NodeData, EdgeData> Copy for Transposed<'a, NodeId, NodeData, EdgeData> {}
@@ -0,0 +1,458 @@
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Dag, DagError, NodeNotFound};
+
+/// Options of [`WalDag`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalOptions {
+    /// Count of records in the log to take a checkpoint automatically,
+    /// `0` means never, default to `10000`
+    pub checkpoint_every: usize,
+    /// Sync the log to the disk after every record, which survives power loss
+    /// but is much slower, default to `false`
+    pub sync: bool,
+}
+
+impl Default for WalOptions {
+    fn default() -> Self {
+        WalOptions {
+            checkpoint_every: 10000,
+            sync: false,
+        }
+    }
+}
+
+/// Errors of [`WalDag`]
+#[derive(Debug)]
+pub enum WalError<NodeId, EdgeData> {
+    /// Reading or writing the files failed
+    Io(io::Error),
+    /// A snapshot or a record can't be encoded or decoded by postcard
+    Encoding(postcard::Error),
+    /// The record at the offset of the log can't be applied, the log is corrupted
+    InvalidRecord(u64),
+    /// The mutation is invalid, see [`DagError`]
+    Dag(DagError<NodeId, EdgeData>),
+}
+
+impl<NodeId, EdgeData> From<io::Error> for WalError<NodeId, EdgeData> {
+    fn from(error: io::Error) -> Self {
+        WalError::Io(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<postcard::Error> for WalError<NodeId, EdgeData> {
+    fn from(error: postcard::Error) -> Self {
+        WalError::Encoding(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<DagError<NodeId, EdgeData>> for WalError<NodeId, EdgeData> {
+    fn from(error: DagError<NodeId, EdgeData>) -> Self {
+        WalError::Dag(error)
+    }
+}
+
+impl<NodeId, EdgeData> From<NodeNotFound<NodeId>> for WalError<NodeId, EdgeData> {
+    fn from(error: NodeNotFound<NodeId>) -> Self {
+        WalError::Dag(error.into())
+    }
+}
+
+impl<NodeId, EdgeData> Display for WalError<NodeId, EdgeData>
+where
+    NodeId: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalError::Io(error) => writeln!(f, "Cannot access the log: {}", error),
+            WalError::Encoding(error) => writeln!(f, "Cannot encode or decode the log: {}", error),
+            WalError::InvalidRecord(offset) => {
+                writeln!(
+                    f,
+                    "Cannot apply the record of the log at offset {}.",
+                    offset
+                )
+            }
+            WalError::Dag(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<NodeId, EdgeData> std::error::Error for WalError<NodeId, EdgeData>
+where
+    NodeId: std::fmt::Debug + Display,
+    EdgeData: std::fmt::Debug,
+{
+}
+
+/// A logged mutation, the order of variants is a part of the format
+#[derive(Serialize)]
+enum RecordRef<'a, NodeId, NodeData, EdgeData> {
+    InsertNode(&'a NodeId, &'a NodeData),
+    InsertEdge(&'a NodeId, &'a NodeId, &'a EdgeData),
+    RemoveEdge(&'a NodeId, &'a NodeId),
+    RemoveNode(&'a NodeId),
+    Clear,
+}
+
+#[derive(Deserialize)]
+enum Record<NodeId, NodeData, EdgeData> {
+    InsertNode(NodeId, NodeData),
+    InsertEdge(NodeId, NodeId, EdgeData),
+    RemoveEdge(NodeId, NodeId),
+    RemoveNode(NodeId),
+    Clear,
+}
+
+/// A `Dag` which logs every mutation to a file before applying it
+/// # Remarks
+/// * Enabled by feature `wal`, opened by [`WalDag::open`], reads go to `Dag` by `Deref`
+/// * Only valid mutations are logged, so replaying the log never fails
+/// * A checkpoint writes a snapshot next to the log, at `path` with `.snapshot` appended,
+///   then truncates the log
+/// * The log is recovered by [`Dag::recover`], a record torn by a crash is dropped
+/// * The log is a sequence of records, a `u32` little-endian length and a postcard
+///   encoded mutation each, after the epoch of the snapshot it follows
+#[derive(Debug)]
+pub struct WalDag<NodeId, NodeData, EdgeData> {
+    dag: Dag<NodeId, NodeData, EdgeData>,
+    path: PathBuf,
+    log: File,
+    epoch: u64,
+    records_len: usize,
+    options: WalOptions,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize + DeserializeOwned,
+    NodeData: Serialize + DeserializeOwned,
+    EdgeData: Serialize + DeserializeOwned,
+{
+    /// Recover `Dag` from the snapshot and the log at `path` written by [`WalDag`]
+    /// # Remarks
+    /// * Missing files are treated as empty, files are NOT modified
+    /// # Errors
+    /// * `Err(Io(_))` when the files can't be read
+    /// * `Err(Encoding(_))` when the snapshot or a complete record is invalid
+    /// * `Err(InvalidRecord(offset))` when a record can't be applied
+    pub fn recover<P: AsRef<Path>>(path: P) -> Result<Self, WalError<NodeId, EdgeData>> {
+        Ok(recover(path.as_ref())?.0)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> WalDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord + Serialize + DeserializeOwned,
+    NodeData: Serialize + DeserializeOwned,
+    EdgeData: Serialize + DeserializeOwned,
+{
+    /// Open the log at `path` with default options, see [`WalDag::open_with`]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WalError<NodeId, EdgeData>> {
+        Self::open_with(path, WalOptions::default())
+    }
+
+    /// Open the log at `path`, recovering `Dag` from it
+    /// # Remarks
+    /// * The files are created if missing, a torn record at the end of the log is cut
+    /// # Errors
+    /// * Same as [`Dag::recover`]
+    pub fn open_with<P: AsRef<Path>>(
+        path: P,
+        options: WalOptions,
+    ) -> Result<Self, WalError<NodeId, EdgeData>> {
+        let path = path.as_ref();
+        let (dag, recovered) = recover(path)?;
+        let mut log = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match recovered.log_len {
+            Some(len) => log.set_len(len)?,
+            // the log is missing or stale, start it again
+            None => {
+                log.set_len(0)?;
+                log.write_all(&recovered.epoch.to_le_bytes())?;
+            }
+        }
+        log.seek(SeekFrom::End(0))?;
+        Ok(WalDag {
+            dag,
+            path: path.to_path_buf(),
+            log,
+            epoch: recovered.epoch,
+            records_len: recovered.records_len,
+            options,
+        })
+    }
+
+    /// Get the `Dag` back, the files are kept
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData> {
+        self.dag
+    }
+
+    /// Get the count of records in the log since the last checkpoint
+    pub fn records_len(&self) -> usize {
+        self.records_len
+    }
+
+    /// Insert a node with data, see [`Dag::insert_node`]
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the mutation can't be logged,
+    ///   `WalDag` is unchanged
+    pub fn insert_node(
+        &mut self,
+        node_id: NodeId,
+        node_data: NodeData,
+    ) -> Result<Option<NodeData>, WalError<NodeId, EdgeData>> {
+        self.log(RecordRef::InsertNode(&node_id, &node_data))?;
+        Ok(self.dag.insert_node(node_id, node_data))
+    }
+
+    /// Insert an edge with data, see [`Dag::insert_edge`]
+    /// # Errors
+    /// * `Err(Dag(_))` when the edge is invalid, nothing is logged
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the mutation can't be logged,
+    ///   `WalDag` is unchanged
+    pub fn insert_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        edge_data: EdgeData,
+    ) -> Result<Option<EdgeData>, WalError<NodeId, EdgeData>> {
+        if self.dag.would_create_cycle(&from, &to)? {
+            // it fails with the path of the cycle, without mutating
+            self.dag.insert_edge(from, to, edge_data)?;
+            unreachable!("would_create_cycle detected a cycle")
+        }
+        self.log(RecordRef::InsertEdge(&from, &to, &edge_data))?;
+        Ok(self
+            .dag
+            .insert_edge(from, to, edge_data)
+            .unwrap_or_else(|_| unreachable!("proved by would_create_cycle")))
+    }
+
+    /// Remove an edge, see [`Dag::remove_edge`]
+    /// # Remarks
+    /// * Nothing is logged when there is no such edge
+    /// # Errors
+    /// * `Err(Dag(NodeNotFound(id)))` when `from` or `to` is NOT found in `WalDag`
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the mutation can't be logged,
+    ///   `WalDag` is unchanged
+    pub fn remove_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+    ) -> Result<Option<EdgeData>, WalError<NodeId, EdgeData>> {
+        if self.dag.get_edge(&from, &to)?.is_none() {
+            return Ok(None);
+        }
+        self.log(RecordRef::RemoveEdge(&from, &to))?;
+        Ok(self.dag.remove_edge(from, to)?)
+    }
+
+    /// Remove a node and all edges related, see [`Dag::remove_node`]
+    /// # Remarks
+    /// * Nothing is logged when `node_id` is NOT found
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the mutation can't be logged,
+    ///   `WalDag` is unchanged
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node(
+        &mut self,
+        node_id: NodeId,
+    ) -> Result<(Option<NodeData>, Vec<(NodeId, NodeId, EdgeData)>), WalError<NodeId, EdgeData>>
+    {
+        if !self.dag.contains_node(&node_id) {
+            return Ok((None, Vec::new()));
+        }
+        self.log(RecordRef::RemoveNode(&node_id))?;
+        Ok(self.dag.remove_node(node_id))
+    }
+
+    /// Remove all nodes and edges
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the mutation can't be logged,
+    ///   `WalDag` is unchanged
+    pub fn clear(&mut self) -> Result<(), WalError<NodeId, EdgeData>> {
+        self.log(RecordRef::Clear)?;
+        self.dag.clear();
+        Ok(())
+    }
+
+    /// Sync the log to the disk
+    /// # Errors
+    /// * `Err(_)` when the log can't be synced
+    pub fn sync(&self) -> io::Result<()> {
+        self.log.sync_data()
+    }
+
+    /// Write a snapshot of `Dag` and truncate the log
+    /// # Remarks
+    /// * The snapshot is written to a temporary file which replaces the old one,
+    ///   a crash at any time keeps a recoverable state
+    /// # Errors
+    /// * `Err(Io(_))` or `Err(Encoding(_))` when the snapshot can't be written
+    pub fn checkpoint(&mut self) -> Result<(), WalError<NodeId, EdgeData>> {
+        let epoch = self.epoch + 1;
+        let mut bytes = epoch.to_le_bytes().to_vec();
+        bytes.extend(self.dag.to_bytes()?);
+        let snapshot_path = with_suffix(&self.path, ".snapshot");
+        let temporary_path = with_suffix(&self.path, ".snapshot.tmp");
+        let mut temporary = File::create(&temporary_path)?;
+        temporary.write_all(&bytes)?;
+        temporary.sync_all()?;
+        fs::rename(&temporary_path, snapshot_path)?;
+        // the log of the older epoch is ignored from now on
+        self.log.set_len(0)?;
+        self.log.seek(SeekFrom::Start(0))?;
+        self.log.write_all(&epoch.to_le_bytes())?;
+        self.log.sync_data()?;
+        self.epoch = epoch;
+        self.records_len = 0;
+        Ok(())
+    }
+
+    /// Append a record to the log, taking a checkpoint before if needed
+    fn log(
+        &mut self,
+        record: RecordRef<'_, NodeId, NodeData, EdgeData>,
+    ) -> Result<(), WalError<NodeId, EdgeData>> {
+        if self.options.checkpoint_every != 0 && self.records_len >= self.options.checkpoint_every {
+            self.checkpoint()?;
+        }
+        let encoded = postcard::to_allocvec(&record)?;
+        let len = u32::try_from(encoded.len())
+            .map_err(|_| WalError::Encoding(postcard::Error::SerializeBufferFull))?;
+        let mut bytes = len.to_le_bytes().to_vec();
+        bytes.extend(encoded);
+        let end = self.log.stream_position()?;
+        if let Err(error) = self.log.write_all(&bytes) {
+            // drop the partial record
+            self.log.set_len(end)?;
+            self.log.seek(SeekFrom::Start(end))?;
+            return Err(error.into());
+        }
+        if self.options.sync {
+            self.log.sync_data()?;
+        }
+        self.records_len += 1;
+        Ok(())
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for WalDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
+
+/// What [`recover`] found besides `Dag`
+struct Recovered {
+    epoch: u64,
+    // bytes of the valid log, `None` when it is missing or older than the snapshot
+    log_len: Option<u64>,
+    records_len: usize,
+}
+
+/// Recover `Dag` from the snapshot and the log at `path`
+#[allow(clippy::type_complexity)]
+fn recover<NodeId, NodeData, EdgeData>(
+    path: &Path,
+) -> Result<(Dag<NodeId, NodeData, EdgeData>, Recovered), WalError<NodeId, EdgeData>>
+where
+    NodeId: Clone + Ord + DeserializeOwned,
+    NodeData: DeserializeOwned,
+    EdgeData: DeserializeOwned,
+{
+    let (epoch, mut dag) = match read_file(&with_suffix(path, ".snapshot"))? {
+        Some(bytes) if bytes.len() >= 8 => {
+            let (epoch, dag) = bytes.split_at(8);
+            (u64_from(epoch), Dag::from_bytes(dag)?)
+        }
+        Some(_) => {
+            return Err(WalError::Encoding(
+                postcard::Error::DeserializeUnexpectedEnd,
+            ))
+        }
+        None => (0, Dag::new()),
+    };
+    let mut recovered = Recovered {
+        epoch,
+        log_len: None,
+        records_len: 0,
+    };
+    let log = match read_file(path)? {
+        Some(log) if log.len() >= 8 && u64_from(&log[..8]) == epoch => log,
+        // a log older than the snapshot was checkpointed already
+        _ => return Ok((dag, recovered)),
+    };
+    let mut offset = 8;
+    // a record torn by a crash is at the end
+    while let Some(len) = log.get(offset..offset + 4) {
+        let len = u32::from_le_bytes(len.try_into().unwrap_or_else(|_| unreachable!())) as usize;
+        let Some(bytes) = log.get(offset + 4..offset + 4 + len) else {
+            break;
+        };
+        let applied = match postcard::from_bytes(bytes)? {
+            Record::InsertNode(node_id, data) => {
+                dag.insert_node(node_id, data);
+                true
+            }
+            Record::InsertEdge(from, to, data) => dag.insert_edge(from, to, data).is_ok(),
+            Record::RemoveEdge(from, to) => dag.remove_edge(from, to).is_ok(),
+            Record::RemoveNode(node_id) => dag.remove_node(node_id).0.is_some(),
+            Record::Clear => {
+                dag.clear();
+                true
+            }
+        };
+        if !applied {
+            return Err(WalError::InvalidRecord(offset as u64));
+        }
+        offset += 4 + len;
+        recovered.records_len += 1;
+    }
+    recovered.log_len = Some(offset as u64);
+    Ok((dag, recovered))
+}
+
+/// Read a whole file, `None` if it is missing
+fn read_file(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match File::open(path) {
+        Ok(mut file) => {
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            Ok(Some(bytes))
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn u64_from(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(
+        bytes[..8]
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("sliced to 8 bytes")),
+    )
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(suffix);
+    PathBuf::from(path)
+}
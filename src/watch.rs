@@ -0,0 +1,99 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+
+use crate::{Dag, DagSnapshot};
+
+/// A `Dag` with a single writer which publishes snapshots to many readers
+/// # Remarks
+/// * Created by [`Dag::into_watch`], enabled by feature `watch`
+/// * The writer reads and mutates its own copy by `Deref` and `DerefMut`,
+///   readers see nothing until [`WatchDag::publish`]
+/// * Readers are created by [`WatchDag::reader`], loading the latest snapshot is wait-free
+/// * The first mutation after a publish clones the graph once if the snapshot is still
+///   alive, so mutating needs `NodeData: Clone` and `EdgeData: Clone`.
+///   Batch mutations between publishes
+#[derive(Debug)]
+pub struct WatchDag<NodeId, NodeData, EdgeData> {
+    dag: Arc<Dag<NodeId, NodeData, EdgeData>>,
+    published: Arc<ArcSwap<Dag<NodeId, NodeData, EdgeData>>>,
+}
+
+/// A reader of [`WatchDag`], which can be cloned and sent to other threads
+#[derive(Debug, Clone)]
+pub struct DagReader<NodeId, NodeData, EdgeData> {
+    published: Arc<ArcSwap<Dag<NodeId, NodeData, EdgeData>>>,
+}
+
+impl<NodeId, NodeData, EdgeData> Dag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Turn `Dag` into a [`WatchDag`], the current graph is published
+    pub fn into_watch(self) -> WatchDag<NodeId, NodeData, EdgeData> {
+        let dag = Arc::new(self);
+        WatchDag {
+            published: Arc::new(ArcSwap::new(dag.clone())),
+            dag,
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> WatchDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone + Ord,
+{
+    /// Publish the current graph to all the readers, it is O(1)
+    pub fn publish(&self) {
+        self.published.store(self.dag.clone());
+    }
+
+    /// Create a reader of the published snapshots
+    pub fn reader(&self) -> DagReader<NodeId, NodeData, EdgeData> {
+        DagReader {
+            published: self.published.clone(),
+        }
+    }
+
+    /// Get the `Dag` back, it is cloned only if some snapshots are still alive
+    pub fn into_inner(self) -> Dag<NodeId, NodeData, EdgeData>
+    where
+        NodeData: Clone,
+        EdgeData: Clone,
+    {
+        // the published one may share the graph
+        drop(self.published);
+        Arc::unwrap_or_clone(self.dag)
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> DagReader<NodeId, NodeData, EdgeData> {
+    /// Load the latest published snapshot, it is wait-free
+    pub fn load(&self) -> DagSnapshot<NodeId, NodeData, EdgeData> {
+        DagSnapshot {
+            dag: self.published.load_full(),
+        }
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> Deref for WatchDag<NodeId, NodeData, EdgeData> {
+    type Target = Dag<NodeId, NodeData, EdgeData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dag
+    }
+}
+
+impl<NodeId, NodeData, EdgeData> DerefMut for WatchDag<NodeId, NodeData, EdgeData>
+where
+    NodeId: Clone,
+    NodeData: Clone,
+    EdgeData: Clone,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Arc::make_mut(&mut self.dag)
+    }
+}